@@ -1,37 +1,125 @@
 use std::collections::VecDeque;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 
 /// Anomaly detection result - ye struct anomaly detection result store karta hai
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AnomalyResult {
     pub is_anomaly: bool,
-    pub anomaly_type: String, // "sudden_slow", "unusual_pattern", "spike"
+    pub anomaly_type: String, // "sudden_slow", "sudden_fast", "unusual_pattern", "spike", "recovered"
     pub severity: f64, // 0.0 to 1.0
     pub description: String,
     pub baseline_value: f64,
     pub current_value: f64,
 }
 
+/// Baseline kaise nikalna hai - Median single spikes se robust hai, Ewma sustained shifts pe
+/// jaldi react karta hai (alpha jitna bada, utna recent values ka weight zyada)
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+pub enum BaselineMode {
+    #[default]
+    Median,
+    Ewma { alpha: f64 },
+}
+
 /// Detects anomalies in query performance - ye class anomalies detect karta hai
 pub struct AnomalyDetector {
     execution_times: VecDeque<u64>,
     max_history: usize,
     baseline_threshold: f64,
+    baseline_mode: BaselineMode,
+    ewma: Option<f64>, // Ewma mode mein har add_execution_time pe update hota hai
+    active_anomaly: Option<String>, // observe() ka state - chal rahe anomaly ka type, None = normal
+    normal_streak: usize, // anomaly ke dauraan lagatar normal samples
+}
+
+// Itne lagatar normal samples ke baad hi recovery - trend window ke edge pe flapping se bachne ke liye
+const RECOVERY_SAMPLES: usize = 5;
+
+impl Default for AnomalyDetector {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AnomalyDetector {
+    /// Median baseline ke saath
     pub fn new() -> Self {
+        Self::with_baseline_mode(BaselineMode::default())
+    }
+    
+    pub fn with_baseline_mode(baseline_mode: BaselineMode) -> Self {
+        let baseline_mode = match baseline_mode {
+            BaselineMode::Ewma { alpha } => BaselineMode::Ewma { alpha: alpha.clamp(0.01, 1.0) },
+            median => median,
+        };
+
         Self {
             execution_times: VecDeque::new(),
             max_history: 100, // Keep last 100 queries for analysis
             baseline_threshold: 2.0, // 2x baseline is considered anomaly
+            baseline_mode,
+            ewma: None,
+            active_anomaly: None,
+            normal_streak: 0,
         }
     }
     
+    /// Sample add + detect ek saath, lekin sirf state transitions report karta hai - normal se
+    /// anomalous hone pe anomaly, RECOVERY_SAMPLES lagatar normal samples ke baad "recovered".
+    /// Sustained incident ke dauraan har query pe alert nahi aata. Sample detect ke baad add hota
+    /// hai taaki baseline mein na gine
+    pub fn observe(&mut self, execution_time: u64) -> Option<AnomalyResult> {
+        let result = self.detect_anomaly(execution_time);
+        self.add_execution_time(execution_time);
+        
+        match (&self.active_anomaly, result.is_anomaly) {
+            (None, true) => {
+                self.active_anomaly = Some(result.anomaly_type.clone());
+                self.normal_streak = 0;
+                Some(result)
+            }
+            (Some(_), true) => {
+                self.normal_streak = 0;
+                None
+            }
+            (Some(_), false) => {
+                self.normal_streak += 1;
+                if self.normal_streak < RECOVERY_SAMPLES {
+                    return None;
+                }
+                self.normal_streak = 0;
+                let previous = self.active_anomaly.take().unwrap_or_default();
+                Some(AnomalyResult {
+                    is_anomaly: false,
+                    anomaly_type: "recovered".to_string(),
+                    severity: 0.0,
+                    description: format!("Query performance back within normal range after {}", previous),
+                    baseline_value: result.baseline_value,
+                    current_value: result.current_value,
+                })
+            }
+            (None, false) => None,
+        }
+    }
+    
+    /// observe() ke hisaab se abhi anomaly chal raha hai ya nahi
+    pub fn in_anomaly(&self) -> bool {
+        self.active_anomaly.is_some()
+    }
+    
     /// Add query execution time for analysis - ye method query execution time add karta hai
     pub fn add_execution_time(&mut self, execution_time: u64) {
         self.execution_times.push_back(execution_time);
         
+        if let BaselineMode::Ewma { alpha } = self.baseline_mode {
+            let value = execution_time as f64;
+            self.ewma = Some(match self.ewma {
+                Some(previous) => alpha * value + (1.0 - alpha) * previous,
+                None => value,
+            });
+        }
+        
         // Keep only recent history - ye recent history maintain karta hai
         if self.execution_times.len() > self.max_history {
             self.execution_times.pop_front();
@@ -70,6 +158,23 @@ impl AnomalyDetector {
             };
         }
         
+        // Check for sudden speedup - (baseline - current) / current, symmetric to the slow case.
+        // Empty results ya warm cache ka signal ho sakta hai
+        let speedup = (baseline - current_time as f64) / (current_time as f64).max(1.0);
+        if baseline > 0.0 && speedup > self.baseline_threshold {
+            return AnomalyResult {
+                is_anomaly: true,
+                anomaly_type: "sudden_fast".to_string(),
+                severity: (speedup / self.baseline_threshold).min(1.0),
+                description: format!(
+                    "Query execution time dropped by {:.1}% from baseline - check for empty results or cache effects",
+                    (baseline - current_time as f64) / baseline * 100.0
+                ),
+                baseline_value: baseline,
+                current_value: current_time as f64,
+            };
+        }
+
         // Check for unusual patterns - ye unusual patterns check karta hai
         if self.detect_unusual_pattern(current_time) {
             return AnomalyResult {
@@ -94,32 +199,40 @@ impl AnomalyDetector {
     }
     
     fn calculate_baseline(&self) -> f64 {
+        if let (BaselineMode::Ewma { .. }, Some(ewma)) = (self.baseline_mode, self.ewma) {
+            return ewma;
+        }
+        
         // Calculate baseline using median - ye median se baseline calculate karta hai
         let mut times: Vec<u64> = self.execution_times.iter().cloned().collect();
         times.sort();
         
         let len = times.len();
-        if len % 2 == 0 {
+        if len.is_multiple_of(2) {
             (times[len / 2 - 1] + times[len / 2]) as f64 / 2.0
         } else {
             times[len / 2] as f64
         }
     }
     
-    fn detect_unusual_pattern(&self, current_time: u64) -> bool {
+    fn detect_unusual_pattern(&self, _current_time: u64) -> bool {
         // Simple pattern detection - ye basic pattern detection hai
+        // Windows poori na bhari ho (15 se kam samples) to actual count se divide - warna avg kam aata
+        // hai aur flat workload bhi "unusual" lagta hai
+        let recent_count = self.execution_times.len().min(5);
         let recent_avg: f64 = self.execution_times.iter()
             .rev()
             .take(5)
             .map(|&t| t as f64)
-            .sum::<f64>() / 5.0;
+            .sum::<f64>() / recent_count.max(1) as f64;
             
+        let older_count = self.execution_times.len().saturating_sub(5).min(10);
         let older_avg: f64 = self.execution_times.iter()
             .rev()
             .skip(5)
             .take(10)
             .map(|&t| t as f64)
-            .sum::<f64>() / 10.0;
+            .sum::<f64>() / older_count.max(1) as f64;
             
         // Check for significant change in trend - ye trend change check karta hai
         if older_avg > 0.0 {
@@ -0,0 +1,55 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Time source - analyzers direct SystemTime::now() ki jagah isse time padhte hai,
+/// taaki tests fake clock inject kar sake. Send + Sync - analyzers threads ke beech move hote hai
+pub trait Clock: Send + Sync {
+    /// Current unix timestamp in seconds
+    fn now_secs(&self) -> u64;
+}
+
+/// Real wall clock - default everywhere
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_secs(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+}
+
+/// Hand-driven clock for deterministic tests - Arc mein share karke inject ke baad bhi advance kar sakte hai
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: AtomicU64,
+}
+
+impl ManualClock {
+    pub fn new(now_secs: u64) -> Self {
+        Self { now: AtomicU64::new(now_secs) }
+    }
+
+    pub fn set(&self, now_secs: u64) {
+        self.now.store(now_secs, Ordering::Relaxed);
+    }
+
+    pub fn advance(&self, secs: u64) {
+        self.now.fetch_add(secs, Ordering::Relaxed);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_secs(&self) -> u64 {
+        self.now.load(Ordering::Relaxed)
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now_secs(&self) -> u64 {
+        (**self).now_secs()
+    }
+}
@@ -1,7 +1,14 @@
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// get_ranked_indexing_candidates ke weights - WHERE filter sabse zyada index se fayda leta hai
+const WHERE_WEIGHT: f64 = 3.0;
+const JOIN_WEIGHT: f64 = 2.0;
+const ORDER_BY_WEIGHT: f64 = 1.0;
+const GROUP_BY_WEIGHT: f64 = 1.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ColumnUsage {
     pub column_name: String,
     pub table_name: String,
@@ -9,6 +16,16 @@ pub struct ColumnUsage {
     pub in_where_clause: u64,
     pub in_join_condition: u64,
     pub in_order_by: u64,
+    #[serde(default)]
+    pub in_group_by: u64,
+    #[serde(default)]
+    pub equality_predicates: u64, // =, IN
+    #[serde(default)]
+    pub range_predicates: u64, // <, >, BETWEEN, prefix LIKE
+    #[serde(default)]
+    pub full_text_predicates: u64, // @@, @>
+    #[serde(default)]
+    pub pattern_match_predicates: u64, // LIKE '%x%' - sirf trigram GIN kaam aata hai
     pub avg_query_time: f64,
 }
 
@@ -21,6 +38,11 @@ impl ColumnUsage {
             in_where_clause: 0,
             in_join_condition: 0,
             in_order_by: 0,
+            in_group_by: 0,
+            equality_predicates: 0,
+            range_predicates: 0,
+            full_text_predicates: 0,
+            pattern_match_predicates: 0,
             avg_query_time: 0.0,
         }
     }
@@ -30,6 +52,33 @@ pub struct ColumnTracker {
     column_stats: HashMap<String, ColumnUsage>,
 }
 
+/// Predicate kind a column was used with - index type choose karne ke liye
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PredicateKind {
+    Equality,
+    Range,
+    FullText,
+    PatternMatch, // leading-wildcard LIKE / ILIKE
+}
+
+impl ColumnUsage {
+    /// Sirf "=" / IN lookups - hash index isi case mein kaam ka hai
+    pub fn is_equality_only(&self) -> bool {
+        self.range_predicates == 0
+            && self.full_text_predicates == 0
+            && self.pattern_match_predicates == 0
+            && self.in_order_by == 0
+            && self.in_group_by == 0
+            && (self.equality_predicates > 0 || self.in_join_condition > 0)
+    }
+}
+
+impl Default for ColumnTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ColumnTracker {
     pub fn new() -> Self {
         Self {
@@ -40,42 +89,112 @@ impl ColumnTracker {
     //yaha pe badme column statistics bhi add karna ha
     pub fn track_usage(&mut self, parsed_query: &crate::analyzer::query_parser::ParsedQuery, execution_time: u64) {
         for where_clause in &parsed_query.where_clauses {
-            if let Some(column) = self.extract_column_from_condition(where_clause) {
-                self.update_column_stats(&column, "WHERE", execution_time);
-            }
+            if let Some((column, kind)) = Self::extract_column_from_condition(where_clause)
+                && let Some(column) = Self::qualify_column(parsed_query, &column) {
+                    self.update_column_stats(&column, "WHERE", execution_time);
+                    self.record_predicate(&column, kind);
+                }
         }
         
         for join_clause in &parsed_query.join_conditions {
-            if let Some(column) = self.extract_column_from_condition(join_clause) {
-                self.update_column_stats(&column, "JOIN", execution_time);
-            }
+            if let Some((column, _)) = Self::extract_column_from_condition(join_clause)
+                && let Some(column) = Self::qualify_column(parsed_query, &column) {
+                    self.update_column_stats(&column, "JOIN", execution_time);
+                }
         }
         
         for column in &parsed_query.order_by_columns {
-            self.update_column_stats(column, "ORDER_BY", execution_time);
+            // "created_at DESC" -> "created_at"
+            let column = column.split_whitespace().next().unwrap_or("");
+            if let Some(column) = Self::qualify_column(parsed_query, column) {
+                self.update_column_stats(&column, "ORDER_BY", execution_time);
+            }
+        }
+        
+        for column in &parsed_query.group_by_columns {
+            if let Some(column) = Self::qualify_column(parsed_query, column) {
+                self.update_column_stats(&column, "GROUP_BY", execution_time);
+            }
         }
     }
     
-    fn extract_column_from_condition(&self, condition: &str) -> Option<String> {
-        if let Some(equals_pos) = condition.find('=') {
-            let left_side = &condition[..equals_pos].trim();
-            if left_side.contains('.') {
-                return Some(left_side.to_string());
-            }
+    // Alias -> base table ("u.id" -> "users.id"); bare column sirf single-table query mein resolve hota hai.
+    // Multi-table query ka bare column ambiguous hai - None
+    fn qualify_column(parsed_query: &crate::analyzer::query_parser::ParsedQuery, column: &str) -> Option<String> {
+        if column.is_empty() {
+            return None;
+        }
+        if column.contains('.') {
+            return Some(parsed_query.resolve_column(column));
+        }
+        
+        let mut tables: Vec<&String> = parsed_query.from_tables.iter().collect();
+        tables.dedup();
+        match tables.as_slice() {
+            [table] => Some(format!("{}.{}", table, column)),
+            _ => None,
+        }
+    }
+    
+    // "col <op> value" -> (col, predicate kind); operator order matters (>= before =)
+    pub(crate) fn extract_column_from_condition(condition: &str) -> Option<(String, PredicateKind)> {
+        let upper = condition.to_uppercase();
+        let operators = [
+            ("@@", PredicateKind::FullText),
+            ("@>", PredicateKind::FullText),
+            (">=", PredicateKind::Range),
+            ("<=", PredicateKind::Range),
+            ("<>", PredicateKind::Range),
+            ("!=", PredicateKind::Range),
+            ("=", PredicateKind::Equality),
+            (">", PredicateKind::Range),
+            ("<", PredicateKind::Range),
+            (" NOT IN ", PredicateKind::Range),
+            (" IN ", PredicateKind::Equality),
+            (" BETWEEN ", PredicateKind::Range),
+            (" ILIKE ", PredicateKind::Range),
+            (" LIKE ", PredicateKind::Range),
+        ];
+        
+        let (position, operator, mut kind) = operators
+            .iter()
+            .filter_map(|(op, kind)| upper.find(op).map(|pos| (pos, *op, *kind)))
+            .min_by_key(|(pos, op, _)| (*pos, std::cmp::Reverse(op.len())))?;
+        
+        // LIKE '%foo%' prefix index use nahi kar sakta - trigram GIN chahiye
+        if operator.contains("LIKE") && upper[position + operator.len()..].trim_start().starts_with("'%") {
+            kind = PredicateKind::PatternMatch;
+        }
+        
+        let left_side = condition[..position].trim().trim_start_matches('(');
+        let is_identifier = left_side.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '.');
+        if !left_side.is_empty() && is_identifier && !left_side.starts_with(|c: char| c.is_ascii_digit()) {
+            return Some((left_side.to_string(), kind));
         }
         None
     }
     
+    fn record_predicate(&mut self, column: &str, kind: PredicateKind) {
+        if let Some(entry) = self.column_stats.get_mut(column) {
+            match kind {
+                PredicateKind::Equality => entry.equality_predicates += 1,
+                PredicateKind::Range => entry.range_predicates += 1,
+                PredicateKind::FullText => entry.full_text_predicates += 1,
+                PredicateKind::PatternMatch => entry.pattern_match_predicates += 1,
+            }
+        }
+    }
+    
+    pub fn get_column_usage(&self, table: &str, column: &str) -> Option<&ColumnUsage> {
+        self.column_stats.get(&format!("{}.{}", table, column))
+    }
+    
     fn update_column_stats(&mut self, column: &str, usage_type: &str, execution_time: u64) {
         let key = column.to_string();
         let entry = self.column_stats.entry(key.clone()).or_insert_with(|| {
-            let (table, col) = if column.contains('.') {
-                let parts: Vec<&str> = column.split('.').collect();
-                (parts[0].to_string(), parts[1].to_string())
-            } else {
-                ("unknown".to_string(), column.to_string())
-            };
-            ColumnUsage::new(col, table)
+            // track_usage hamesha "table.column" pass karta hai
+            let (table, col) = column.split_once('.').unwrap_or(("unknown", column));
+            ColumnUsage::new(col.to_string(), table.to_string())
         });
         
         entry.usage_count += 1;
@@ -83,6 +202,7 @@ impl ColumnTracker {
             "WHERE" => entry.in_where_clause += 1,
             "JOIN" => entry.in_join_condition += 1,
             "ORDER_BY" => entry.in_order_by += 1,
+            "GROUP_BY" => entry.in_group_by += 1,
             _ => {}
         }
         
@@ -90,18 +210,62 @@ impl ColumnTracker {
         entry.avg_query_time = total_time / entry.usage_count as f64;
     }
     
+    pub fn get_table_columns(&self, table: &str) -> Vec<&ColumnUsage> {
+        let mut columns: Vec<&ColumnUsage> = self
+            .column_stats
+            .values()
+            .filter(|col| col.table_name == table)
+            .collect();
+        columns.sort_by(|a, b| b.usage_count.cmp(&a.usage_count).then(a.column_name.cmp(&b.column_name)));
+        columns
+    }
+    
     pub fn get_most_used_columns(&self, limit: usize) -> Vec<&ColumnUsage> {
         let mut columns: Vec<&ColumnUsage> = self.column_stats.values().collect();
-        columns.sort_by(|a, b| b.usage_count.cmp(&a.usage_count));
+        columns.sort_by(|a, b| {
+            b.usage_count
+                .cmp(&a.usage_count)
+                .then_with(|| (&a.table_name, &a.column_name).cmp(&(&b.table_name, &b.column_name)))
+        });
         columns.into_iter().take(limit).collect()
     }
     
     pub fn get_indexing_candidates(&self) -> Vec<&ColumnUsage> {
-        self.column_stats
+        let mut candidates: Vec<&ColumnUsage> = self.column_stats
             .values()
-            .filter(|col| col.in_where_clause > 0 || col.in_join_condition > 0)
+            .filter(|col| col.in_where_clause > 0 || col.in_join_condition > 0 || col.in_group_by > 0)
             .filter(|col| col.usage_count >= 5)
-            .collect()
+            .collect();
+        // HashMap order har run alag hota hai - table/column se sort karke output stable
+        candidates.sort_by(|a, b| (&a.table_name, &a.column_name).cmp(&(&b.table_name, &b.column_name)));
+        candidates
+    }
+    
+    /// Candidates sorted by composite score (highest first), score ke saath.
+    /// score = (3*WHERE + 2*JOIN + 1*ORDER BY + 1*GROUP BY) * (1 + avg_query_time / 100) - slow queries ke columns upar
+    pub fn get_ranked_indexing_candidates(&self, limit: usize) -> Vec<(&ColumnUsage, f64)> {
+        let mut ranked: Vec<(&ColumnUsage, f64)> = self
+            .get_indexing_candidates()
+            .into_iter()
+            .map(|col| (col, Self::candidate_score(col)))
+            .collect();
+        
+        ranked.sort_by(|(a, a_score), (b, b_score)| {
+            b_score
+                .partial_cmp(a_score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| (&a.table_name, &a.column_name).cmp(&(&b.table_name, &b.column_name)))
+        });
+        ranked.truncate(limit);
+        ranked
+    }
+    
+    fn candidate_score(usage: &ColumnUsage) -> f64 {
+        let weighted_usage = usage.in_where_clause as f64 * WHERE_WEIGHT
+            + usage.in_join_condition as f64 * JOIN_WEIGHT
+            + usage.in_order_by as f64 * ORDER_BY_WEIGHT
+            + usage.in_group_by as f64 * GROUP_BY_WEIGHT;
+        weighted_usage * (1.0 + usage.avg_query_time.max(0.0) / 100.0)
     }
     
     //yaha pe badme column correlation bhi add karna ha
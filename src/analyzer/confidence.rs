@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+/// Confidence score with explanation - score ke saath wo factors jinse score bana, UI mein dikhane ke liye
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Confidence {
+    pub score: f64, // 0.0 to 1.0
+    pub factors: Vec<String>,
+}
+
+impl Confidence {
+    pub fn new(score: f64, factors: Vec<String>) -> Self {
+        Self {
+            score: score.clamp(0.0, 1.0),
+            factors,
+        }
+    }
+}
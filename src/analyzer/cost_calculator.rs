@@ -1,14 +1,31 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::analyzer::{percent_improvement, ExistingIndex, ExistingIndexChecker, IndexMaintenanceCostAnalyzer, JoinClause};
 
 /// Query cost calculation - ye struct query cost calculate karta hai
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct QueryCost {
     pub base_cost: f64,
     pub row_scan_cost: f64,
     pub join_cost: f64,
     pub sort_cost: f64,
+    pub write_cost: f64, // 0 for reads
     pub total_cost: f64,
     pub cost_category: String, // "low", "medium", "high"
+    #[serde(default)]
+    pub join_breakdown: Vec<String>, // per-join type aur fan-out factor, structured joins se
+}
+
+/// Budget breach details - CI gate ke liye, kaunsa component sabse zyada cost kar raha hai
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BudgetBreach {
+    pub budget: f64,
+    pub total_cost: f64,
+    pub overage: f64,
+    pub overage_percent: f64,
+    pub dominant_component: String, // "scan", "join", "sort", "write"
+    pub dominant_component_cost: f64,
+    pub suggestion: String,
 }
 
 /// Calculates query execution cost - ye class query cost calculate karta hai
@@ -16,6 +33,14 @@ pub struct CostCalculator {
     base_row_cost: f64,
     join_multiplier: f64,
     sort_multiplier: f64,
+    maintenance_analyzer: IndexMaintenanceCostAnalyzer,
+    existing_indexes: ExistingIndexChecker, // unique keys se join fan-out estimate hota hai
+}
+
+impl Default for CostCalculator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl CostCalculator {
@@ -24,9 +49,84 @@ impl CostCalculator {
             base_row_cost: 0.001, // Base cost per row
             join_multiplier: 1.5,  // Join complexity multiplier
             sort_multiplier: 2.0, // Sort complexity multiplier
+            maintenance_analyzer: IndexMaintenanceCostAnalyzer::new(),
+            existing_indexes: ExistingIndexChecker::new(),
         }
     }
     
+    /// Create calculator with custom weights - tuned profile load karne ke liye
+    pub fn with_weights(base_row_cost: f64, join_multiplier: f64, sort_multiplier: f64) -> Self {
+        Self {
+            base_row_cost,
+            join_multiplier,
+            sort_multiplier,
+            maintenance_analyzer: IndexMaintenanceCostAnalyzer::new(),
+            existing_indexes: ExistingIndexChecker::new(),
+        }
+    }
+    
+    /// Schema ka index register karta hai - unique keys se join fan-out 1x maana jata hai
+    pub fn add_existing_index(&mut self, index: ExistingIndex) {
+        self.existing_indexes.add_existing_index(index);
+    }
+    
+    /// Postgres preset - hash/merge joins ki wajah se join cost kam hai
+    pub fn postgres_defaults() -> Self {
+        Self::with_weights(0.0012, 1.3, 1.8)
+    }
+    
+    /// MySQL preset - nested-loop joins zyada expensive hote hai
+    pub fn mysql_defaults() -> Self {
+        Self::with_weights(0.001, 1.8, 2.2)
+    }
+    
+    /// SQLite preset - rows sasti hai but joins aur sorts single-threaded hai
+    pub fn sqlite_defaults() -> Self {
+        Self::with_weights(0.0008, 2.5, 2.5)
+    }
+    
+    pub fn base_row_cost(&self) -> f64 {
+        self.base_row_cost
+    }
+    
+    pub fn join_multiplier(&self) -> f64 {
+        self.join_multiplier
+    }
+    
+    pub fn sort_multiplier(&self) -> f64 {
+        self.sort_multiplier
+    }
+    
+    /// Calibrate base_row_cost against observed times - least-squares fit karta hai
+    /// Samples must be costed with the current weights. base_cost is the observed time itself,
+    /// so only the row-driven part (scan + join + sort) is fitted. Returns mean absolute error in ms.
+    pub fn calibrate(&mut self, samples: &[(QueryCost, u64)]) -> f64 {
+        if samples.is_empty() || self.base_row_cost <= 0.0 {
+            return 0.0;
+        }
+        
+        // Row-driven cost is linear in base_row_cost, so k = cost / base_row_cost
+        let points: Vec<(f64, f64)> = samples
+            .iter()
+            .map(|(cost, actual_ms)| {
+                let row_driven = cost.row_scan_cost + cost.join_cost + cost.sort_cost;
+                (row_driven / self.base_row_cost, *actual_ms as f64)
+            })
+            .collect();
+        
+        let sum_kk: f64 = points.iter().map(|(k, _)| k * k).sum();
+        let sum_ky: f64 = points.iter().map(|(k, y)| k * y).sum();
+        
+        if sum_kk > 0.0 {
+            self.base_row_cost = (sum_ky / sum_kk).max(0.0);
+        }
+        
+        points
+            .iter()
+            .map(|(k, y)| (y - k * self.base_row_cost).abs())
+            .sum::<f64>() / points.len() as f64
+    }
+    
     /// Calculate cost for a query - ye method query ka cost calculate karta hai
     pub fn calculate_cost(&self, 
         rows_scanned: u64, 
@@ -35,6 +135,19 @@ impl CostCalculator {
         has_order_by: bool,
         has_group_by: bool) -> QueryCost {
         
+        self.calculate_cost_with_limit(rows_scanned, execution_time, join_count, has_order_by, has_group_by, None)
+    }
+    
+    /// Calculate cost with a row limit - ORDER BY ... LIMIT k top-N heap sort hai, full sort nahi
+    /// `row_limit` = LIMIT + OFFSET (jitni rows sort ke baad chahiye)
+    pub fn calculate_cost_with_limit(&self, 
+        rows_scanned: u64, 
+        execution_time: u64, 
+        join_count: usize, 
+        has_order_by: bool,
+        has_group_by: bool,
+        row_limit: Option<u64>) -> QueryCost {
+        
         // Base cost calculation - ye base cost calculate karta hai
         let base_cost = execution_time as f64;
         
@@ -50,29 +163,78 @@ impl CostCalculator {
         
         // Sort cost calculation - ye sort cost calculate karta hai
         let sort_cost = if has_order_by || has_group_by {
-            row_scan_cost * self.sort_multiplier
+            row_scan_cost * self.sort_multiplier * self.top_n_factor(rows_scanned, has_group_by, row_limit)
         } else {
             0.0
         };
         
         let total_cost = base_cost + row_scan_cost + join_cost + sort_cost;
         
-        // Determine cost category - ye cost category determine karta hai
-        let cost_category = if total_cost < 10.0 {
-            "low".to_string()
-        } else if total_cost < 100.0 {
-            "medium".to_string()
-        } else {
-            "high".to_string()
-        };
-        
         QueryCost {
             base_cost,
             row_scan_cost,
             join_cost,
             sort_cost,
+            write_cost: 0.0,
             total_cost,
-            cost_category,
+            cost_category: self.determine_category(total_cost),
+            join_breakdown: Vec::new(),
+        }
+    }
+    
+    // Full sort n*log(n) hai, top-k heap n*log(k) - ratio log(k)/log(n). GROUP BY ko saari rows
+    // aggregate karni padti hai, isliye wahan limit se sort sasta nahi hota
+    fn top_n_factor(&self, rows_scanned: u64, has_group_by: bool, row_limit: Option<u64>) -> f64 {
+        match row_limit {
+            Some(limit) if !has_group_by && limit < rows_scanned => {
+                let full = ((rows_scanned + 1) as f64).log2();
+                let top_n = ((limit + 1) as f64).log2();
+                if full > 0.0 { (top_n / full).max(0.05) } else { 1.0 }
+            }
+            _ => 1.0,
+        }
+    }
+    
+    /// Calculate write cost - ye method INSERT/UPDATE/DELETE ka index maintenance cost bhi include karta hai
+    pub fn calculate_write_cost(&self, 
+        statement_type: &str, 
+        rows_affected: u64, 
+        index_count: usize) -> QueryCost {
+        
+        // UPDATE/DELETE ko pehle rows dhoondni padti hai, INSERT ko nahi; UPSERT/MERGE ko conflict check ke liye
+        let row_scan_cost = match statement_type.to_uppercase().as_str() {
+            "UPDATE" | "DELETE" | "UPSERT" | "MERGE" => rows_affected as f64 * self.base_row_cost,
+            _ => 0.0,
+        };
+        
+        let write_cost = self.maintenance_analyzer.estimate_statement_overhead(
+            statement_type,
+            rows_affected,
+            index_count,
+        );
+        
+        let total_cost = row_scan_cost + write_cost;
+        
+        QueryCost {
+            base_cost: 0.0,
+            row_scan_cost,
+            join_cost: 0.0,
+            sort_cost: 0.0,
+            write_cost,
+            total_cost,
+            cost_category: self.determine_category(total_cost),
+            join_breakdown: Vec::new(),
+        }
+    }
+    
+    fn determine_category(&self, total_cost: f64) -> String {
+        // Determine cost category - ye cost category determine karta hai
+        if total_cost < 10.0 {
+            "low".to_string()
+        } else if total_cost < 100.0 {
+            "medium".to_string()
+        } else {
+            "high".to_string()
         }
     }
     
@@ -82,17 +244,81 @@ impl CostCalculator {
         execution_time: u64,
         rows_scanned: u64) -> QueryCost {
         
-        let join_count = parsed_query.join_conditions.len();
+        // Structured joins ho to per-join cost niche lagta hai, flat multiplier nahi
+        let join_count = if parsed_query.joins.is_empty() { parsed_query.join_conditions.len() } else { 0 };
         let has_order_by = !parsed_query.order_by_columns.is_empty();
-        let has_group_by = false; // Would need to detect GROUP BY in parser
+        let has_group_by = !parsed_query.group_by_columns.is_empty();
         
-        self.calculate_cost(
+        // OFFSET wali rows bhi sort hoti hai, bas return nahi hoti
+        let row_limit = parsed_query.limit.map(|limit| limit + parsed_query.offset.unwrap_or(0));
+        
+        let mut cost = self.calculate_cost_with_limit(
             rows_scanned,
             execution_time,
             join_count,
             has_order_by,
-            has_group_by
-        )
+            has_group_by,
+            row_limit
+        );
+        
+        if !parsed_query.joins.is_empty() {
+            let (join_cost, join_breakdown) = self.calculate_join_cost(cost.row_scan_cost, &parsed_query.joins);
+            cost.join_cost = join_cost;
+            cost.total_cost += join_cost;
+            cost.cost_category = self.determine_category(cost.total_cost);
+            cost.join_breakdown = join_breakdown;
+        }
+        
+        cost
+    }
+    
+    /// Per-join cost - join_multiplier ko join type aur estimated fan-out se scale karta hai.
+    /// Returns (total join cost, har join ka breakdown)
+    pub fn calculate_join_cost(&self, row_scan_cost: f64, joins: &[JoinClause]) -> (f64, Vec<String>) {
+        let mut total = 0.0;
+        let mut breakdown = Vec::new();
+        
+        for join in joins {
+            // Outer joins unmatched rows bhi rakhte hai, FULL dono side se; CROSS = cartesian product
+            let type_factor = match join.join_type.as_str() {
+                "LEFT" | "RIGHT" => 1.3,
+                "FULL" => 1.8,
+                "CROSS" => 3.0,
+                _ => 1.0,
+            };
+            let fan_out = self.estimate_fan_out(join);
+            let join_cost = row_scan_cost * self.join_multiplier * type_factor * fan_out;
+            total += join_cost;
+            
+            breakdown.push(format!(
+                "{} JOIN {} -> {}: {:.1}x type, {:.1}x fan-out, cost {:.2}",
+                join.join_type, join.left_table, join.right_table, type_factor, fan_out, join_cost
+            ));
+        }
+        
+        (total, breakdown)
+    }
+    
+    // Cardinality heuristic - kisi side pe unique key ho to many-to-one (1x); dono side ke indexes
+    // pata ho aur koi unique na ho tab one-to-many / many-to-many maan ke rows multiply hoti hai
+    fn estimate_fan_out(&self, join: &JoinClause) -> f64 {
+        if join.on_columns.is_empty() {
+            return 1.0; // CROSS type_factor already cartesian product cover karta hai
+        }
+        let is_key = |column: &str| match column.split_once('.') {
+            Some((table, name)) => self.existing_indexes.is_unique_column(table, name),
+            None => None,
+        };
+        let sides: Vec<Option<bool>> = join
+            .on_columns
+            .iter()
+            .flat_map(|(left, right)| [is_key(left), is_key(right)])
+            .collect();
+        if sides.iter().all(|side| *side == Some(false)) {
+            2.0
+        } else {
+            1.0 // unique key mila, ya cardinality unknown
+        }
     }
     
     /// Get cost optimization suggestions - ye method cost optimization suggestions deta hai
@@ -107,6 +333,9 @@ impl CostCalculator {
         // High join cost suggestions - ye high join cost ke liye suggestions hai
         if cost.join_cost > 100.0 {
             suggestions.push("High join cost detected - review join conditions and consider denormalization".to_string());
+            if !cost.join_breakdown.is_empty() {
+                suggestions.push(format!("Join cost breakdown: {}", cost.join_breakdown.join("; ")));
+            }
         }
         
         // High sort cost suggestions - ye high sort cost ke liye suggestions hai
@@ -114,6 +343,11 @@ impl CostCalculator {
             suggestions.push("High sort cost detected - consider pre-sorted indexes or limit result set".to_string());
         }
         
+        // High write cost suggestions - index maintenance writes ko slow kar raha hai
+        if cost.write_cost > 50.0 {
+            suggestions.push("High write cost detected - review index count on write-heavy tables or batch the writes".to_string());
+        }
+        
         // Overall cost suggestions - ye overall cost ke liye suggestions hai
         if cost.total_cost > 200.0 {
             suggestions.push("Very high query cost - consider query rewriting or caching strategy".to_string());
@@ -124,7 +358,7 @@ impl CostCalculator {
     
     /// Compare costs for optimization - ye method costs compare karta hai optimization ke liye
     pub fn compare_costs(&self, original_cost: &QueryCost, optimized_cost: &QueryCost) -> (f64, String) {
-        let improvement_percent = ((original_cost.total_cost - optimized_cost.total_cost) / original_cost.total_cost) * 100.0;
+        let improvement_percent = percent_improvement(original_cost.total_cost, optimized_cost.total_cost);
         
         let improvement_level = if improvement_percent > 50.0 {
             "Excellent".to_string()
@@ -139,6 +373,38 @@ impl CostCalculator {
         (improvement_percent, improvement_level)
     }
     
+    /// Check cost against a budget - budget cross hone pe dominant component ke saath breach deta hai
+    pub fn check_budget(&self, cost: &QueryCost, budget: f64) -> Option<BudgetBreach> {
+        if cost.total_cost <= budget {
+            return None;
+        }
+        
+        // base_cost observed time hai, fix karne layak component nahi - isliye compare nahi hota
+        let components = [
+            ("scan", cost.row_scan_cost, "Add indexes on WHERE clause columns to reduce rows scanned"),
+            ("join", cost.join_cost, "Index join columns or reduce the number of joined tables"),
+            ("sort", cost.sort_cost, "Use an index matching the ORDER BY / GROUP BY or limit the result set"),
+            ("write", cost.write_cost, "Drop unused indexes on the table or batch the writes"),
+        ];
+        let (component, component_cost, suggestion) = components
+            .iter()
+            .copied()
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))?;
+        
+        let overage = cost.total_cost - budget;
+        let overage_percent = if budget > 0.0 { overage / budget * 100.0 } else { 100.0 };
+        
+        Some(BudgetBreach {
+            budget,
+            total_cost: cost.total_cost,
+            overage,
+            overage_percent,
+            dominant_component: component.to_string(),
+            dominant_component_cost: component_cost,
+            suggestion: suggestion.to_string(),
+        })
+    }
+    
     /// Get cost summary - ye method cost summary deta hai
     pub fn get_cost_summary(&self, costs: &[QueryCost]) -> (f64, f64, usize, usize) {
         if costs.is_empty() {
@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use std::collections::HashMap;
+use crate::analyzer::{QueryLog, QueryParser};
+use crate::recommender::ReasonCode;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DeadlockInfo {
     pub deadlock_id: String,
     pub timestamp: u64,
@@ -11,18 +14,60 @@ pub struct DeadlockInfo {
     pub resolution_time: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DeadlockPrevention {
     pub query_pattern: String,
+    pub tables: Vec<String>,
     pub risk_level: String,
     pub prevention_strategy: String,
     pub recommended_changes: Vec<String>,
+    #[serde(default)]
+    pub reason_code: ReasonCode,
+}
+
+// Recorded lock sequence that acquired two tables against the canonical order
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LockOrderViolation {
+    pub lock_sequence: String,
+    pub acquired_first: String,
+    pub acquired_second: String,
+    pub recommendation: String,
+}
+
+// Transaction isolation level - stricter levels zyada (aur zyada der tak) locks rakhte hai
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+pub enum IsolationLevel {
+    ReadUncommitted,
+    #[default]
+    ReadCommitted,
+    RepeatableRead, // MySQL InnoDB default - gap/next-key locks
+    Serializable,
+}
+
+impl IsolationLevel {
+    // READ COMMITTED baseline; REPEATABLE READ gap locks, SERIALIZABLE predicate locks + serialization failures
+    fn risk_multiplier(&self) -> f64 {
+        match self {
+            IsolationLevel::ReadUncommitted => 0.8,
+            IsolationLevel::ReadCommitted => 1.0,
+            IsolationLevel::RepeatableRead => 1.4,
+            IsolationLevel::Serializable => 1.8,
+        }
+    }
 }
 
 pub struct DeadlockDetector {
     deadlock_history: Vec<DeadlockInfo>,
     query_patterns: HashMap<String, u64>,
     lock_sequences: HashMap<String, Vec<String>>,
+    wait_for_graph: HashMap<String, Vec<(String, String)>>, // waiting txn -> [(holder txn, resource)]
+    isolation_level: IsolationLevel, // recommendations isi level ke hisaab se
+}
+
+impl Default for DeadlockDetector {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl DeadlockDetector {
@@ -31,9 +76,16 @@ impl DeadlockDetector {
             deadlock_history: Vec::new(),
             query_patterns: HashMap::new(),
             lock_sequences: HashMap::new(),
+            wait_for_graph: HashMap::new(),
+            isolation_level: IsolationLevel::default(),
         }
     }
     
+    /// Workload ka transaction isolation level - recommended changes isse padhte hai
+    pub fn set_isolation_level(&mut self, isolation_level: IsolationLevel) {
+        self.isolation_level = isolation_level;
+    }
+    
     //yaha pe badme advanced deadlock analysis bhi add karna ha
     pub fn record_deadlock(&mut self, deadlock: DeadlockInfo) {
         self.deadlock_history.push(deadlock.clone());
@@ -45,37 +97,51 @@ impl DeadlockDetector {
         
         // Track lock sequences
         let sequence_key = deadlock.locked_tables.join("->");
-        self.lock_sequences.entry(sequence_key).or_insert_with(Vec::new)
+        self.lock_sequences.entry(sequence_key).or_default()
             .extend(deadlock.involved_queries.clone());
     }
     
     pub fn analyze_deadlock_patterns(&self) -> Vec<DeadlockPrevention> {
         let mut preventions = Vec::new();
         
-        // Find frequently deadlocking queries
-        for (query, count) in &self.query_patterns {
+        // Find frequently deadlocking queries - most frequent first, ties query text se (stable output)
+        let mut query_patterns: Vec<(&String, &u64)> = self.query_patterns.iter().collect();
+        query_patterns.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        for (query, count) in query_patterns {
             if *count > 1 {
                 preventions.push(DeadlockPrevention {
                     query_pattern: query.clone(),
+                    tables: self.tables_locked_by(query),
                     risk_level: self.determine_risk_level(*count),
                     prevention_strategy: self.suggest_prevention_strategy(query),
                     recommended_changes: self.get_recommended_changes(query),
+                    reason_code: ReasonCode::DeadlockProneQuery,
                 });
             }
         }
         
         // Analyze lock sequences
-        for (sequence, queries) in &self.lock_sequences {
+        let lock_order = self.suggest_lock_order();
+        let violations = self.find_lock_order_violations();
+        let mut lock_sequences: Vec<(&String, &Vec<String>)> = self.lock_sequences.iter().collect();
+        lock_sequences.sort_by(|a, b| a.0.cmp(b.0));
+        for (sequence, queries) in lock_sequences {
             if queries.len() > 1 {
+                let mut recommended_changes: Vec<String> = violations
+                    .iter()
+                    .filter(|v| &v.lock_sequence == sequence)
+                    .map(|v| v.recommendation.clone())
+                    .collect();
+                recommended_changes.push("Use shorter transactions".to_string());
+                recommended_changes.push("Consider lock timeouts".to_string());
+                
                 preventions.push(DeadlockPrevention {
                     query_pattern: format!("Lock sequence: {}", sequence),
+                    tables: sequence.split("->").map(|t| t.to_string()).collect(),
                     risk_level: "High".to_string(),
-                    prevention_strategy: "Standardize lock order".to_string(),
-                    recommended_changes: vec![
-                        "Always acquire locks in the same order".to_string(),
-                        "Use shorter transactions".to_string(),
-                        "Consider lock timeouts".to_string(),
-                    ],
+                    prevention_strategy: format!("Standardize lock order: {}", lock_order.join(" -> ")),
+                    recommended_changes,
+                    reason_code: ReasonCode::LockOrder,
                 });
             }
         }
@@ -83,6 +149,141 @@ impl DeadlockDetector {
         preventions
     }
     
+    // Globally consistent table order jo observed sequences ke saath sabse kam inversions deta hai
+    pub fn suggest_lock_order(&self) -> Vec<String> {
+        // precedence[(a, b)] = kitni baar a ko b se pehle lock kiya gaya
+        let mut precedence: HashMap<(String, String), u64> = HashMap::new();
+        let mut tables: Vec<String> = Vec::new();
+        
+        for deadlock in &self.deadlock_history {
+            for (i, first) in deadlock.locked_tables.iter().enumerate() {
+                if !tables.contains(first) {
+                    tables.push(first.clone());
+                }
+                for second in deadlock.locked_tables.iter().skip(i + 1) {
+                    if first != second {
+                        *precedence.entry((first.clone(), second.clone())).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+        tables.sort();
+        
+        // Greedy feedback-arc heuristic - jis table ka (before - after) sabse zyada ho wo pehle
+        let mut order = Vec::new();
+        while !tables.is_empty() {
+            let score = |table: &String| -> i64 {
+                tables.iter()
+                    .filter(|other| *other != table)
+                    .map(|other| {
+                        let before = precedence.get(&(table.clone(), other.clone())).copied().unwrap_or(0) as i64;
+                        let after = precedence.get(&(other.clone(), table.clone())).copied().unwrap_or(0) as i64;
+                        before - after
+                    })
+                    .sum()
+            };
+            
+            let mut best = 0;
+            for i in 1..tables.len() {
+                if score(&tables[i]) > score(&tables[best]) {
+                    best = i;
+                }
+            }
+            order.push(tables.remove(best));
+        }
+        
+        order
+    }
+    
+    pub fn find_lock_order_violations(&self) -> Vec<LockOrderViolation> {
+        let order = self.suggest_lock_order();
+        let rank = |table: &str| order.iter().position(|t| t == table).unwrap_or(usize::MAX);
+        
+        let mut sequences: Vec<&String> = self.lock_sequences.keys().collect();
+        sequences.sort();
+        
+        let mut violations = Vec::new();
+        for sequence in sequences {
+            let tables: Vec<&str> = sequence.split("->").collect();
+            for (i, first) in tables.iter().enumerate() {
+                for second in tables.iter().skip(i + 1) {
+                    if rank(first) > rank(second) {
+                        violations.push(LockOrderViolation {
+                            lock_sequence: sequence.clone(),
+                            acquired_first: first.to_string(),
+                            acquired_second: second.to_string(),
+                            recommendation: format!("Always lock {} before {}", second, first),
+                        });
+                    }
+                }
+            }
+        }
+        
+        violations
+    }
+    
+    /// Multi-statement transaction mein tables kis order mein lock hote hai - sirf writes
+    /// (aur SELECT ... FOR UPDATE) row locks lete hai, plain reads skip
+    pub fn transaction_lock_order(&self, transaction_sql: &str) -> Vec<String> {
+        let parser = QueryParser;
+        let mut order: Vec<String> = Vec::new();
+        
+        for statement in parser.split_statements(transaction_sql) {
+            let statement_type = QueryLog::classify(&statement);
+            let takes_locks = statement_type != "SELECT" || statement.to_uppercase().contains("FOR UPDATE");
+            if statement_type == "OTHER" || !takes_locks {
+                continue;
+            }
+            
+            for table in parser.parse(&statement).from_tables {
+                if !order.contains(&table) {
+                    order.push(table);
+                }
+            }
+        }
+        
+        order
+    }
+    
+    /// Transaction ka lock order suggest_lock_order ke against check karta hai - deploy se pehle
+    pub fn check_transaction_lock_order(&self, transaction_sql: &str) -> Vec<LockOrderViolation> {
+        let order = self.suggest_lock_order();
+        let rank = |table: &str| order.iter().position(|t| t == table);
+        let tables = self.transaction_lock_order(transaction_sql);
+        let sequence = tables.join("->");
+        
+        let mut violations = Vec::new();
+        for (i, first) in tables.iter().enumerate() {
+            for second in tables.iter().skip(i + 1) {
+                // Jo tables kabhi deadlock mein nahi aaye unka canonical rank nahi hai
+                if let (Some(first_rank), Some(second_rank)) = (rank(first), rank(second))
+                    && first_rank > second_rank {
+                        violations.push(LockOrderViolation {
+                            lock_sequence: sequence.clone(),
+                            acquired_first: first.clone(),
+                            acquired_second: second.clone(),
+                            recommendation: format!("Always lock {} before {}", second, first),
+                        });
+                    }
+            }
+        }
+        
+        violations
+    }
+    
+    // Tables locked in deadlocks this query was part of
+    fn tables_locked_by(&self, query: &str) -> Vec<String> {
+        let mut tables: Vec<String> = Vec::new();
+        for deadlock in self.deadlock_history.iter().filter(|d| d.involved_queries.iter().any(|q| q == query)) {
+            for table in &deadlock.locked_tables {
+                if !tables.contains(table) {
+                    tables.push(table.clone());
+                }
+            }
+        }
+        tables
+    }
+    
     fn determine_risk_level(&self, deadlock_count: u64) -> String {
         if deadlock_count > 5 {
             "Critical".to_string()
@@ -125,7 +326,17 @@ impl DeadlockDetector {
         }
         
         changes.push("Add proper indexes to reduce lock time".to_string());
-        changes.push("Use READ COMMITTED isolation level".to_string());
+        // Already READ COMMITTED ya neeche ho to isolation change ka suggestion bekaar hai
+        match self.isolation_level {
+            IsolationLevel::Serializable => {
+                changes.push("Use READ COMMITTED isolation level unless SERIALIZABLE guarantees are required".to_string());
+                changes.push("Retry transactions on serialization failures".to_string());
+            }
+            IsolationLevel::RepeatableRead => {
+                changes.push("Use READ COMMITTED isolation level to avoid gap locks from REPEATABLE READ".to_string());
+            }
+            IsolationLevel::ReadCommitted | IsolationLevel::ReadUncommitted => {}
+        }
         
         changes
     }
@@ -140,20 +351,25 @@ impl DeadlockDetector {
             0.0
         };
         
-        let trend = if total_deadlocks > 10 {
-            let recent_deadlocks = self.deadlock_history.iter()
-                .rev()
-                .take(5)
-                .count();
-            let older_deadlocks = self.deadlock_history.iter()
-                .rev()
-                .skip(5)
-                .take(5)
-                .count();
+        // Recent 5 vs older 5 ka mean wait_time compare karta hai - 20% se zyada change = trend
+        let trend = if total_deadlocks >= 10 {
+            let mean_wait = |window: Vec<&DeadlockInfo>| {
+                window.iter().map(|d| d.wait_time as f64).sum::<f64>() / window.len() as f64
+            };
+            let recent_wait = mean_wait(self.deadlock_history.iter().rev().take(5).collect());
+            let older_wait = mean_wait(self.deadlock_history.iter().rev().skip(5).take(5).collect());
+            
+            let change = if older_wait > 0.0 {
+                (recent_wait - older_wait) / older_wait
+            } else if recent_wait > 0.0 {
+                1.0
+            } else {
+                0.0
+            };
             
-            if recent_deadlocks > older_deadlocks {
+            if change > 0.2 {
                 "increasing".to_string()
-            } else if recent_deadlocks < older_deadlocks {
+            } else if change < -0.2 {
                 "decreasing".to_string()
             } else {
                 "stable".to_string()
@@ -166,7 +382,7 @@ impl DeadlockDetector {
     }
     
     //yaha pe badme predictive deadlock detection bhi add karna ha
-    pub fn predict_deadlock_risk(&self, new_query: &str, current_locks: &[String]) -> f64 {
+    pub fn predict_deadlock_risk(&self, new_query: &str, current_locks: &[String], isolation_level: IsolationLevel) -> f64 {
         let mut risk_score = 0.0;
         
         // Check if query pattern has caused deadlocks before
@@ -186,13 +402,70 @@ impl DeadlockDetector {
             risk_score += 0.2;
         }
         
-        if new_query.contains("UPDATE") || new_query.contains("DELETE") {
+        // Upserts conflict row pe bhi lock lete hai, isliye writes jaisa hi risk
+        let is_upsert = matches!(QueryLog::classify(new_query).as_str(), "UPSERT" | "MERGE");
+        if new_query.contains("UPDATE") || new_query.contains("DELETE") || is_upsert {
             risk_score += 0.3;
         }
         
+        // Same query stricter isolation pe zyada locks zyada der tak hold karti hai
+        risk_score *= isolation_level.risk_multiplier();
+        
         risk_score.min(1.0) // Cap at 1.0
     }
     
+    // Live lock data - txn_id is waiting on resource held by holder_txn_id
+    pub fn register_lock_wait(&mut self, txn_id: &str, holder_txn_id: &str, resource: &str) {
+        let edges = self.wait_for_graph.entry(txn_id.to_string()).or_default();
+        let edge = (holder_txn_id.to_string(), resource.to_string());
+        if !edges.contains(&edge) {
+            edges.push(edge);
+        }
+    }
+    
+    // Transaction commit/rollback ke baad uske saare wait edges hata deta hai
+    pub fn release_transaction(&mut self, txn_id: &str) {
+        self.wait_for_graph.remove(txn_id);
+        for edges in self.wait_for_graph.values_mut() {
+            edges.retain(|(holder, _)| holder != txn_id);
+        }
+        self.wait_for_graph.retain(|_, edges| !edges.is_empty());
+    }
+    
+    // Every elementary cycle in the wait-for graph is a deadlock; each cycle starts at its smallest txn id
+    pub fn find_cycles(&self) -> Vec<Vec<String>> {
+        let mut nodes: Vec<&String> = self.wait_for_graph.keys().collect();
+        nodes.sort();
+        
+        let mut cycles = Vec::new();
+        for start in nodes {
+            let mut path = vec![start.clone()];
+            self.collect_cycles(start, start, &mut path, &mut cycles);
+        }
+        cycles
+    }
+    
+    // DFS only through txns greater than start, so each cycle is reported exactly once
+    fn collect_cycles(&self, start: &str, current: &str, path: &mut Vec<String>, cycles: &mut Vec<Vec<String>>) {
+        let Some(edges) = self.wait_for_graph.get(current) else {
+            return;
+        };
+        
+        let mut holders: Vec<&String> = edges.iter().map(|(holder, _)| holder).collect();
+        holders.sort();
+        holders.dedup();
+        
+        for holder in holders {
+            if holder == start {
+                cycles.push(path.clone());
+            } else if holder.as_str() > start && !path.contains(holder) {
+                path.push(holder.clone());
+                self.collect_cycles(start, holder, path, cycles);
+                path.pop();
+            }
+        }
+    }
+    
     pub fn get_optimization_recommendations(&self) -> Vec<String> {
         let mut recommendations = Vec::new();
         
@@ -202,7 +475,7 @@ impl DeadlockDetector {
         }
         
         let high_risk_queries: Vec<_> = self.query_patterns.iter()
-            .filter(|(_, &count)| count > 2)
+            .filter(|(_, count)| **count > 2)
             .collect();
             
         if !high_risk_queries.is_empty() {
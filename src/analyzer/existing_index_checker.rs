@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ExistingIndex {
     pub table_name: String,
     pub column_names: Vec<String>,
@@ -12,11 +13,11 @@ pub struct ExistingIndex {
     pub filter_condition: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct IndexConflict {
     pub recommended_index: String,
     pub existing_index: String,
-    pub conflict_type: String, // "duplicate", "overlapping", "redundant"
+    pub conflict_type: String, // "duplicate", "prefix_redundant", "overlapping"
     pub severity: f64,
 }
 
@@ -24,6 +25,12 @@ pub struct ExistingIndexChecker {
     existing_indexes: HashMap<String, Vec<ExistingIndex>>,
 }
 
+impl Default for ExistingIndexChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ExistingIndexChecker {
     pub fn new() -> Self {
         Self {
@@ -34,16 +41,42 @@ impl ExistingIndexChecker {
     //yaha pe badme database connection bhi add karna ha
     pub fn add_existing_index(&mut self, index: ExistingIndex) {
         let table_name = index.table_name.clone();
-        self.existing_indexes.entry(table_name).or_insert_with(Vec::new).push(index);
+        self.existing_indexes.entry(table_name).or_default().push(index);
+    }
+    
+    /// Column akele kisi unique, non-partial index ki key hai ya nahi - None jab table ke indexes hi pata nahi
+    pub fn is_unique_column(&self, table_name: &str, column_name: &str) -> Option<bool> {
+        let table_indexes = self.existing_indexes.get(table_name)?;
+        Some(table_indexes.iter().any(|index| {
+            index.is_unique
+                && !index.is_partial
+                && index.column_names.len() == 1
+                && index.column_names[0].eq_ignore_ascii_case(column_name)
+        }))
     }
     
     pub fn check_for_conflicts(&self, recommended_table: &str, recommended_columns: &[String]) -> Vec<IndexConflict> {
+        self.check_for_conflicts_with_filter(recommended_table, recommended_columns, None)
+    }
+    
+    // recommended_filter = partial index ka WHERE condition, full index ke liye None
+    pub fn check_for_conflicts_with_filter(&self, 
+        recommended_table: &str, 
+        recommended_columns: &[String],
+        recommended_filter: Option<&str>) -> Vec<IndexConflict> {
+        
         let mut conflicts = Vec::new();
         
         if let Some(table_indexes) = self.existing_indexes.get(recommended_table) {
             for existing_index in table_indexes {
                 let conflict = self.analyze_index_overlap(existing_index, recommended_columns);
-                if let Some(conflict) = conflict {
+                if let Some(mut conflict) = conflict {
+                    // Existing index must cover every row the recommended one would - warna duplicate nahi hai
+                    let covers_rows = self.filter_covers(existing_index, recommended_filter);
+                    if !covers_rows && conflict.conflict_type != "overlapping" {
+                        conflict.conflict_type = "overlapping".to_string();
+                        conflict.severity *= 0.5;
+                    }
                     conflicts.push(conflict);
                 }
             }
@@ -52,35 +85,74 @@ impl ExistingIndexChecker {
         conflicts
     }
     
+    // True when existing filter is equal to or broader than the recommended one
+    fn filter_covers(&self, existing: &ExistingIndex, recommended_filter: Option<&str>) -> bool {
+        let existing_filter = match (&existing.filter_condition, existing.is_partial) {
+            (Some(filter), _) => filter,
+            (None, true) => return false, // partial index with unknown filter - can't verify
+            (None, false) => return true,
+        };
+        
+        let Some(recommended_filter) = recommended_filter else {
+            return false; // full index needs all rows, partial index can't serve it
+        };
+        
+        // Broader = existing predicates are a subset of the recommended AND-ed predicates
+        let recommended_predicates = Self::split_predicates(recommended_filter);
+        Self::split_predicates(existing_filter)
+            .iter()
+            .all(|p| recommended_predicates.contains(p))
+    }
+    
+    fn split_predicates(filter: &str) -> Vec<String> {
+        let normalized = filter
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        
+        let mut predicates = Vec::new();
+        let mut current: Vec<&str> = Vec::new();
+        for token in normalized.split(' ') {
+            if token.eq_ignore_ascii_case("AND") {
+                predicates.push(current.join(" ").to_lowercase());
+                current.clear();
+            } else {
+                current.push(token);
+            }
+        }
+        predicates.push(current.join(" ").to_lowercase());
+        predicates.retain(|p| !p.is_empty());
+        predicates
+    }
+    
+    // Column order matters - (a, b) index covers lookups on (a) but not on (b)
     fn analyze_index_overlap(&self, existing: &ExistingIndex, recommended: &[String]) -> Option<IndexConflict> {
-        let existing_set: std::collections::HashSet<String> = existing.column_names.iter().cloned().collect();
-        let recommended_set: std::collections::HashSet<String> = recommended.iter().cloned().collect();
+        let conflict = |conflict_type: &str, severity: f64| IndexConflict {
+            recommended_index: recommended.join(", "),
+            existing_index: existing.index_name.clone(),
+            conflict_type: conflict_type.to_string(),
+            severity,
+        };
+        
+        if recommended.is_empty() {
+            return None;
+        }
+        
+        if existing.column_names.as_slice() == recommended {
+            return Some(conflict("duplicate", 1.0));
+        }
         
-        if existing_set == recommended_set {
-            return Some(IndexConflict {
-                recommended_index: recommended.join(", "),
-                existing_index: existing.index_name.clone(),
-                conflict_type: "duplicate".to_string(),
-                severity: 1.0,
-            });
+        if existing.column_names.starts_with(recommended) {
+            return Some(conflict("prefix_redundant", 0.8));
         }
         
-        if existing_set.is_superset(&recommended_set) {
-            return Some(IndexConflict {
-                recommended_index: recommended.join(", "),
-                existing_index: existing.index_name.clone(),
-                conflict_type: "redundant".to_string(),
-                severity: 0.8,
-            });
+        // Same leading column - existing index partially serves the same lookups
+        if existing.column_names.first() == recommended.first() {
+            return Some(conflict("overlapping", 0.5));
         }
         
-        if !existing_set.is_disjoint(&recommended_set) {
-            return Some(IndexConflict {
-                recommended_index: recommended.join(", "),
-                existing_index: existing.index_name.clone(),
-                conflict_type: "overlapping".to_string(),
-                severity: 0.6,
-            });
+        if recommended.iter().any(|c| existing.column_names.contains(c)) {
+            return Some(conflict("overlapping", 0.2));
         }
         
         None
@@ -1,10 +1,28 @@
-use serde::{Deserialize, Serialize};
-use std::fs::File;
+use serde::Serialize;
+use std::fs::{self, File};
 use std::io::Write;
+use std::path::Path;
+
+use crate::analyzer::SqlDialect;
+use crate::recommender::Recommendation;
+
+/// Migration file options - CONCURRENTLY sirf Postgres pe lagta hai
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SqlExportOptions {
+    pub dialect: SqlDialect,
+    pub concurrently: bool, // CREATE INDEX CONCURRENTLY - table lock nahi leta, par transaction ke bahar chalana padta hai
+    pub include_rollback: bool, // "-- down" section with rollback_sql, reverse order mein
+}
 
 /// Export functionality for analysis results - ye class results export karta hai
 pub struct DataExporter;
 
+impl Default for DataExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DataExporter {
     pub fn new() -> Self {
         Self
@@ -29,6 +47,115 @@ impl DataExporter {
         Ok(())
     }
     
+    /// Har result type ka JSON Schema dir mein likhta hai - <TypeName>.schema.json, front-end
+    /// codegen ke liye. Dir na ho to bana deta hai
+    pub fn export_schemas(&self, dir: &str) -> Result<(), Box<dyn std::error::Error>> {
+        fs::create_dir_all(dir)?;
+        
+        for (type_name, schema) in crate::schema::result_type_schemas() {
+            let path = Path::new(dir).join(format!("{}.schema.json", type_name));
+            let json = serde_json::to_string_pretty(&schema)?;
+            fs::write(path, json)?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Recommendations ka runnable .sql migration - har statement apni line pe, upar reason aur
+    /// estimated benefit ka comment. Jin recommendations ka SQL nahi hai (e.g. deadlock advice) skip
+    pub fn export_to_sql<R: Recommendation>(&self, recommendations: &[R], filename: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.export_to_sql_with_options(recommendations, filename, SqlExportOptions::default())
+    }
+
+    pub fn export_to_sql_with_options<R: Recommendation>(
+        &self,
+        recommendations: &[R],
+        filename: &str,
+        options: SqlExportOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(filename, self.render_sql_migration(recommendations, options))?;
+        Ok(())
+    }
+
+    /// export_to_sql ka content bina file likhe
+    pub fn render_sql_migration<R: Recommendation>(&self, recommendations: &[R], options: SqlExportOptions) -> String {
+        let concurrently = options.concurrently && options.dialect == SqlDialect::Postgres;
+        let statements: Vec<(&R, String)> = recommendations
+            .iter()
+            .filter_map(|rec| rec.migration_sql(options.dialect).map(|sql| (rec, sql)))
+            .collect();
+
+        let mut migration = format!("-- Index/schema migration: {} statement(s)\n", statements.len());
+        if concurrently {
+            migration.push_str("-- CREATE INDEX CONCURRENTLY cannot run inside a transaction block\n");
+        }
+        if options.include_rollback {
+            migration.push_str("\n-- up\n");
+        }
+
+        for (rec, sql) in &statements {
+            migration.push('\n');
+            migration.push_str(&format!("-- {}: {}\n", rec.table(), single_line(rec.description())));
+            if let Some(benefit) = rec.estimated_benefit() {
+                migration.push_str(&format!("-- Estimated benefit: {}\n", benefit));
+            }
+
+            push_statement(&mut migration, sql, concurrently);
+        }
+
+        if options.include_rollback {
+            migration.push_str("\n-- down\n");
+            // Ulte order mein - baad wala statement pehle wale pe depend kar sakta hai
+            for (rec, sql) in statements.iter().rev() {
+                if sql.trim_start().starts_with("--") {
+                    continue; // advisory comment ka kuch undo nahi karna
+                }
+                migration.push('\n');
+                match rec.rollback_sql(options.dialect) {
+                    Some(rollback) => {
+                        migration.push_str(&format!("-- revert {}: {}\n", rec.table(), single_line(rec.description())));
+                        push_statement(&mut migration, &rollback, concurrently);
+                    }
+                    None => migration.push_str(&format!(
+                        "-- irreversible ({}): {}\n",
+                        rec.table(),
+                        single_line(sql)
+                    )),
+                }
+            }
+        }
+
+        migration
+    }
+
     //function export_to_xml() {}
-    //function export_to_sql() {}
+}
+
+// Multi-line SQL / reasons ko ek line mein - har statement apni line pe rahe
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Statement ko ek line + ';' ke saath likhta hai; "-- Partition ..." jaise advisory statements comment hi rehte hai
+fn push_statement(migration: &mut String, sql: &str, concurrently: bool) {
+    let sql = single_line(sql);
+    if sql.starts_with("--") {
+        migration.push_str(&sql);
+    } else {
+        let sql = if concurrently { with_concurrently(&sql) } else { sql };
+        migration.push_str(sql.trim_end_matches(';'));
+        migration.push(';');
+    }
+    migration.push('\n');
+}
+
+// "CREATE [UNIQUE] INDEX name ..." / "DROP INDEX name" -> "... INDEX CONCURRENTLY name ..."
+fn with_concurrently(sql: &str) -> String {
+    for prefix in ["CREATE INDEX ", "CREATE UNIQUE INDEX ", "DROP INDEX "] {
+        if let Some(rest) = sql.strip_prefix(prefix)
+            && !rest.starts_with("CONCURRENTLY") {
+            return format!("{}CONCURRENTLY {}", prefix, rest);
+        }
+    }
+    sql.to_string()
 }
@@ -0,0 +1,12 @@
+/// Regression ka lower bound - 10x slower ko -900% dikhane se koi fayda nahi, "2x se zyada slow" kaafi hai
+const MAX_REGRESSION_PERCENT: f64 = -100.0;
+
+/// (old - new) / old * 100, safe version - old zero / negative / NaN ho to 0.0 (compare karne ko
+/// kuch nahi), result -100.0..=100.0 mein clamp hota hai. Negative = regression
+pub fn percent_improvement(old: f64, new: f64) -> f64 {
+    if !old.is_finite() || !new.is_finite() || old <= 0.0 {
+        return 0.0;
+    }
+
+    ((old - new) / old * 100.0).clamp(MAX_REGRESSION_PERCENT, 100.0)
+}
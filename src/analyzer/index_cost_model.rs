@@ -0,0 +1,69 @@
+/// Index type ke hisaab se improvement predict karne ka pluggable model.
+/// Returns the fraction of current_time the index saves (0.0 = no gain, 1.0 = free)
+pub trait IndexCostModel {
+    fn predict_improvement(&self, current_time: u64, rows: u64, selectivity: Option<f64>) -> f64;
+}
+
+// Selectivity na ho to ek typical equality predicate maan lete hai
+const ASSUMED_SELECTIVITY: f64 = 0.05;
+
+/// B-tree - random I/O ~4x sequential read, so ~25% matching rows pe scan jitna hi fast
+pub struct BTreeCostModel {
+    pub effectiveness: f64,
+}
+
+impl Default for BTreeCostModel {
+    fn default() -> Self {
+        Self { effectiveness: 0.8 }
+    }
+}
+
+impl IndexCostModel for BTreeCostModel {
+    fn predict_improvement(&self, _current_time: u64, _rows: u64, selectivity: Option<f64>) -> f64 {
+        let selectivity = selectivity.unwrap_or(ASSUMED_SELECTIVITY).clamp(0.0, 1.0);
+        let index_read_fraction = (selectivity * 4.0).min(1.0);
+        self.effectiveness * (1.0 - index_read_fraction)
+    }
+}
+
+/// Hash - sirf equality lookups; O(1) probe, lekin bahut rows match ho to fayda jaldi khatam
+pub struct HashCostModel {
+    pub effectiveness: f64,
+}
+
+impl Default for HashCostModel {
+    fn default() -> Self {
+        Self { effectiveness: 0.85 }
+    }
+}
+
+impl IndexCostModel for HashCostModel {
+    fn predict_improvement(&self, _current_time: u64, _rows: u64, selectivity: Option<f64>) -> f64 {
+        let selectivity = selectivity.unwrap_or(ASSUMED_SELECTIVITY).clamp(0.0, 1.0);
+        let index_read_fraction = (selectivity * 5.0).min(1.0);
+        self.effectiveness * (1.0 - index_read_fraction)
+    }
+}
+
+/// GIN - full-text / containment; posting lists chhote tables pe overhead zyada dete hai
+pub struct GinCostModel {
+    pub effectiveness: f64,
+    pub min_rows: u64, // isse kam rows pe seq scan + filter hi sasta hai
+}
+
+impl Default for GinCostModel {
+    fn default() -> Self {
+        Self { effectiveness: 0.7, min_rows: 1000 }
+    }
+}
+
+impl IndexCostModel for GinCostModel {
+    fn predict_improvement(&self, _current_time: u64, rows: u64, selectivity: Option<f64>) -> f64 {
+        if rows < self.min_rows {
+            return 0.0;
+        }
+        let selectivity = selectivity.unwrap_or(ASSUMED_SELECTIVITY).clamp(0.0, 1.0);
+        let index_read_fraction = (selectivity * 3.0).min(1.0);
+        self.effectiveness * (1.0 - index_read_fraction)
+    }
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use super::{ColumnInfo, SchemaOptimizer};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MaintenanceCost {
     pub index_name: String,
     pub table_name: String,
@@ -15,6 +17,13 @@ pub struct MaintenanceCost {
 pub struct IndexMaintenanceCostAnalyzer {
     base_write_time: f64,
     index_overhead_factor: f64,
+    schema_optimizer: SchemaOptimizer, // data type widths ke liye
+}
+
+impl Default for IndexMaintenanceCostAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl IndexMaintenanceCostAnalyzer {
@@ -22,6 +31,7 @@ impl IndexMaintenanceCostAnalyzer {
         Self {
             base_write_time: 1.0, // Base write time in ms
             index_overhead_factor: 0.15, // 15% overhead per index
+            schema_optimizer: SchemaOptimizer::new(),
         }
     }
     
@@ -33,6 +43,26 @@ impl IndexMaintenanceCostAnalyzer {
         write_frequency: u64) -> MaintenanceCost {
         
         let overhead = self.calculate_index_overhead(column_count);
+        self.build_maintenance_cost(index_name, table_name, overhead, write_frequency)
+    }
+    
+    pub fn calculate_maintenance_cost_with_types(&self, 
+        index_name: &str, 
+        table_name: &str, 
+        columns: &[ColumnInfo],
+        write_frequency: u64) -> MaintenanceCost {
+        
+        let key_bytes: u64 = columns.iter().map(|c| self.schema_optimizer.column_width(c)).sum();
+        let overhead = self.calculate_index_overhead(columns.len()) * self.key_width_multiplier(key_bytes);
+        self.build_maintenance_cost(index_name, table_name, overhead, write_frequency)
+    }
+    
+    fn build_maintenance_cost(&self, 
+        index_name: &str, 
+        table_name: &str, 
+        overhead: f64,
+        write_frequency: u64) -> MaintenanceCost {
+        
         let insert_cost = self.estimate_insert_cost(overhead, write_frequency);
         let update_cost = self.estimate_update_cost(overhead, write_frequency);
         let delete_cost = self.estimate_delete_cost(overhead, write_frequency);
@@ -58,6 +88,13 @@ impl IndexMaintenanceCostAnalyzer {
         base_overhead * column_multiplier
     }
     
+    // 8-byte key (BIGINT) baseline; wider keys mean fewer entries per page and more page splits,
+    // so cost grows logarithmically with key bytes
+    fn key_width_multiplier(&self, key_bytes: u64) -> f64 {
+        let relative_width = key_bytes as f64 / 8.0;
+        1.0 + relative_width.ln().max(0.0) * 0.25
+    }
+    
     fn estimate_insert_cost(&self, overhead: f64, write_frequency: u64) -> f64 {
         let base_cost = self.base_write_time;
         let frequency_factor = (write_frequency as f64 / 1000.0).min(2.0);
@@ -108,6 +145,23 @@ impl IndexMaintenanceCostAnalyzer {
         (new_write_time, impact_level)
     }
     
+    pub fn estimate_statement_overhead(&self, 
+        statement_type: &str, 
+        rows_affected: u64, 
+        index_count: usize) -> f64 {
+        
+        // Same relative costs as estimate_*_cost - updates sabse mehenge, deletes thode saste
+        let statement_factor = match statement_type.to_uppercase().as_str() {
+            "UPDATE" => 1.5,
+            // Conflict pe update path bhi chal sakta hai - worst case update jitna
+            "UPSERT" | "MERGE" => 1.5,
+            "DELETE" => 0.8,
+            _ => 1.0,
+        };
+        
+        self.analyze_batch_operations(rows_affected, index_count) * statement_factor
+    }
+    
     //yaha pe badme batch operation analysis bhi add karna ha
     pub fn analyze_batch_operations(&self, 
         batch_size: u64, 
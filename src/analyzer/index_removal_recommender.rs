@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use schemars::JsonSchema;
+use std::collections::BTreeMap;
+use crate::recommender::ReasonCode;
+use super::{Clock, ExistingIndex, IndexUsageSimulator, QueryLog, QueryParser, SystemClock};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct IndexUsageStats {
     pub index_name: String,
     pub table_name: String,
@@ -9,40 +12,132 @@ pub struct IndexUsageStats {
     pub last_used: u64,
     pub query_benefit: f64,
     pub maintenance_cost: f64,
+    #[serde(default)]
+    pub observation_start: u64, // tracking kab shuru hua (unix secs) - 0 = bahut pehle se
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl IndexUsageStats {
+    // Query logs se usage stats banata hai - index tabhi use hota hai jab uska leading column
+    // WHERE ya JOIN mein reference ho
+    pub fn from_logs(indexes: &[ExistingIndex], logs: &[QueryLog]) -> Vec<IndexUsageStats> {
+        let parser = QueryParser;
+        let parsed_logs: Vec<_> = logs.iter().map(|log| (log, parser.parse(&log.query))).collect();
+        // Pehle log se observation window shuru hoti hai - logs hi nahi to window empty hai
+        let observation_start = logs.iter().map(|log| log.timestamp).min().unwrap_or(u64::MAX);
+        
+        indexes
+            .iter()
+            .map(|index| {
+                let table_logs: Vec<_> = parsed_logs
+                    .iter()
+                    .filter(|(log, parsed)| {
+                        log.tables_accessed.contains(&index.table_name)
+                            || parsed.from_tables.contains(&index.table_name)
+                    })
+                    .collect();
+                
+                let using_logs: Vec<_> = table_logs
+                    .iter()
+                    .filter(|(_, parsed)| Self::references_leading_column(index, parsed))
+                    .collect();
+                
+                let write_count = table_logs
+                    .iter()
+                    .filter(|(log, _)| matches!(log.query_type().as_str(), "INSERT" | "UPDATE" | "DELETE" | "UPSERT" | "MERGE"))
+                    .count();
+                let ratio = |count: usize| if table_logs.is_empty() {
+                    0.0
+                } else {
+                    count as f64 / table_logs.len() as f64
+                };
+                
+                IndexUsageStats {
+                    index_name: index.index_name.clone(),
+                    table_name: index.table_name.clone(),
+                    usage_count: using_logs.len() as u64,
+                    last_used: using_logs.iter().map(|(log, _)| log.timestamp).max().unwrap_or(0),
+                    query_benefit: ratio(using_logs.len()), // share of table queries served
+                    maintenance_cost: ratio(write_count), // share of table queries that are writes
+                    observation_start,
+                }
+            })
+            .collect()
+    }
+    
+    fn references_leading_column(index: &ExistingIndex, parsed: &super::ParsedQuery) -> bool {
+        let Some(leading) = index.column_names.first() else {
+            return false;
+        };
+        let qualified = format!("{}.{}", index.table_name, leading);
+        
+        parsed
+            .referenced_columns()
+            .iter()
+            .any(|column| column == leading || *column == qualified)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RemovalRecommendation {
     pub index_name: String,
     pub table_name: String,
     pub removal_reason: String,
+    #[serde(default)]
+    pub reason_code: ReasonCode,
     pub confidence_score: f64,
     pub estimated_savings: f64,
     pub risk_level: String,
     pub sql_statement: String,
 }
 
+const POINT_LOOKUP_SELECTIVITY: f64 = 0.01;
+
+/// Index drop karne ka quantified downside - kitni queries coverage khoyengi aur kitna slow hongi
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RemovalImpact {
+    pub index_name: String,
+    pub affected_query_count: usize,
+    pub estimated_time_increase_ms: u64, // affected queries ka total extra time
+}
+
 pub struct IndexRemovalRecommender {
     usage_threshold: u64,
     time_threshold: u64,
     benefit_threshold: f64,
+    clock: Box<dyn Clock>, // analyze_index_usage ka "now"
+}
+
+impl Default for IndexRemovalRecommender {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl IndexRemovalRecommender {
     pub fn new() -> Self {
+        Self::with_clock(Box::new(SystemClock))
+    }
+    
+    pub fn with_clock(clock: Box<dyn Clock>) -> Self {
         Self {
             usage_threshold: 10, // Minimum usage count
             time_threshold: 86400 * 30, // 30 days in seconds
             benefit_threshold: 0.1, // Minimum benefit ratio
+            clock,
         }
     }
     
     //yaha pe badme advanced usage analysis bhi add karna ha
     pub fn analyze_index_usage(&self, usage_stats: &[IndexUsageStats]) -> Vec<RemovalRecommendation> {
+        self.analyze_index_usage_at(usage_stats, self.clock.now_secs())
+    }
+    
+    // now = current unix timestamp in seconds (last_used ke saath compare hota hai)
+    pub fn analyze_index_usage_at(&self, usage_stats: &[IndexUsageStats], now: u64) -> Vec<RemovalRecommendation> {
         let mut recommendations = Vec::new();
         
         for stats in usage_stats {
-            if let Some(recommendation) = self.evaluate_index_for_removal(stats) {
+            if let Some(recommendation) = self.evaluate_index_for_removal(stats, now) {
                 recommendations.push(recommendation);
             }
         }
@@ -52,7 +147,62 @@ impl IndexRemovalRecommender {
         recommendations
     }
     
-    fn evaluate_index_for_removal(&self, stats: &IndexUsageStats) -> Option<RemovalRecommendation> {
+    /// What-if drop - IndexUsageSimulator ko ulta chalata hai: observed time index ke saath hai,
+    /// to bina index ka time = observed / (1 - predicted improvement)
+    pub fn simulate_removal_impact(&self, index: &ExistingIndex, logs: &[QueryLog]) -> RemovalImpact {
+        let parser = QueryParser;
+        let simulator = IndexUsageSimulator::new();
+        let mut affected_query_count = 0;
+        let mut estimated_time_increase_ms = 0.0;
+        
+        for log in logs {
+            let parsed = parser.parse(&log.query);
+            let on_table = log.tables_accessed.contains(&index.table_name)
+                || parsed.from_tables.contains(&index.table_name);
+            if !on_table || !IndexUsageStats::references_leading_column(index, &parsed) {
+                continue;
+            }
+            affected_query_count += 1;
+            
+            // Leading column wale predicate ki selectivity se improvement estimate hota hai. Equality
+            // lookup point lookup hai - generic heuristic ka 0.5 default yaha benefit zero kar deta
+            let leading = index.column_names.first().map(|c| c.as_str()).unwrap_or("");
+            let selectivity = parsed
+                .where_clauses
+                .iter()
+                .find(|clause| clause.contains(leading))
+                .map(|clause| {
+                    let is_equality = clause.contains('=') && !clause.contains(['<', '>', '!']);
+                    if is_equality { POINT_LOOKUP_SELECTIVITY } else { simulator.estimate_selectivity(clause) }
+                });
+            
+            let simulation = simulator.simulate_with_selectivity(
+                &index.table_name,
+                &index.column_names,
+                log.execution_time_ms,
+                log.rows_scanned,
+                selectivity,
+            );
+            let improvement = (simulation.improvement_percent / 100.0).clamp(0.0, 0.99);
+            let time_without_index = log.execution_time_ms as f64 / (1.0 - improvement);
+            estimated_time_increase_ms += time_without_index - log.execution_time_ms as f64;
+        }
+        
+        RemovalImpact {
+            index_name: index.index_name.clone(),
+            affected_query_count,
+            estimated_time_increase_ms: estimated_time_increase_ms.round() as u64,
+        }
+    }
+    
+    fn evaluate_index_for_removal(&self, stats: &IndexUsageStats, now: u64) -> Option<RemovalRecommendation> {
+        // Window time_threshold se chhota hai to "unused" ka matlab bas "abhi tak nahi dekha" hai -
+        // quarter-end reporting index ek hafte ke data pe drop nahi hona chahiye
+        let observation_window = now.saturating_sub(stats.observation_start);
+        if observation_window < self.time_threshold {
+            return None;
+        }
+        
         let mut reasons = Vec::new();
         let mut confidence = 0.0;
         
@@ -62,8 +212,9 @@ impl IndexRemovalRecommender {
             confidence += 0.3;
         }
         
-        // Check last usage time
-        if stats.last_used < self.time_threshold {
+        // Check last usage time - elapsed duration since last use, not the raw timestamp
+        let elapsed = now.saturating_sub(stats.last_used);
+        if elapsed > self.time_threshold {
             reasons.push("Not used recently".to_string());
             confidence += 0.2;
         }
@@ -81,6 +232,13 @@ impl IndexRemovalRecommender {
         }
         
         if confidence > 0.5 {
+            // Koi use hi nahi / kam use = UnusedIndex; use hota hai par fayda kam = LowBenefit
+            let low_benefit = stats.query_benefit < stats.maintenance_cost * self.benefit_threshold;
+            let reason_code = if low_benefit && stats.usage_count >= self.usage_threshold {
+                ReasonCode::LowBenefit
+            } else {
+                ReasonCode::UnusedIndex
+            };
             let risk_level = self.determine_risk_level(confidence, stats.usage_count);
             let estimated_savings = self.calculate_savings(stats);
             let sql_statement = self.generate_drop_sql(stats);
@@ -89,6 +247,7 @@ impl IndexRemovalRecommender {
                 index_name: stats.index_name.clone(),
                 table_name: stats.table_name.clone(),
                 removal_reason: reasons.join(", "),
+                reason_code,
                 confidence_score: confidence,
                 estimated_savings,
                 risk_level,
@@ -125,18 +284,18 @@ impl IndexRemovalRecommender {
     
     pub fn find_redundant_indexes(&self, indexes: &[IndexUsageStats]) -> Vec<RemovalRecommendation> {
         let mut redundant = Vec::new();
-        let mut grouped: HashMap<String, Vec<&IndexUsageStats>> = HashMap::new();
+        let mut grouped: BTreeMap<String, Vec<&IndexUsageStats>> = BTreeMap::new(); // table order se output stable
         
         // Group indexes by table
         for index in indexes {
-            grouped.entry(index.table_name.clone()).or_insert_with(Vec::new).push(index);
+            grouped.entry(index.table_name.clone()).or_default().push(index);
         }
         
         // Find redundant indexes within each table
         for (_, table_indexes) in grouped {
             if table_indexes.len() > 1 {
                 let mut sorted_indexes = table_indexes.clone();
-                sorted_indexes.sort_by(|a, b| a.usage_count.cmp(&b.usage_count));
+                sorted_indexes.sort_by_key(|a| a.usage_count);
                 
                 // Mark lower usage indexes as redundant
                 for (i, index) in sorted_indexes.iter().enumerate() {
@@ -145,6 +304,7 @@ impl IndexRemovalRecommender {
                             index_name: index.index_name.clone(),
                             table_name: index.table_name.clone(),
                             removal_reason: "Redundant - lower usage than other indexes".to_string(),
+                            reason_code: ReasonCode::RedundantIndex,
                             confidence_score: 0.7,
                             estimated_savings: 30.0,
                             risk_level: "Medium".to_string(),
@@ -159,7 +319,7 @@ impl IndexRemovalRecommender {
     }
     
     //yaha pe badme index dependency analysis bhi add karna ha
-    pub fn get_safe_removal_candidates(&self, recommendations: &[RemovalRecommendation]) -> Vec<&RemovalRecommendation> {
+    pub fn get_safe_removal_candidates<'a>(&self, recommendations: &'a [RemovalRecommendation]) -> Vec<&'a RemovalRecommendation> {
         recommendations
             .iter()
             .filter(|rec| rec.risk_level == "Very Low" || rec.risk_level == "Low")
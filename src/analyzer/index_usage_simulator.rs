@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::collections::HashMap;
+use crate::analyzer::{percent_improvement, Confidence, IndexCostModel, PartialIndexRecommender, SchemaOptimizer, TableSchema};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct IndexSimulation {
     pub table_name: String,
     pub column_names: Vec<String>,
@@ -8,12 +11,34 @@ pub struct IndexSimulation {
     pub predicted_execution_time: u64,
     pub improvement_percent: f64,
     pub confidence_score: f64,
+    #[serde(default)]
+    pub confidence_factors: Vec<String>, // confidence_score kyun aisa hai - "complex 4-column index" etc.
     pub storage_cost_mb: f64,
 }
 
+impl IndexSimulation {
+    pub fn confidence(&self) -> Confidence {
+        Confidence::new(self.confidence_score, self.confidence_factors.clone())
+    }
+}
+
 pub struct IndexUsageSimulator {
     base_performance_factor: f64,
     index_effectiveness_factor: f64,
+    predictions: HashMap<String, u64>, // table+columns -> last predicted time
+    observed_results: Vec<(u64, u64)>, // (predicted, actual)
+    cost_model: Option<Box<dyn IndexCostModel>>, // None = built-in formulas
+    schema_optimizer: SchemaOptimizer, // data type widths ke liye
+    column_widths: HashMap<String, u64>, // "table.column" -> bytes per key
+    table_rows: HashMap<String, u64>, // index har row ka entry rakhta hai, sirf scanned ka nahi
+    fill_factor: f64, // leaf pages kitne bhare jate hai - Postgres B-tree default 90%
+    overhead_multiplier: f64, // tuple headers, page headers, inner pages
+}
+
+impl Default for IndexUsageSimulator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl IndexUsageSimulator {
@@ -21,6 +46,40 @@ impl IndexUsageSimulator {
         Self {
             base_performance_factor: 0.1, // Index typically improves by 90%
             index_effectiveness_factor: 0.8, // 80% effectiveness for new indexes
+            predictions: HashMap::new(),
+            observed_results: Vec::new(),
+            cost_model: None,
+            schema_optimizer: SchemaOptimizer::new(),
+            column_widths: HashMap::new(),
+            table_rows: HashMap::new(),
+            fill_factor: 0.9,
+            overhead_multiplier: 1.8, // fill factor ke saath ~2x raw key size
+        }
+    }
+    
+    /// Column widths aur row count register karta hai - inke bina storage estimate flat
+    /// 8 bytes per column * rows_scanned pe fallback karta hai
+    pub fn add_table_schema(&mut self, schema: &TableSchema) {
+        for column in &schema.columns {
+            let width = self.schema_optimizer.column_width(column);
+            self.column_widths.insert(format!("{}.{}", schema.table_name, column.name), width);
+        }
+        if schema.row_count > 0 {
+            self.table_rows.insert(schema.table_name.clone(), schema.row_count);
+        }
+    }
+    
+    /// fill_factor 0.1..=1.0 (FILLFACTOR / 100), overhead_multiplier >= 1.0 raw key bytes ke upar
+    pub fn set_storage_model(&mut self, fill_factor: f64, overhead_multiplier: f64) {
+        self.fill_factor = fill_factor.clamp(0.1, 1.0);
+        self.overhead_multiplier = overhead_multiplier.max(1.0);
+    }
+    
+    /// Custom improvement model inject karta hai - e.g. HashCostModel / GinCostModel ya apna khud ka
+    pub fn with_model(cost_model: Box<dyn IndexCostModel>) -> Self {
+        Self {
+            cost_model: Some(cost_model),
+            ..Self::new()
         }
     }
     
@@ -31,10 +90,28 @@ impl IndexUsageSimulator {
         current_time: u64, 
         rows_scanned: u64) -> IndexSimulation {
         
-        let predicted_time = self.calculate_predicted_time(current_time, rows_scanned, columns.len());
-        let improvement = ((current_time as f64 - predicted_time as f64) / current_time as f64) * 100.0;
+        self.simulate_with_selectivity(table_name, columns, current_time, rows_scanned, None)
+    }
+    
+    // selectivity = fraction of rows the indexed predicate matches; None keeps the generic model
+    pub fn simulate_with_selectivity(&self, 
+        table_name: &str, 
+        columns: &[String], 
+        current_time: u64, 
+        rows_scanned: u64,
+        selectivity: Option<f64>) -> IndexSimulation {
+        
+        let predicted_time = match (&self.cost_model, selectivity) {
+            (Some(model), _) => {
+                let improvement = model.predict_improvement(current_time, rows_scanned, selectivity).clamp(0.0, 1.0);
+                ((current_time as f64 * (1.0 - improvement)) as u64).max(1)
+            }
+            (None, Some(selectivity)) => self.calculate_selective_time(current_time, selectivity),
+            (None, None) => self.calculate_predicted_time(current_time, rows_scanned, columns.len()),
+        };
+        let improvement = percent_improvement(current_time as f64, predicted_time as f64);
         let storage_cost = self.estimate_storage_cost(table_name, columns, rows_scanned);
-        let confidence = self.calculate_confidence(columns.len(), rows_scanned);
+        let confidence = self.calculate_confidence(columns.len(), rows_scanned, selectivity);
         
         IndexSimulation {
             table_name: table_name.to_string(),
@@ -42,11 +119,58 @@ impl IndexUsageSimulator {
             current_execution_time: current_time,
             predicted_execution_time: predicted_time,
             improvement_percent: improvement,
-            confidence_score: confidence,
+            confidence_score: confidence.score,
+            confidence_factors: confidence.factors,
             storage_cost_mb: storage_cost,
         }
     }
     
+    // Selectivity estimate for a WHERE condition - PartialIndexRecommender wala heuristic reuse karta hai
+    pub fn estimate_selectivity(&self, condition: &str) -> f64 {
+        PartialIndexRecommender::new().calculate_selectivity(condition)
+    }
+    
+    /// Simulation ka prediction yaad rakhta hai taaki index banne ke baad record_actual_result
+    /// real time se compare kar sake
+    pub fn record_prediction(&mut self, simulation: &IndexSimulation) {
+        self.predictions.insert(
+            Self::prediction_key(&simulation.table_name, &simulation.column_names),
+            simulation.predicted_execution_time,
+        );
+    }
+    
+    pub fn record_actual_result(&mut self, table_name: &str, columns: &[String], actual_time_ms: u64) -> bool {
+        match self.predictions.get(&Self::prediction_key(table_name, columns)) {
+            Some(&predicted) => {
+                self.observed_results.push((predicted, actual_time_ms));
+                true
+            }
+            None => false,
+        }
+    }
+    
+    // Mean absolute percentage error of recorded predictions (0.0 = perfect). Koi observation na ho to
+    // NaN - 0.0 "perfect" se confuse hota
+    pub fn prediction_accuracy(&self) -> f64 {
+        if self.observed_results.is_empty() {
+            return f64::NAN;
+        }
+        
+        let total_error: f64 = self.observed_results
+            .iter()
+            .map(|(predicted, actual)| {
+                let actual = (*actual).max(1) as f64;
+                (actual - *predicted as f64).abs() / actual
+            })
+            .sum();
+        
+        (total_error / self.observed_results.len() as f64) * 100.0
+    }
+    
+    fn prediction_key(table_name: &str, columns: &[String]) -> String {
+        format!("{}({})", table_name, columns.join(","))
+    }
+    
     fn calculate_predicted_time(&self, current_time: u64, rows_scanned: u64, column_count: usize) -> u64 {
         let base_improvement = self.base_performance_factor;
         let column_factor = 1.0 - (column_count as f64 * 0.05); // More columns = better improvement
@@ -64,7 +188,29 @@ impl IndexUsageSimulator {
         predicted_time.max(1) // Minimum 1ms
     }
     
+    fn calculate_selective_time(&self, current_time: u64, selectivity: f64) -> u64 {
+        // Index fetches are random I/O (~4x a sequential read, like Postgres random_page_cost),
+        // so once a predicate matches ~25% of rows a scan is just as fast
+        let index_read_fraction = (selectivity.clamp(0.0, 1.0) * 4.0).min(1.0);
+        let total_improvement = self.index_effectiveness_factor * (1.0 - index_read_fraction);
+        let predicted_time = (current_time as f64 * (1.0 - total_improvement)) as u64;
+        
+        predicted_time.max(1) // Minimum 1ms
+    }
+    
     fn estimate_storage_cost(&self, table_name: &str, columns: &[String], rows_scanned: u64) -> f64 {
+        let widths: Vec<Option<u64>> = columns
+            .iter()
+            .map(|column| self.column_widths.get(&format!("{}.{}", table_name, column)).copied())
+            .collect();
+        if widths.iter().any(|width| width.is_some()) {
+            // Unknown columns within a known table get the flat 8 bytes
+            let key_bytes: u64 = widths.iter().map(|width| width.unwrap_or(8)).sum();
+            let rows = self.table_rows.get(table_name).copied().unwrap_or(rows_scanned);
+            let total_size_bytes = key_bytes as f64 * rows as f64 * self.overhead_multiplier / self.fill_factor;
+            return total_size_bytes / (1024.0 * 1024.0);
+        }
+        
         let base_size_per_row = 8.0; // 8 bytes per column
         let column_count = columns.len() as f64;
         let estimated_rows = rows_scanned as f64;
@@ -73,24 +219,36 @@ impl IndexUsageSimulator {
         total_size_bytes / (1024.0 * 1024.0) // Convert to MB
     }
     
-    fn calculate_confidence(&self, column_count: usize, rows_scanned: u64) -> f64 {
+    fn calculate_confidence(&self, column_count: usize, rows_scanned: u64, selectivity: Option<f64>) -> Confidence {
+        let mut factors = Vec::new();
+        
         let column_confidence = if column_count == 1 {
+            factors.push("single-column index - predictable benefit".to_string());
             0.9 // Single column indexes are very reliable
         } else if column_count <= 3 {
+            factors.push(format!("{}-column composite index", column_count));
             0.8 // Multi-column indexes are good
         } else {
+            factors.push(format!("complex {}-column index - less predictable", column_count));
             0.6 // Complex indexes are less predictable
         };
         
         let row_confidence = if rows_scanned > 10000 {
+            factors.push(format!("large scan ({} rows) - index benefit is clear", rows_scanned));
             0.9 // Large scans benefit more from indexes
         } else if rows_scanned > 1000 {
+            factors.push(format!("medium scan ({} rows)", rows_scanned));
             0.8 // Medium scans benefit well
         } else {
+            factors.push(format!("small scan ({} rows) - benefit less predictable", rows_scanned));
             0.6 // Small scans have less predictable benefit
         };
         
-        (column_confidence + row_confidence) / 2.0
+        if selectivity.is_none() && self.cost_model.is_none() {
+            factors.push("no selectivity estimate - generic improvement model".to_string());
+        }
+        
+        Confidence::new((column_confidence + row_confidence) / 2.0, factors)
     }
     
     pub fn simulate_multiple_indexes(&self, simulations: &[IndexSimulation]) -> Vec<IndexSimulation> {
@@ -1,8 +1,15 @@
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::analyzer::ExistingIndexChecker;
+use crate::recommender::index_recommender::{IndexRecommendation, IndexType};
+use crate::recommender::ReasonCode;
+
+// recommend_join_indexes ka default - isse slow join pe unindexed join column ka index recommend hota hai
+const SLOW_JOIN_THRESHOLD_MS: f64 = 200.0;
 
 /// Table join analysis - ye struct table joins analyze karta hai
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct JoinPattern {
     pub table1: String,
     pub table2: String,
@@ -10,65 +17,117 @@ pub struct JoinPattern {
     pub avg_execution_time: f64,
     pub join_type: String, // INNER, LEFT, RIGHT, etc.
     pub performance_score: f64,
+    pub join_columns: Vec<String>, // "table.column" from ON conditions
+    #[serde(default)]
+    pub join_column_pairs: Vec<(String, String)>, // ON left = right, "table.column" dono side
+}
+
+/// Ordered tables joined in a single query - ye struct multi-table join chain store karta hai
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct JoinChain {
+    pub tables: Vec<String>,
+    pub occurrence_count: u64,
+    pub avg_execution_time: f64,
+    pub has_cartesian_product: bool, // CROSS JOIN or comma join without a condition
 }
 
 /// Analyzes table join patterns - ye class table joins analyze karta hai
 pub struct JoinAnalyzer {
     join_stats: HashMap<String, JoinPattern>,
+    chain_stats: HashMap<String, JoinChain>,
+}
+
+impl Default for JoinAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl JoinAnalyzer {
     pub fn new() -> Self {
         Self {
             join_stats: HashMap::new(),
+            chain_stats: HashMap::new(),
         }
     }
     
-    /// Analyze join from parsed query - ye method join analyze karta hai
+    /// Analyze join from parsed query - ye method structured joins analyze karta hai
     pub fn analyze_join(&mut self, parsed_query: &crate::analyzer::query_parser::ParsedQuery, execution_time: u64) {
-        if parsed_query.join_conditions.is_empty() {
+        if parsed_query.joins.is_empty() {
             return;
         }
         
-        // Extract table pairs from joins - ye important hai join analysis ke liye
-        for join_condition in &parsed_query.join_conditions {
-            if let Some((table1, table2)) = self.extract_table_pair(join_condition) {
-                let key = self.create_join_key(&table1, &table2);
-                
-                let entry = self.join_stats.entry(key).or_insert_with(|| {
-                    JoinPattern {
-                        table1: table1.clone(),
-                        table2: table2.clone(),
-                        join_count: 0,
-                        avg_execution_time: 0.0,
-                        join_type: self.detect_join_type(join_condition),
-                        performance_score: 0.0,
+        self.record_chain(parsed_query, execution_time);
+        
+        // Structured joins use karta hai - aliases already resolve ho chuke hai parser mein
+        for join in &parsed_query.joins {
+            let mut key = Self::create_join_key(&join.left_table, &join.right_table);
+            if join.join_type == "CROSS" {
+                // Cartesian joins ko alag track karta hai taaki inner join stats mein mix na ho
+                key.push_str("_CROSS");
+            }
+            
+            let entry = self.join_stats.entry(key).or_insert_with(|| {
+                JoinPattern {
+                    table1: join.left_table.clone(),
+                    table2: join.right_table.clone(),
+                    join_count: 0,
+                    avg_execution_time: 0.0,
+                    join_type: join.join_type.clone(),
+                    performance_score: 0.0,
+                    join_columns: Vec::new(),
+                    join_column_pairs: Vec::new(),
+                }
+            });
+            
+            entry.join_count += 1;
+            let total_time = entry.avg_execution_time * (entry.join_count - 1) as f64 + execution_time as f64;
+            entry.avg_execution_time = total_time / entry.join_count as f64;
+            
+            for (left, right) in &join.on_columns {
+                for column in [left, right] {
+                    if !entry.join_columns.contains(column) {
+                        entry.join_columns.push(column.clone());
                     }
-                });
-                
-                entry.join_count += 1;
-                let total_time = entry.avg_execution_time * (entry.join_count - 1) as f64 + execution_time as f64;
-                entry.avg_execution_time = total_time / entry.join_count as f64;
-                
-                // Calculate performance score - ye performance score calculate karta hai
-                entry.performance_score = self.calculate_performance_score(entry);
+                }
+                let pair = (left.clone(), right.clone());
+                if !entry.join_column_pairs.contains(&pair) {
+                    entry.join_column_pairs.push(pair);
+                }
             }
+            
+            // Calculate performance score - ye performance score calculate karta hai
+            entry.performance_score = Self::calculate_performance_score(entry);
         }
     }
     
-    fn extract_table_pair(&self, join_condition: &str) -> Option<(String, String)> {
-        // Simple table pair extraction - ye basic extraction hai
-        let parts: Vec<&str> = join_condition.split_whitespace().collect();
-        if parts.len() >= 2 {
-            let table1 = parts[0].to_string();
-            let table2 = if parts.len() > 2 { parts[2].to_string() } else { parts[1].to_string() };
-            Some((table1, table2))
-        } else {
-            None
+    fn record_chain(&mut self, parsed_query: &crate::analyzer::query_parser::ParsedQuery, execution_time: u64) {
+        // Chain order = FROM table followed by each joined table
+        let mut tables = vec![parsed_query.joins[0].left_table.clone()];
+        for join in &parsed_query.joins {
+            if !tables.contains(&join.right_table) {
+                tables.push(join.right_table.clone());
+            }
         }
+        
+        // Sirf CROSS JOIN / bina predicate ka comma join - NATURAL, USING aur non-equi ON joins ke
+        // on_columns khaali ho sakte hai lekin wo Cartesian nahi hai
+        let has_cartesian_product = parsed_query.joins.iter().any(|join| join.join_type == "CROSS");
+        
+        let entry = self.chain_stats.entry(tables.join("->")).or_insert_with(|| JoinChain {
+            tables,
+            occurrence_count: 0,
+            avg_execution_time: 0.0,
+            has_cartesian_product,
+        });
+        
+        entry.occurrence_count += 1;
+        entry.has_cartesian_product |= has_cartesian_product;
+        let total_time = entry.avg_execution_time * (entry.occurrence_count - 1) as f64 + execution_time as f64;
+        entry.avg_execution_time = total_time / entry.occurrence_count as f64;
     }
     
-    fn create_join_key(&self, table1: &str, table2: &str) -> String {
+    fn create_join_key(table1: &str, table2: &str) -> String {
         // Create consistent key for table pairs - ye consistent key banata hai
         if table1 < table2 {
             format!("{}_JOIN_{}", table1, table2)
@@ -77,20 +136,7 @@ impl JoinAnalyzer {
         }
     }
     
-    fn detect_join_type(&self, join_condition: &str) -> String {
-        let condition_upper = join_condition.to_uppercase();
-        if condition_upper.contains("LEFT JOIN") {
-            "LEFT".to_string()
-        } else if condition_upper.contains("RIGHT JOIN") {
-            "RIGHT".to_string()
-        } else if condition_upper.contains("INNER JOIN") {
-            "INNER".to_string()
-        } else {
-            "INNER".to_string() // Default
-        }
-    }
-    
-    fn calculate_performance_score(&self, join_pattern: &JoinPattern) -> f64 {
+    fn calculate_performance_score(join_pattern: &JoinPattern) -> f64 {
         // Performance score based on frequency and execution time - ye performance score calculate karta hai
         let frequency_score = (join_pattern.join_count as f64 / 100.0).min(1.0);
         let time_score = (1000.0 / join_pattern.avg_execution_time).min(1.0);
@@ -99,19 +145,38 @@ impl JoinAnalyzer {
     
     /// Get most frequent joins - ye method sabse zyada frequent joins deta hai
     pub fn get_frequent_joins(&self, limit: usize) -> Vec<&JoinPattern> {
-        let mut joins: Vec<&JoinPattern> = self.join_stats.values().collect();
-        joins.sort_by(|a, b| b.join_count.cmp(&a.join_count));
+        let mut joins = self.sorted_joins();
+        joins.sort_by_key(|join| std::cmp::Reverse(join.join_count)); // stable sort - ties key order mein rehte hai
         joins.into_iter().take(limit).collect()
     }
     
     /// Get slow joins - ye method slow joins identify karta hai
     pub fn get_slow_joins(&self, threshold_ms: f64) -> Vec<&JoinPattern> {
-        self.join_stats
-            .values()
+        self.sorted_joins()
+            .into_iter()
             .filter(|join| join.avg_execution_time > threshold_ms)
             .collect()
     }
     
+    /// Get expensive join chains - ye method slow multi-table chains deta hai (slowest first)
+    pub fn get_expensive_chains(&self, threshold_ms: f64) -> Vec<&JoinChain> {
+        let mut chains: Vec<(&String, &JoinChain)> = self.chain_stats
+            .iter()
+            .filter(|(_, chain)| chain.avg_execution_time > threshold_ms)
+            .collect();
+        chains.sort_by(|(a_key, a), (b_key, b)| {
+            b.avg_execution_time.total_cmp(&a.avg_execution_time).then_with(|| a_key.cmp(b_key))
+        });
+        chains.into_iter().map(|(_, chain)| chain).collect()
+    }
+    
+    // HashMap iteration order har run mein alag hota hai - key se sort karke output reproducible rehta hai
+    fn sorted_joins(&self) -> Vec<&JoinPattern> {
+        let mut entries: Vec<(&String, &JoinPattern)> = self.join_stats.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        entries.into_iter().map(|(_, join)| join).collect()
+    }
+    
     /// Get join recommendations - ye method join recommendations deta hai
     pub fn get_join_recommendations(&self) -> Vec<String> {
         let mut recommendations = Vec::new();
@@ -129,6 +194,16 @@ impl JoinAnalyzer {
             ));
         }
         
+        // Check for Cartesian products - bina condition ke joins usually galti se hote hai
+        for join in self.sorted_joins() {
+            if join.join_type == "CROSS" && join.join_columns.is_empty() {
+                recommendations.push(format!(
+                    "CROSS JOIN between {} and {} has no join condition ({} occurrences) - likely an accidental Cartesian product",
+                    join.table1, join.table2, join.join_count
+                ));
+            }
+        }
+        
         // Check for performance trends - ye performance trends check karta hai
         let high_performance_joins: Vec<_> = self.join_stats.values()
             .filter(|join| join.performance_score > 0.8)
@@ -144,6 +219,64 @@ impl JoinAnalyzer {
         recommendations
     }
     
+    /// Recommend indexes for slow joins - join column jiska koi existing index nahi hai uske liye
+    /// ready-to-apply IndexRecommendation deta hai (create_index_sql se statement milta hai).
+    /// Primary keys bhi checker mein registered honi chahiye, warna unka side bhi recommend hoga
+    pub fn recommend_join_indexes(&self, checker: &ExistingIndexChecker) -> Vec<IndexRecommendation> {
+        self.recommend_join_indexes_above(checker, SLOW_JOIN_THRESHOLD_MS)
+    }
+    
+    /// recommend_join_indexes jaisa, lekin slow join ka threshold caller deta hai
+    pub fn recommend_join_indexes_above(&self, checker: &ExistingIndexChecker, threshold_ms: f64) -> Vec<IndexRecommendation> {
+        let mut recommendations: Vec<IndexRecommendation> = Vec::new();
+        
+        for join in self.get_slow_joins(threshold_ms) {
+            for (left, right) in &join.join_column_pairs {
+                for column in [left, right] {
+                    let Some((table, column_name)) = column.split_once('.') else {
+                        continue;
+                    };
+                    
+                    if recommendations.iter().any(|r| r.table == table && r.column == column_name) {
+                        continue;
+                    }
+                    
+                    // Leading column wala index ho to join lookup already serve ho jata hai
+                    let is_indexed = checker
+                        .check_for_conflicts(table, &[column_name.to_string()])
+                        .iter()
+                        .any(|c| c.conflict_type == "duplicate" || c.conflict_type == "prefix_redundant");
+                    if is_indexed {
+                        continue;
+                    }
+                    
+                    let base = if join.avg_execution_time > threshold_ms * 5.0 { 100 } else { 50 };
+                    let freq_bonus = (join.join_count / 10).min(50) as u32;
+                    
+                    recommendations.push(IndexRecommendation {
+                        table: table.to_string(),
+                        column: column_name.to_string(),
+                        index_type: IndexType::BTree,
+                        priority: base + freq_bonus,
+                        estimated_improvement_percent: 40.0 + (join.join_count as f64 / 100.0).min(30.0),
+                        reason: format!(
+                            "{} JOIN between {} and {} is slow (avg {:.2}ms over {} joins) and {} is unindexed",
+                            join.join_type, left, right, join.avg_execution_time, join.join_count, column
+                        ),
+                        reason_code: ReasonCode::UnindexedJoin,
+                        include_columns: Vec::new(),
+                        operator_class: None,
+                        composite_columns: Vec::new(),
+                        column_order_rationale: None,
+                    });
+                }
+            }
+        }
+        
+        recommendations.sort_by_key(|rec| std::cmp::Reverse(rec.priority));
+        recommendations
+    }
+    
     /// Get join statistics summary - ye method join statistics summary deta hai
     pub fn get_join_summary(&self) -> (usize, f64, usize) {
         let total_joins = self.join_stats.len();
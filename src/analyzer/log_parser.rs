@@ -0,0 +1,539 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use flate2::read::MultiGzDecoder;
+
+use super::{QueryLog, QueryParser};
+
+// Postgres csvlog column positions (PG 9.0+ layout)
+const PG_CSV_MIN_COLUMNS: usize = 23;
+const PG_LOG_TIME: usize = 0;
+const PG_SESSION_ID: usize = 5;
+const PG_MESSAGE: usize = 13;
+const PG_QUERY: usize = 19;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum LogParseError {
+    UnterminatedQuote { line: usize },
+    MissingColumns { line: usize, found: usize, expected: usize },
+    InvalidTimestamp { line: usize, value: String },
+    InvalidDuration { line: usize, value: String },
+    Io { path: String, message: String },
+}
+
+impl fmt::Display for LogParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogParseError::UnterminatedQuote { line } => write!(f, "line {}: unterminated quoted field", line),
+            LogParseError::MissingColumns { line, found, expected } => {
+                write!(f, "line {}: expected at least {} columns, found {}", line, expected, found)
+            }
+            LogParseError::InvalidTimestamp { line, value } => write!(f, "line {}: invalid timestamp '{}'", line, value),
+            LogParseError::InvalidDuration { line, value } => write!(f, "line {}: invalid duration '{}'", line, value),
+            LogParseError::Io { path, message } => write!(f, "{}: {}", path, message),
+        }
+    }
+}
+
+impl std::error::Error for LogParseError {}
+
+// Database log files se QueryLog entries banata hai
+impl QueryLog {
+    //yaha pe badme general query log bhi add karna ha
+    pub fn parse_mysql_slow_log(contents: &str) -> Vec<QueryLog> {
+        MySqlSlowLogStream::new(contents.lines().map(Ok::<_, LogParseError>)).filter_map(Result::ok).collect()
+    }
+
+    /// File wrapper - ".gz" extension ho to streaming gzip decode, poori file memory mein nahi aati
+    pub fn parse_mysql_slow_log_file(path: &Path) -> Result<Vec<QueryLog>, LogParseError> {
+        Self::stream_mysql_slow_log_file(path)?.collect()
+    }
+
+    /// Lazy version - har entry parse hote hi yield hoti hai, 50GB log bhi bounded memory mein
+    pub fn stream_mysql_slow_log_file(
+        path: &Path,
+    ) -> Result<impl Iterator<Item = Result<QueryLog, LogParseError>>, LogParseError> {
+        Ok(MySqlSlowLogStream::new(read_log_lines(path)?))
+    }
+
+    // log_min_duration_statement ("duration: N ms  statement: ...") aur log_duration + log_statement
+    // dono formats handle karta hai - duration-only lines usi session ke last statement se pair hoti hai
+    pub fn parse_postgres_csvlog(contents: &str) -> Result<Vec<QueryLog>, LogParseError> {
+        PostgresCsvLogStream::new(read_csv_records(contents)?.into_iter().map(Ok)).collect()
+    }
+
+    /// File wrapper - ".gz" ho to streaming decode; records ek ek karke parse hote hai
+    pub fn parse_postgres_csvlog_file(path: &Path) -> Result<Vec<QueryLog>, LogParseError> {
+        Self::stream_postgres_csvlog_file(path)?.collect()
+    }
+
+    /// Lazy version - pehli error ke baad iterator aage kuch yield nahi karta
+    pub fn stream_postgres_csvlog_file(
+        path: &Path,
+    ) -> Result<impl Iterator<Item = Result<QueryLog, LogParseError>>, LogParseError> {
+        Ok(PostgresCsvLogStream::new(CsvRecordStream::new(read_log_lines(path)?)))
+    }
+}
+
+// MySQL slow log lines -> QueryLog, ek entry complete hote hi yield
+struct MySqlSlowLogStream<I> {
+    lines: I,
+    entry: SlowLogEntry,
+    finished: bool,
+}
+
+impl<I> MySqlSlowLogStream<I> {
+    fn new(lines: I) -> Self {
+        Self {
+            lines,
+            entry: SlowLogEntry::default(),
+            finished: false,
+        }
+    }
+
+    // Har line zyada se zyada ek pichli entry flush karti hai
+    fn process_line(&mut self, line: &str) -> Option<QueryLog> {
+        let entry = &mut self.entry;
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+
+        if let Some(header) = trimmed.strip_prefix('#') {
+            let header = header.trim();
+            let mut flushed = None;
+            // "# Time:" starts a new entry - pichli entry ko flush karo
+            if let Some(time) = header.strip_prefix("Time:") {
+                flushed = entry.flush();
+                entry.timestamp = parse_iso_timestamp(time.trim());
+            } else if header.starts_with("Query_time:") {
+                if !entry.statement.is_empty() {
+                    flushed = entry.flush();
+                }
+                entry.query_time_secs = header_value(header, "Query_time:").and_then(|v| v.parse().ok());
+                entry.rows_examined = header_value(header, "Rows_examined:").and_then(|v| v.parse().ok());
+                entry.rows_sent = header_value(header, "Rows_sent:").and_then(|v| v.parse().ok());
+            } else if header.starts_with("administrator command:") {
+                entry.is_admin = true;
+            }
+            return flushed;
+        }
+
+        let upper = trimmed.to_uppercase();
+        // SET timestamp=N; is the exact unix time of the query - statement nahi hai
+        if let Some(value) = upper.strip_prefix("SET TIMESTAMP=") {
+            if let Ok(timestamp) = value.trim_end_matches(';').trim().parse() {
+                entry.timestamp = Some(timestamp);
+            }
+            return None;
+        }
+        // Server banner lines and "use db;" noise
+        if upper.starts_with("USE ")
+            || upper.starts_with("TCP PORT:")
+            || upper.starts_with("TIME ")
+            || trimmed.contains(", Version:")
+        {
+            return None;
+        }
+
+        if !entry.statement.is_empty() {
+            entry.statement.push(' ');
+        }
+        entry.statement.push_str(trimmed);
+        None
+    }
+}
+
+impl<S: AsRef<str>, I: Iterator<Item = Result<S, LogParseError>>> Iterator for MySqlSlowLogStream<I> {
+    type Item = Result<QueryLog, LogParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        loop {
+            let Some(line) = self.lines.next() else {
+                self.finished = true;
+                return self.entry.flush().map(Ok);
+            };
+            match line {
+                Ok(line) => {
+                    if let Some(log) = self.process_line(line.as_ref()) {
+                        return Some(Ok(log));
+                    }
+                }
+                Err(err) => {
+                    self.finished = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+// Postgres csvlog records -> QueryLog; pending statements per session state mein rehte hai
+struct PostgresCsvLogStream<R> {
+    records: R,
+    pending_statements: HashMap<String, (String, u64)>,
+    finished: bool,
+}
+
+impl<R> PostgresCsvLogStream<R> {
+    fn new(records: R) -> Self {
+        Self {
+            records,
+            pending_statements: HashMap::new(),
+            finished: false,
+        }
+    }
+
+    // None = record se koi QueryLog nahi bana (log_statement line, unmatched duration)
+    fn process_record(
+        pending_statements: &mut HashMap<String, (String, u64)>,
+        line: usize,
+        record: Vec<String>,
+    ) -> Result<Option<QueryLog>, LogParseError> {
+        if record.len() < PG_CSV_MIN_COLUMNS {
+            return Err(LogParseError::MissingColumns {
+                line,
+                found: record.len(),
+                expected: PG_CSV_MIN_COLUMNS,
+            });
+        }
+
+        let log_time = record[PG_LOG_TIME].trim();
+        let timestamp = parse_iso_timestamp(log_time).ok_or_else(|| LogParseError::InvalidTimestamp {
+            line,
+            value: log_time.to_string(),
+        })?;
+        let session_id = record[PG_SESSION_ID].clone();
+        let message = record[PG_MESSAGE].trim();
+
+        let Some(duration_text) = message.strip_prefix("duration:") else {
+            // log_statement line - duration baad mein aayega
+            if let Some(statement) = strip_statement_prefix(message) {
+                pending_statements.insert(session_id, (statement.to_string(), timestamp));
+            }
+            return Ok(None);
+        };
+
+        let (duration_part, statement_part) = match duration_text.split_once(" ms") {
+            Some((duration, rest)) => (duration.trim(), rest.trim()),
+            None => (duration_text.trim(), ""),
+        };
+        let duration_ms: f64 = duration_part.parse().map_err(|_| LogParseError::InvalidDuration {
+            line,
+            value: duration_part.to_string(),
+        })?;
+
+        let (statement, statement_time) = match strip_statement_prefix(statement_part) {
+            Some(statement) => (statement.to_string(), timestamp),
+            None if !record[PG_QUERY].trim().is_empty() => (record[PG_QUERY].trim().to_string(), timestamp),
+            None => match pending_statements.remove(&session_id) {
+                Some(pending) => pending,
+                None => return Ok(None),
+            },
+        };
+
+        let query = statement.trim().trim_end_matches(';').to_string();
+        let tables = QueryParser.parse(&query).from_tables;
+        Ok(Some(QueryLog::new(query, duration_ms.round() as u64, statement_time, tables, 0)))
+    }
+}
+
+impl<R: Iterator<Item = Result<(usize, Vec<String>), LogParseError>>> Iterator for PostgresCsvLogStream<R> {
+    type Item = Result<QueryLog, LogParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        for record in self.records.by_ref() {
+            let result = record.and_then(|(line, record)| Self::process_record(&mut self.pending_statements, line, record));
+            match result {
+                Ok(Some(log)) => return Some(Ok(log)),
+                Ok(None) => continue,
+                Err(err) => {
+                    self.finished = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        self.finished = true;
+        None
+    }
+}
+
+// Plain ya gzip file ki lines - invalid UTF-8 bytes lossy replace hote hai, ek kharab byte
+// pe multi-GB log ka parse fail nahi hona chahiye
+fn read_log_lines(path: &Path) -> Result<impl Iterator<Item = Result<String, LogParseError>>, LogParseError> {
+    let file = File::open(path).map_err(|err| io_error_for(path, err))?;
+    let reader: Box<dyn BufRead> = if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("gz")) {
+        // MultiGz - logrotate ke concatenated gzip members bhi padh leta hai
+        Box::new(BufReader::new(MultiGzDecoder::new(BufReader::new(file))))
+    } else {
+        Box::new(BufReader::new(file))
+    };
+
+    let path = path.to_path_buf();
+    Ok(reader.split(b'\n').map(move |line| {
+        line.map(|bytes| String::from_utf8_lossy(&bytes).trim_end_matches('\r').to_string())
+            .map_err(|err| io_error_for(&path, err))
+    }))
+}
+
+fn io_error_for(path: &Path, err: std::io::Error) -> LogParseError {
+    LogParseError::Io {
+        path: path.display().to_string(),
+        message: err.to_string(),
+    }
+}
+
+// Line-by-line CSV records - quoted field ke andar newline ho to record agli line tak chalta hai,
+// isliye sirf ek record buffer mein rehta hai
+struct CsvRecordStream<I> {
+    lines: I,
+    buffer: String,
+    in_quotes: bool,
+    line: usize,
+    record_line: usize,
+}
+
+impl<I> CsvRecordStream<I> {
+    fn new(lines: I) -> Self {
+        Self {
+            lines,
+            buffer: String::new(),
+            in_quotes: false,
+            line: 0,
+            record_line: 1,
+        }
+    }
+
+    // Buffer mein exactly ek record hai; line numbers file ke hisaab se shift karta hai
+    fn parse_buffered(&self, buffer: &str) -> Result<(usize, Vec<String>), LogParseError> {
+        let offset = self.record_line - 1;
+        let shift = |err: LogParseError| match err {
+            LogParseError::UnterminatedQuote { line } => LogParseError::UnterminatedQuote { line: line + offset },
+            other => other,
+        };
+        let mut records = read_csv_records(buffer).map_err(shift)?;
+        let (line, fields) = records.pop().unwrap_or((1, Vec::new()));
+        Ok((line + offset, fields))
+    }
+}
+
+impl<I: Iterator<Item = Result<String, LogParseError>>> Iterator for CsvRecordStream<I> {
+    type Item = Result<(usize, Vec<String>), LogParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(line) = self.lines.next() else {
+                if self.buffer.trim().is_empty() {
+                    return None;
+                }
+                // Quote kabhi band nahi hua - read_csv_records UnterminatedQuote dega
+                let buffer = std::mem::take(&mut self.buffer);
+                return Some(self.parse_buffered(&buffer));
+            };
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err)),
+            };
+
+            self.line += 1;
+            if self.buffer.is_empty() {
+                self.record_line = self.line;
+            }
+            self.buffer.push_str(&line);
+            self.buffer.push('\n');
+
+            // "" escapes quote count even rakhte hai - odd count = quoted field khula ya band hua
+            if line.matches('"').count() % 2 == 1 {
+                self.in_quotes = !self.in_quotes;
+            }
+            if self.in_quotes {
+                continue;
+            }
+            let buffer = std::mem::take(&mut self.buffer);
+            if buffer.trim().is_empty() {
+                continue;
+            }
+            return Some(self.parse_buffered(&buffer));
+        }
+    }
+}
+
+// "statement: SELECT ..." / "execute S_1: SELECT ..." -> SQL text
+fn strip_statement_prefix(message: &str) -> Option<&str> {
+    if let Some(statement) = message.strip_prefix("statement:") {
+        return Some(statement.trim());
+    }
+    let rest = message.strip_prefix("execute ")?;
+    rest.split_once(':').map(|(_, statement)| statement.trim())
+}
+
+// RFC 4180 records - quoted fields mein commas, "" escapes aur newlines ho sakte hai.
+// Returns (starting line number, fields)
+pub(crate) fn read_csv_records(contents: &str) -> Result<Vec<(usize, Vec<String>)>, LogParseError> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = contents.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            '\n' if !in_quotes => {
+                fields.push(std::mem::take(&mut field));
+                let record = std::mem::take(&mut fields);
+                if !(record.len() == 1 && record[0].trim().is_empty()) {
+                    records.push((record_line, record));
+                }
+                line += 1;
+                record_line = line;
+            }
+            '\r' if !in_quotes => {}
+            _ => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+
+    if in_quotes {
+        return Err(LogParseError::UnterminatedQuote { line: record_line });
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((record_line, fields));
+    }
+
+    Ok(records)
+}
+
+#[derive(Default)]
+struct SlowLogEntry {
+    timestamp: Option<u64>,
+    query_time_secs: Option<f64>,
+    rows_examined: Option<u64>,
+    rows_sent: Option<u64>,
+    is_admin: bool,
+    statement: String,
+}
+
+impl SlowLogEntry {
+    fn flush(&mut self) -> Option<QueryLog> {
+        let entry = std::mem::take(self);
+        // Timestamp next entry tak carry hota hai jab tak naya "# Time:" na aaye
+        self.timestamp = entry.timestamp;
+
+        let query = entry.statement.trim().trim_end_matches(';').trim().to_string();
+        let query_time_secs = entry.query_time_secs?;
+        if query.is_empty() || entry.is_admin || query.starts_with("# administrator command") {
+            return None;
+        }
+
+        let tables = QueryParser.parse(&query).from_tables;
+        let log = QueryLog::new(
+            query,
+            (query_time_secs * 1000.0).round() as u64,
+            entry.timestamp.unwrap_or(0),
+            tables,
+            entry.rows_examined.unwrap_or(0),
+        );
+        Some(match entry.rows_sent {
+            Some(rows_sent) => log.with_rows_returned(rows_sent),
+            None => log,
+        })
+    }
+}
+
+// "Query_time: 2.5  Lock_time: 0.0 ..." -> value after the given key
+fn header_value<'a>(header: &'a str, key: &str) -> Option<&'a str> {
+    let start = header.find(key)? + key.len();
+    header[start..].split_whitespace().next()
+}
+
+// "2024-01-15T10:23:45.123456Z", "2024-01-15T10:23:45+05:30", "2024-01-15 10:23:45" ya csvlog ka
+// "2024-01-15 10:23:45.123 UTC" / "... +0530" -> unix seconds (UTC). Named zones (IST, CET) ambiguous hai - None
+pub(crate) fn parse_iso_timestamp(text: &str) -> Option<u64> {
+    let (date, time) = text.trim().split_once(['T', ' '])?;
+    let mut date_parts = date.split('-').map(|p| p.parse::<i64>().ok());
+    let (year, month, day) = (date_parts.next()??, date_parts.next()??, date_parts.next()??);
+
+    let mut fields = time.split_whitespace();
+    let (clock, mut offset_secs) = split_utc_offset(fields.next()?)?;
+    match fields.next() {
+        None => {}
+        Some("UTC" | "GMT" | "Z") => {}
+        Some(zone) if zone.starts_with(['+', '-']) => offset_secs += parse_utc_offset(zone)?,
+        Some(_) => return None,
+    }
+    if fields.next().is_some() {
+        return None;
+    }
+    let clock = clock.split('.').next()?;
+    let mut time_parts = clock.split(':').map(|p| p.parse::<i64>().ok());
+    let (hour, minute) = (time_parts.next()??, time_parts.next()??);
+    // Seconds optional hai, lekin diye hai to valid hone chahiye
+    let second = match time_parts.next() {
+        Some(second) => second?,
+        None => 0,
+    };
+
+    // Days since epoch - Howard Hinnant's civil-from-days algorithm
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    let seconds = days * 86400 + hour * 3600 + minute * 60 + second - offset_secs;
+    u64::try_from(seconds).ok()
+}
+
+// "10:23:45Z" / "10:23:45.5+05:30" / "10:23:45-0800" -> (clock, offset in seconds east of UTC)
+fn split_utc_offset(time: &str) -> Option<(&str, i64)> {
+    if let Some(clock) = time.strip_suffix('Z') {
+        return Some((clock, 0));
+    }
+    match time.rfind(['+', '-']) {
+        Some(position) => {
+            let (clock, zone) = time.split_at(position);
+            Some((clock, parse_utc_offset(zone)?))
+        }
+        None => Some((time, 0)),
+    }
+}
+
+// "+05:30" / "-0800" / "+05" -> seconds
+fn parse_utc_offset(zone: &str) -> Option<i64> {
+    let sign = if zone.starts_with('-') { -1 } else { 1 };
+    let digits: String = zone[1..].chars().filter(|c| *c != ':').collect();
+    if !digits.chars().all(|c| c.is_ascii_digit()) || !matches!(digits.len(), 2 | 4) {
+        return None;
+    }
+    let hours: i64 = digits[..2].parse().ok()?;
+    let minutes: i64 = if digits.len() == 4 { digits[2..].parse().ok()? } else { 0 };
+    if hours > 14 || minutes > 59 {
+        return None;
+    }
+    Some(sign * (hours * 3600 + minutes * 60))
+}
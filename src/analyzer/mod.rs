@@ -1,41 +1,57 @@
 pub mod query_log;
+pub mod log_parser;
 pub mod pattern_analyzer;
 pub mod query_parser;
+pub mod sargability_analyzer;
 pub mod column_tracker;
 pub mod time_analyzer;
 pub mod query_fingerprinter;
+pub mod template_interner;
 pub mod join_analyzer;
+pub mod confidence;
+pub mod improvement;
+pub mod clock;
 pub mod cost_calculator;
 pub mod anomaly_detector;
 pub mod export;
 pub mod existing_index_checker;
+pub mod index_cost_model;
 pub mod index_usage_simulator;
 pub mod partial_index_recommender;
 pub mod index_maintenance_cost;
 pub mod priority_scoring;
 pub mod index_removal_recommender;
 pub mod query_plan_analyzer;
+pub mod pagination_advisor;
 pub mod schema_optimizer;
 pub mod performance_monitor;
 pub mod deadlock_detector;
 
 pub use query_log::QueryLog;
-pub use pattern_analyzer::{PatternAnalyzer, QueryPattern};
-pub use query_parser::{QueryParser, ParsedQuery};
-pub use column_tracker::{ColumnTracker, ColumnUsage};
+pub use log_parser::LogParseError;
+pub use pattern_analyzer::{PatternAnalyzer, QueryPattern, ScoringStrategy};
+pub use query_parser::{QueryParser, ParsedQuery, JoinClause, SelectItem};
+pub use sargability_analyzer::{SargabilityAnalyzer, NonSargablePredicate, TypeMismatch};
+pub use column_tracker::{ColumnTracker, ColumnUsage, PredicateKind};
 pub use time_analyzer::{TimeAnalyzer, TimePattern};
 pub use query_fingerprinter::{QueryFingerprinter, QueryFingerprint};
-pub use join_analyzer::{JoinAnalyzer, JoinPattern};
-pub use cost_calculator::{CostCalculator, QueryCost};
-pub use anomaly_detector::{AnomalyDetector, AnomalyResult};
-pub use export::DataExporter;
+pub use template_interner::TemplateInterner;
+pub use join_analyzer::{JoinAnalyzer, JoinPattern, JoinChain};
+pub use confidence::Confidence;
+pub use improvement::percent_improvement;
+pub use clock::{Clock, SystemClock, ManualClock};
+pub use cost_calculator::{BudgetBreach, CostCalculator, QueryCost};
+pub use anomaly_detector::{AnomalyDetector, AnomalyResult, BaselineMode};
+pub use export::{DataExporter, SqlExportOptions};
 pub use existing_index_checker::{ExistingIndexChecker, ExistingIndex, IndexConflict};
+pub use index_cost_model::{IndexCostModel, BTreeCostModel, HashCostModel, GinCostModel};
 pub use index_usage_simulator::{IndexUsageSimulator, IndexSimulation};
-pub use partial_index_recommender::{PartialIndexRecommender, PartialIndexRecommendation};
+pub use partial_index_recommender::{PartialIndexRecommender, PartialIndexRecommendation, ColumnStats, FilterCondition, FilterValue};
 pub use index_maintenance_cost::{IndexMaintenanceCostAnalyzer, MaintenanceCost};
-pub use priority_scoring::{PriorityScoringAlgorithm, PriorityScore};
-pub use index_removal_recommender::{IndexRemovalRecommender, IndexUsageStats, RemovalRecommendation};
+pub use priority_scoring::{PriorityScoringAlgorithm, PriorityScore, WeightError};
+pub use index_removal_recommender::{IndexRemovalRecommender, IndexUsageStats, RemovalRecommendation, RemovalImpact};
 pub use query_plan_analyzer::{QueryPlanAnalyzer, QueryPlan, PlanOperation};
-pub use schema_optimizer::{SchemaOptimizer, TableSchema, ColumnInfo, SchemaOptimization};
-pub use performance_monitor::{PerformanceMonitor, PerformanceMetric, PerformanceAlert};
-pub use deadlock_detector::{DeadlockDetector, DeadlockInfo, DeadlockPrevention};
\ No newline at end of file
+pub use pagination_advisor::{PaginationAdvisor, PaginationRecommendation};
+pub use schema_optimizer::{SchemaOptimizer, SqlDialect, TableSchema, ColumnInfo, SchemaOptimization, ForeignKey};
+pub use performance_monitor::{PerformanceMonitor, PerformanceMetric, PerformanceAlert, MetricBucket, AlertCallback};
+pub use deadlock_detector::{DeadlockDetector, DeadlockInfo, DeadlockPrevention, IsolationLevel, LockOrderViolation};
\ No newline at end of file
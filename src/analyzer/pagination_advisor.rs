@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::analyzer::{ExistingIndex, ParsedQuery, QueryParser, QueryPattern};
+use crate::recommender::ReasonCode;
+
+/// Query rewrite recommendation - bina LIMIT wale bade SELECT ko paginate karna
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PaginationRecommendation {
+    pub table_name: String,
+    pub query_template: String,
+    pub frequency: u64,
+    pub avg_rows_scanned: u64,
+    pub estimated_rows_returned: Option<u64>, // scan amplification se, log source rows_returned na de to None
+    pub suggested_limit: u64,
+    pub suggested_rewrite: String,
+    pub estimated_savings_percent: f64,
+    pub estimated_time_saved_ms: f64, // saari executions ka total, per run nahi
+    pub priority: u32,
+    pub reason: String,
+    #[serde(default)]
+    pub reason_code: ReasonCode,
+}
+
+// GROUP BY / bina index ka ORDER BY - engine LIMIT se pehle har row scan aur sort karta hai,
+// sirf result transfer bachta hai
+const FULL_SORT_SAVINGS_FACTOR: f64 = 0.1;
+
+/// Frequent, unbounded SELECTs dhoondhta hai jo har baar hazaaron rows laate hai. Ye sirf shape dekhta
+/// hai - result sach mein sirf display ke liye hai ya nahi, ye caller ko confirm karna hoga
+pub struct PaginationAdvisor {
+    min_frequency: u64,
+    min_rows_per_query: u64,
+    page_size: u64,
+    existing_indexes: Vec<ExistingIndex>, // ORDER BY index se serve hota hai ya nahi
+}
+
+impl Default for PaginationAdvisor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PaginationAdvisor {
+    pub fn new() -> Self {
+        Self::with_thresholds(10, 10_000, 50)
+    }
+
+    pub fn with_thresholds(min_frequency: u64, min_rows_per_query: u64, page_size: u64) -> Self {
+        Self {
+            min_frequency,
+            min_rows_per_query,
+            page_size: page_size.max(1),
+            existing_indexes: Vec::new(),
+        }
+    }
+
+    /// Known index register karta hai - ORDER BY uske leading columns pe ho to LIMIT sort bhi bacha leta hai
+    pub fn add_existing_index(&mut self, index: ExistingIndex) {
+        self.existing_indexes.push(index);
+    }
+
+    /// analyze_by_fingerprint() ke patterns chahiye - template ke bina query shape pata nahi chalta
+    pub fn recommend(&self, patterns: &[QueryPattern]) -> Vec<PaginationRecommendation> {
+        let mut recommendations: Vec<PaginationRecommendation> =
+            patterns.iter().filter_map(|pattern| self.check_pattern(pattern)).collect();
+        recommendations.sort_by_key(|rec| std::cmp::Reverse(rec.priority));
+        recommendations
+    }
+
+    fn check_pattern(&self, pattern: &QueryPattern) -> Option<PaginationRecommendation> {
+        if pattern.query_type != "SELECT" || pattern.frequency < self.min_frequency {
+            return None;
+        }
+        let template = pattern.fingerprint.as_deref()?.trim().trim_end_matches(';');
+        let parsed = QueryParser.parse(template);
+        if parsed.limit.is_some() {
+            return None;
+        }
+        // SELECT COUNT(*) / SUM(..) bina GROUP BY ek hi row deta hai
+        let single_row = parsed.group_by_columns.is_empty()
+            && !parsed.select_items.is_empty()
+            && parsed.select_items.iter().all(|item| item.is_aggregate);
+        if single_row {
+            return None;
+        }
+
+        let avg_rows_scanned = pattern.total_rows_scanned / pattern.frequency.max(1);
+        if avg_rows_scanned < self.min_rows_per_query {
+            return None;
+        }
+        let estimated_rows_returned = pattern
+            .avg_scan_amplification
+            .map(|amplification| (avg_rows_scanned as f64 / amplification.max(1.0)).round() as u64);
+        // Chhota result set - LIMIT se kuch nahi bachega, problem index ki hai
+        let rows_out = estimated_rows_returned.unwrap_or(avg_rows_scanned);
+        if rows_out <= self.page_size * 2 {
+            return None;
+        }
+
+        let table_name = parsed.from_tables.first().cloned().or_else(|| pattern.tables.first().cloned())?;
+        let sorts_every_row = !parsed.group_by_columns.is_empty()
+            || (!parsed.order_by_columns.is_empty() && !self.order_is_indexed(&table_name, &parsed));
+        let mut savings_fraction = 1.0 - self.page_size as f64 / rows_out as f64;
+        if sorts_every_row {
+            savings_fraction *= FULL_SORT_SAVINGS_FACTOR;
+        }
+        let estimated_time_saved_ms = pattern.avg_execution_time_ms * savings_fraction * pattern.frequency as f64;
+        let ordering_note = if !parsed.group_by_columns.is_empty() {
+            "GROUP BY still aggregates every row before the LIMIT, so the savings are mostly transfer".to_string()
+        } else if parsed.order_by_columns.is_empty() {
+            "add a deterministic ORDER BY (e.g. the primary key) so pages are stable".to_string()
+        } else if !sorts_every_row {
+            "the ORDER BY is served by an existing index".to_string()
+        } else {
+            format!(
+                "index the ORDER BY columns ({}) or the database still sorts every row before applying the LIMIT",
+                parsed.order_by_columns.join(", ")
+            )
+        };
+
+        Some(PaginationRecommendation {
+            table_name,
+            query_template: template.to_string(),
+            frequency: pattern.frequency,
+            avg_rows_scanned,
+            estimated_rows_returned,
+            suggested_limit: self.page_size,
+            suggested_rewrite: format!("{} LIMIT {}", template, self.page_size),
+            estimated_savings_percent: savings_fraction * 100.0,
+            estimated_time_saved_ms,
+            priority: self.calculate_priority(pattern, savings_fraction),
+            reason: format!(
+                "Unbounded SELECT runs {} times and reads ~{} rows each time - consider adding LIMIT/pagination \
+                 (keyset pagination for deep pages); {}",
+                pattern.frequency, rows_out, ordering_note
+            ),
+            reason_code: ReasonCode::UnboundedResult,
+        })
+    }
+
+    // ORDER BY columns kisi index ke leading columns hai (same order) to engine top-N seedha index se padhta hai
+    fn order_is_indexed(&self, table_name: &str, parsed: &ParsedQuery) -> bool {
+        let order_columns: Vec<String> = parsed
+            .order_by_columns
+            .iter()
+            .filter_map(|item| item.split_whitespace().next())
+            .map(|column| parsed.resolve_column(column))
+            .map(|column| column.rsplit('.').next().unwrap_or(&column).to_lowercase())
+            .collect();
+        self.existing_indexes
+            .iter()
+            .filter(|index| index.table_name == table_name && !index.is_partial)
+            .any(|index| {
+                index.column_names.len() >= order_columns.len()
+                    && index.column_names.iter().zip(&order_columns).all(|(indexed, column)| indexed.eq_ignore_ascii_case(column))
+            })
+    }
+
+    // IndexRecommender jaisa scale - 50 base, frequency aur savings ke bonus
+    fn calculate_priority(&self, pattern: &QueryPattern, savings_fraction: f64) -> u32 {
+        let freq_bonus = (pattern.frequency / 10).min(50) as u32;
+        let savings_bonus = (savings_fraction * 30.0).round() as u32;
+        50 + freq_bonus + savings_bonus
+    }
+}
@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use std::collections::HashMap;
+use crate::analyzer::QueryParser;
+use crate::recommender::ReasonCode;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PartialIndexRecommendation {
     pub table_name: String,
     pub column_names: Vec<String>,
@@ -10,10 +13,66 @@ pub struct PartialIndexRecommendation {
     pub estimated_storage_savings: f64,
     pub performance_impact: f64,
     pub sql_statement: String,
+    pub reason: String,
+    #[serde(default)]
+    pub reason_code: ReasonCode,
+}
+
+// Validated WHERE predicate: column <operator> literal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct FilterCondition {
+    pub column: String,
+    pub operator: String, // "=", "!=", "<", ">", "<=", ">=", "IS", "IS NOT", "LIKE"
+    pub value: FilterValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum FilterValue {
+    Text(String),
+    Number(String),
+    Boolean(bool),
+    Null,
+}
+
+impl FilterCondition {
+    /// Single WHERE predicate ("status = 'open'") parse karta hai - sirf column <op> literal shapes
+    pub fn parse(condition: &str) -> Option<FilterCondition> {
+        PartialIndexRecommender::parse_condition(&PartialIndexRecommender::tokenize(condition))
+    }
+
+    // Clean SQL for the predicate - strings re-quoted with '' escaping
+    pub fn to_sql(&self) -> String {
+        let value = match &self.value {
+            FilterValue::Text(text) => format!("'{}'", text.replace('\'', "''")),
+            FilterValue::Number(number) => number.clone(),
+            FilterValue::Boolean(flag) => flag.to_string(),
+            FilterValue::Null => "NULL".to_string(),
+        };
+        format!("{} {} {}", self.column, self.operator, value)
+    }
+}
+
+// Real column statistics, e.g. from pg_stats / information_schema
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ColumnStats {
+    pub distinct_values: u64,
+    pub total_rows: u64,
+    pub null_fraction: f64,
+    #[serde(default)]
+    pub sentinel_fraction: f64, // rows holding '' / 0 / 'N/A' placeholders instead of NULL
+    #[serde(default)]
+    pub observed_values: Vec<String>, // saari distinct values, sirf tab jab puri list pata ho (e.g. pg_stats MCVs)
 }
 
 pub struct PartialIndexRecommender {
     condition_patterns: HashMap<String, f64>,
+    column_stats: HashMap<String, ColumnStats>, // "column" or "table.column" -> stats
+}
+
+impl Default for PartialIndexRecommender {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PartialIndexRecommender {
@@ -29,9 +88,20 @@ impl PartialIndexRecommender {
         
         Self {
             condition_patterns: patterns,
+            column_stats: HashMap::new(),
         }
     }
     
+    pub fn with_column_stats(column_stats: HashMap<String, ColumnStats>) -> Self {
+        let mut recommender = Self::new();
+        recommender.column_stats = column_stats;
+        recommender
+    }
+    
+    pub fn set_column_stats(&mut self, column: &str, stats: ColumnStats) {
+        self.column_stats.insert(column.to_string(), stats);
+    }
+    
     //yaha pe badme advanced pattern matching bhi add karna ha
     pub fn analyze_query_for_partial_index(&self, 
         query: &str, 
@@ -39,66 +109,250 @@ impl PartialIndexRecommender {
         columns: &[String]) -> Option<PartialIndexRecommendation> {
         
         let conditions = self.extract_where_conditions(query);
-        let best_condition = self.find_best_partial_condition(&conditions);
+        let filters = self.find_partial_conditions(&conditions);
+        if filters.is_empty() {
+            return None;
+        }
         
-        if let Some(condition) = best_condition {
-            let selectivity = self.calculate_selectivity(&condition);
-            let storage_savings = self.estimate_storage_savings(selectivity);
-            let performance_impact = self.estimate_performance_impact(selectivity);
-            let sql = self.generate_partial_index_sql(table_name, columns, &condition);
-            
-            Some(PartialIndexRecommendation {
-                table_name: table_name.to_string(),
-                column_names: columns.to_vec(),
-                filter_condition: condition,
-                selectivity_ratio: selectivity,
-                estimated_storage_savings: storage_savings,
-                performance_impact: performance_impact,
-                sql_statement: sql,
-            })
-        } else {
-            None
+        // Predicate columns become the WHERE filter, key columns go in the index itself
+        let key_columns = self.select_key_columns(query, columns, &conditions, &filters);
+        
+        let filter_condition = filters
+            .iter()
+            .map(|f| f.to_sql())
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        let selectivity = filters
+            .iter()
+            .map(|f| self.calculate_selectivity(&f.to_sql()))
+            .product::<f64>();
+        let storage_savings = self.estimate_storage_savings(selectivity);
+        let performance_impact = self.estimate_performance_impact(selectivity);
+        let sql = self.generate_partial_index_sql(table_name, &key_columns, &filters);
+        
+        Some(PartialIndexRecommendation {
+            table_name: table_name.to_string(),
+            column_names: key_columns,
+            filter_condition: filter_condition.clone(),
+            selectivity_ratio: selectivity,
+            estimated_storage_savings: storage_savings,
+            performance_impact,
+            sql_statement: sql,
+            reason: format!(
+                "Partial index for rows matching {} (selectivity {:.2})",
+                filter_condition,
+                selectivity
+            ),
+            reason_code: ReasonCode::LowSelectivity,
+        })
+    }
+    
+    // Caller columns minus filter columns; agar kuch nahi bacha to query se derive karta hai
+    // (parameterized equality first, then ranges, then ORDER BY)
+    fn select_key_columns(&self, 
+        query: &str, 
+        columns: &[String], 
+        conditions: &[FilterCondition], 
+        filters: &[FilterCondition]) -> Vec<String> {
+        
+        // "o.status" aur "status" ek hi column hai - dono side bare naam pe compare
+        let is_filter_column = |column: &str| {
+            let column = Self::bare_column(column);
+            filters.iter().any(|f| Self::bare_column(&f.column).eq_ignore_ascii_case(&column))
+        };
+        
+        let mut key_columns: Vec<String> = columns
+            .iter()
+            .filter(|c| !is_filter_column(c))
+            .map(|c| Self::bare_column(c))
+            .collect();
+        if !key_columns.is_empty() {
+            return key_columns;
         }
+        
+        let remaining: Vec<&FilterCondition> = conditions.iter().filter(|c| !filters.contains(c)).collect();
+        let parsed = QueryParser.parse(query);
+        let derived = remaining.iter().filter(|c| c.operator == "=")
+            .chain(remaining.iter().filter(|c| c.operator != "="))
+            .map(|c| c.column.clone())
+            .chain(
+                parsed
+                    .order_by_columns
+                    .iter()
+                    .filter_map(|c| c.split_whitespace().next().map(|c| c.to_string())),
+            );
+        
+        for column in derived {
+            let column = Self::bare_column(&column);
+            if !is_filter_column(&column) && !key_columns.contains(&column) {
+                key_columns.push(column);
+            }
+        }
+        
+        if key_columns.is_empty() {
+            // Nothing else to key on - index the filter column itself
+            key_columns.push(Self::bare_column(&filters[0].column));
+        }
+        key_columns
     }
     
-    fn extract_where_conditions(&self, query: &str) -> Vec<String> {
-        let query_upper = query.to_uppercase();
-        let mut conditions = Vec::new();
+    // CREATE INDEX ek hi table pe hota hai - "o.status" / "\"status\"" -> "status"
+    fn bare_column(column: &str) -> String {
+        let column = column.rsplit('.').next().unwrap_or(column);
+        column.trim_matches(|c| c == '"' || c == '`').to_string()
+    }
+    
+    pub fn extract_where_conditions(&self, query: &str) -> Vec<FilterCondition> {
+        let tokens = Self::tokenize(query);
+        
+        let Some(where_pos) = tokens.iter().position(|t| t.eq_ignore_ascii_case("WHERE")) else {
+            return Vec::new();
+        };
         
-        if let Some(where_start) = query_upper.find("WHERE") {
-            let where_clause = &query[where_start + 5..];
-            let conditions_str = where_clause.split_whitespace().collect::<Vec<&str>>().join(" ");
+        // WHERE clause ends at the next top-level clause keyword
+        let mut conditions = Vec::new();
+        let mut current: Vec<String> = Vec::new();
+        let mut i = where_pos + 1;
+        while i < tokens.len() {
+            let upper = tokens[i].to_uppercase();
+            let next_upper = tokens.get(i + 1).map(|t| t.to_uppercase()).unwrap_or_default();
+            if ["LIMIT", "HAVING", "OFFSET", "RETURNING"].contains(&upper.as_str())
+                || ((upper == "ORDER" || upper == "GROUP") && next_upper == "BY") {
+                break;
+            }
             
-            // Split by AND/OR but keep the logic
-            let parts: Vec<&str> = conditions_str.split(" AND ").collect();
-            for part in parts {
-                let or_parts: Vec<&str> = part.split(" OR ").collect();
-                for or_part in or_parts {
-                    conditions.push(or_part.trim().to_string());
-                }
+            if upper == "AND" || upper == "OR" {
+                conditions.extend(Self::parse_condition(&current));
+                current.clear();
+            } else {
+                current.push(tokens[i].clone());
             }
+            i += 1;
         }
+        conditions.extend(Self::parse_condition(&current));
         
         conditions
     }
     
-    fn find_best_partial_condition(&self, conditions: &[String]) -> Option<String> {
-        let mut best_condition = None;
-        let mut best_score = 0.0;
+    // Quote-aware tokenizer - string literals ek token rehte hai (quotes ke saath)
+    fn tokenize(text: &str) -> Vec<String> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut tokens = Vec::new();
+        let mut i = 0;
         
-        for condition in conditions {
-            let score = self.score_condition(condition);
-            if score > best_score {
-                best_score = score;
-                best_condition = Some(condition.clone());
+        while i < chars.len() {
+            let c = chars[i];
+            if c.is_whitespace() {
+                i += 1;
+            } else if c == '\'' {
+                let mut literal = String::from("'");
+                i += 1;
+                while i < chars.len() {
+                    if chars[i] == '\'' {
+                        if chars.get(i + 1) == Some(&'\'') {
+                            literal.push_str("''");
+                            i += 2;
+                            continue;
+                        }
+                        break;
+                    }
+                    literal.push(chars[i]);
+                    i += 1;
+                }
+                literal.push('\'');
+                i += 1;
+                tokens.push(literal);
+            } else if "=<>!".contains(c) {
+                let mut op = c.to_string();
+                if chars.get(i + 1).is_some_and(|n| "=<>".contains(*n)) {
+                    op.push(chars[i + 1]);
+                    i += 1;
+                }
+                i += 1;
+                tokens.push(op);
+            } else if "(),;".contains(c) {
+                tokens.push(c.to_string());
+                i += 1;
+            } else {
+                let start = i;
+                while i < chars.len() && !chars[i].is_whitespace() && !"'=<>!(),;".contains(chars[i]) {
+                    i += 1;
+                }
+                tokens.push(chars[start..i].iter().collect());
             }
         }
         
-        if best_score > 0.3 { // Minimum threshold for partial index
-            best_condition
+        tokens
+    }
+    
+    // Only "column <op> literal" shapes are accepted - baaki (subqueries, column = column) skip
+    fn parse_condition(tokens: &[String]) -> Option<FilterCondition> {
+        let (column, rest) = tokens.split_first()?;
+        let is_identifier = column
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+            && !column.starts_with(|c: char| c.is_ascii_digit());
+        if !is_identifier {
+            return None;
+        }
+        
+        let (operator, value_tokens) = match rest {
+            [op, not, value @ ..] if op.eq_ignore_ascii_case("IS") && not.eq_ignore_ascii_case("NOT") => {
+                ("IS NOT".to_string(), value)
+            }
+            [op, value @ ..] if op.eq_ignore_ascii_case("IS") || op.eq_ignore_ascii_case("LIKE") => {
+                (op.to_uppercase(), value)
+            }
+            [op, value @ ..] if ["=", "!=", "<>", "<", ">", "<=", ">="].contains(&op.as_str()) => {
+                (op.replace("<>", "!="), value)
+            }
+            _ => return None,
+        };
+        
+        let [value] = value_tokens else {
+            return None;
+        };
+        
+        let value = if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+            FilterValue::Text(value[1..value.len() - 1].replace("''", "'"))
+        } else if value.eq_ignore_ascii_case("TRUE") || value.eq_ignore_ascii_case("FALSE") {
+            FilterValue::Boolean(value.eq_ignore_ascii_case("TRUE"))
+        } else if value.eq_ignore_ascii_case("NULL") {
+            FilterValue::Null
+        } else if value.parse::<f64>().is_ok() {
+            FilterValue::Number(value.clone())
         } else {
-            None
+            return None;
+        };
+        
+        // IS only makes sense with NULL / booleans
+        if operator.starts_with("IS") && !matches!(value, FilterValue::Null | FilterValue::Boolean(_)) {
+            return None;
         }
+        
+        Some(FilterCondition { column: column.clone(), operator, value })
+    }
+    
+    // All conditions worth baking into the partial index filter, most selective first
+    fn find_partial_conditions(&self, conditions: &[FilterCondition]) -> Vec<FilterCondition> {
+        let mut scored: Vec<(f64, &FilterCondition)> = conditions
+            .iter()
+            // Numeric equality is usually a parameter (id = 42), not a stable partial-index filter
+            .filter(|c| !(c.operator == "=" && matches!(c.value, FilterValue::Number(_))))
+            // Score is the expected fraction of rows kept - filter must exclude most rows
+            .map(|c| (self.score_condition(&c.to_sql()), c))
+            .filter(|(score, _)| *score <= 0.3)
+            .collect();
+        
+        scored.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        
+        let mut filters: Vec<FilterCondition> = Vec::new();
+        for (_, condition) in scored {
+            if !filters.contains(condition) {
+                filters.push(condition.clone());
+            }
+        }
+        filters
     }
     
     fn score_condition(&self, condition: &str) -> f64 {
@@ -123,8 +377,12 @@ impl PartialIndexRecommender {
         }
     }
     
-    fn calculate_selectivity(&self, condition: &str) -> f64 {
-        // Simple selectivity estimation
+    pub fn calculate_selectivity(&self, condition: &str) -> f64 {
+        if let Some(selectivity) = self.selectivity_from_stats(condition) {
+            return selectivity;
+        }
+        
+        // Simple selectivity estimation - no stats supplied, heuristic fallback
         if condition.contains("= true") || condition.contains("= false") {
             0.1 // Boolean conditions are usually highly selective
         } else if condition.contains(">") {
@@ -136,6 +394,37 @@ impl PartialIndexRecommender {
         }
     }
     
+    fn selectivity_from_stats(&self, condition: &str) -> Option<f64> {
+        let column = condition
+            .split(|c: char| c.is_whitespace() || "=<>!".contains(c))
+            .next()?
+            .trim();
+        let stats = self.column_stats
+            .get(column)
+            .or_else(|| column.rsplit_once('.').and_then(|(_, bare)| self.column_stats.get(bare)))?;
+        
+        let non_null = 1.0 - stats.null_fraction.clamp(0.0, 1.0);
+        let distinct = stats.distinct_values.max(1) as f64;
+        let condition_lower = condition.to_lowercase();
+        
+        let selectivity = if condition_lower.contains(" in (") {
+            // IN list - har value ek equality hai
+            let values = condition_lower.matches(',').count() + 1;
+            (values as f64 / distinct).min(1.0) * non_null
+        } else if condition_lower.contains("is null") {
+            stats.null_fraction
+        } else if condition.contains('=') && !condition.contains("!=") && !condition.contains("<>")
+            && !condition.contains(">=") && !condition.contains("<=") {
+            // Equality predicate - uniform distribution assume karta hai
+            non_null / distinct
+        } else {
+            // Ranges need a histogram - heuristic pe fallback
+            return None;
+        };
+        
+        Some(selectivity.clamp(0.0, 1.0))
+    }
+    
     fn estimate_storage_savings(&self, selectivity: f64) -> f64 {
         // Storage savings = (1 - selectivity) * estimated_index_size
         let base_index_size = 100.0; // MB
@@ -147,14 +436,21 @@ impl PartialIndexRecommender {
         (1.0 - selectivity) * 100.0
     }
     
-    fn generate_partial_index_sql(&self, table_name: &str, columns: &[String], condition: &str) -> String {
+    fn generate_partial_index_sql(&self, table_name: &str, columns: &[String], filters: &[FilterCondition]) -> String {
         let column_list = columns.join(", ");
+        let filter_column = Self::bare_column(&filters[0].column);
+        let filter_sql = filters
+            .iter()
+            .map(|f| FilterCondition { column: Self::bare_column(&f.column), ..f.clone() }.to_sql())
+            .collect::<Vec<_>>()
+            .join(" AND ");
         format!(
-            "CREATE INDEX idx_{}_partial ON {} ({}) WHERE {}",
+            "CREATE INDEX idx_{}_{}_partial ON {} ({}) WHERE {}",
             table_name,
+            filter_column,
             table_name,
             column_list,
-            condition
+            filter_sql
         )
     }
     
@@ -172,7 +468,6 @@ impl PartialIndexRecommender {
         
         // Simple optimizations
         optimized = optimized.replace(" = true", "");
-        optimized = optimized.replace(" = false", " = false");
         optimized = optimized.replace("  ", " "); // Remove double spaces
         
         optimized
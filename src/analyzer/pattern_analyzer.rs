@@ -1,73 +1,441 @@
-use super::QueryLog;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use super::{QueryLog, TemplateInterner};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct QueryPattern {
     pub query_type: String,
+    pub fingerprint: Option<String>, // None for the coarse query_type grouping
     pub avg_execution_time_ms: f64,
+    pub p50_execution_time_ms: f64,
+    pub p95_execution_time_ms: f64,
+    pub max_execution_time_ms: u64,
     pub frequency: u64,
     pub tables: Vec<String>,
     pub slowness_score: f64,
     pub total_rows_scanned: u64,
+    #[serde(default)]
+    pub avg_scan_amplification: Option<f64>, // rows_returned wale logs ka average, warna None
 }
 
+impl QueryPattern {
+    /// Sharded analyzers ke patterns combine karta hai - (query_type, fingerprint) pe group hota hai.
+    /// Averages frequency-weighted hai; p50/p95 exact merge nahi ho sakte isliye weighted
+    /// approximation hai, aur slowness_score shards ka sum hai (TimeFrequency ke liye exact)
+    pub fn merge(patterns: Vec<QueryPattern>) -> Vec<QueryPattern> {
+        let mut merged: HashMap<(String, Option<String>), QueryPattern> = HashMap::new();
+
+        for pattern in patterns {
+            let key = (pattern.query_type.clone(), pattern.fingerprint.clone());
+            let Some(existing) = merged.get_mut(&key) else {
+                merged.insert(key, pattern);
+                continue;
+            };
+
+            let total = existing.frequency + pattern.frequency;
+            let weighted = |a: f64, b: f64| if total == 0 {
+                0.0
+            } else {
+                (a * existing.frequency as f64 + b * pattern.frequency as f64) / total as f64
+            };
+
+            existing.avg_execution_time_ms = weighted(existing.avg_execution_time_ms, pattern.avg_execution_time_ms);
+            existing.p50_execution_time_ms = weighted(existing.p50_execution_time_ms, pattern.p50_execution_time_ms);
+            existing.p95_execution_time_ms = weighted(existing.p95_execution_time_ms, pattern.p95_execution_time_ms);
+            existing.max_execution_time_ms = existing.max_execution_time_ms.max(pattern.max_execution_time_ms);
+            existing.avg_scan_amplification = match (existing.avg_scan_amplification, pattern.avg_scan_amplification) {
+                (Some(a), Some(b)) => Some(weighted(a, b)),
+                (a, b) => a.or(b),
+            };
+            existing.frequency = total;
+            existing.total_rows_scanned += pattern.total_rows_scanned;
+            existing.slowness_score += pattern.slowness_score;
+
+            for table in pattern.tables {
+                if !existing.tables.contains(&table) {
+                    existing.tables.push(table);
+                }
+            }
+            existing.tables.sort();
+        }
+
+        let mut patterns: Vec<QueryPattern> = merged.into_values().collect();
+        patterns.sort_by(QueryPattern::cmp_by_slowness);
+        patterns
+    }
+
+    /// Slowest first; ties query_type, fingerprint aur tables se tootte hai taaki HashMap-derived
+    /// output har run mein same order mein aaye
+    pub fn cmp_by_slowness(a: &QueryPattern, b: &QueryPattern) -> std::cmp::Ordering {
+        b.slowness_score
+            .total_cmp(&a.slowness_score)
+            .then_with(|| a.query_type.cmp(&b.query_type))
+            .then_with(|| a.fingerprint.cmp(&b.fingerprint))
+            .then_with(|| a.tables.cmp(&b.tables))
+    }
+}
+
+/// get_performance_summary() ka default slow cutoff - apne SLA ke liye with_threshold use karo
+pub const DEFAULT_SLOW_QUERY_THRESHOLD_MS: f64 = 100.0;
+
+/// analyze_by_tag mein bina tags wale logs ki class
+pub const UNTAGGED: &str = "untagged";
+
+/// slowness_score kaise compute hota hai
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+pub enum ScoringStrategy {
+    /// avg_time * frequency
+    #[default]
+    TimeFrequency,
+    /// avg_time * (1 + log10(1 + avg rows)) * (1 + ln(frequency)) - bade scans upar aate hai,
+    /// frequency ka effect log se dampen hota hai
+    TimePerRowWeighted,
+}
+
+// 32ms tak har value ka apna bucket, uske baad har power of two 16 buckets mein - ~6% relative error
+const EXACT_LATENCY_BUCKETS: u64 = 32;
+const SUB_BUCKET_BITS: u32 = 4;
+
+/// Fixed-bucket latency histogram - O(log buckets) record/remove, at most ~1000 buckets per group
+#[derive(Debug, Clone, Default)]
+struct LatencyHistogram {
+    counts: BTreeMap<u16, u64>, // bucket index -> samples, empty buckets hata diye jate hai
+    total: u64,
+}
+
+impl LatencyHistogram {
+    fn bucket(value: u64) -> u16 {
+        if value < EXACT_LATENCY_BUCKETS {
+            return value as u16;
+        }
+        let exponent = 63 - value.leading_zeros();
+        let sub_bucket = (value >> (exponent - SUB_BUCKET_BITS)) & ((1 << SUB_BUCKET_BITS) - 1);
+        let first_exponent = EXACT_LATENCY_BUCKETS.trailing_zeros();
+        (EXACT_LATENCY_BUCKETS + (((exponent - first_exponent) as u64) << SUB_BUCKET_BITS) + sub_bucket) as u16
+    }
+
+    // (lowest, highest) value jo is bucket mein aa sakti hai
+    fn bounds(bucket: u16) -> (u64, u64) {
+        let bucket = bucket as u64;
+        if bucket < EXACT_LATENCY_BUCKETS {
+            return (bucket, bucket);
+        }
+        let offset = bucket - EXACT_LATENCY_BUCKETS;
+        let exponent = EXACT_LATENCY_BUCKETS.trailing_zeros() as u64 + (offset >> SUB_BUCKET_BITS);
+        let sub_bucket = offset & ((1 << SUB_BUCKET_BITS) - 1);
+        let width = 1u64 << (exponent - SUB_BUCKET_BITS as u64);
+        let low = ((1u64 << SUB_BUCKET_BITS) + sub_bucket) * width;
+        (low, low.saturating_add(width - 1))
+    }
+
+    fn record(&mut self, value: u64) {
+        *self.counts.entry(Self::bucket(value)).or_insert(0) += 1;
+        self.total += 1;
+    }
+
+    fn remove(&mut self, value: u64) {
+        let bucket = Self::bucket(value);
+        if let Some(count) = self.counts.get_mut(&bucket) {
+            *count -= 1;
+            self.total -= 1;
+            if *count == 0 {
+                self.counts.remove(&bucket);
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    fn upper_bound(&self) -> u64 {
+        self.counts.keys().next_back().map_or(0, |&bucket| Self::bounds(bucket).1)
+    }
+
+    // Nearest-rank percentile - bucket ka midpoint report hota hai
+    fn percentile(&self, pct: f64) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+
+        let rank = ((pct / 100.0) * self.total as f64).ceil().clamp(1.0, self.total as f64) as u64;
+        let mut seen = 0;
+        for (&bucket, &count) in &self.counts {
+            seen += count;
+            if seen >= rank {
+                let (low, high) = Self::bounds(bucket);
+                return low as f64 + (high - low) as f64 / 2.0;
+            }
+        }
+        0.0
+    }
+}
+
+// Running totals for one group - add_log pe update hota hai taaki analyze() ko logs re-scan na karne pade
+#[derive(Debug, Clone, Default)]
+struct PatternAggregate {
+    count: u64,
+    total_time_ms: u64,
+    total_rows_scanned: u64,
+    amplification_sum: f64,
+    amplification_count: u64, // sirf rows_returned wale logs
+    latencies: LatencyHistogram, // p50/p95 ke liye - group kitna bhi bada ho, size fixed
+    max_time_ms: u64,
+    tables: BTreeMap<String, u64>, // table -> kitne logs mein aaya, eviction pe decrement hota hai
+}
+
+impl PatternAggregate {
+    fn record(&mut self, log: &QueryLog) {
+        self.count += 1;
+        self.total_time_ms += log.execution_time_ms;
+        self.total_rows_scanned += log.rows_scanned;
+        if let Some(amplification) = log.scan_amplification() {
+            self.amplification_sum += amplification;
+            self.amplification_count += 1;
+        }
+        self.latencies.record(log.execution_time_ms);
+        self.max_time_ms = self.max_time_ms.max(log.execution_time_ms);
+        for table in &log.tables_accessed {
+            *self.tables.entry(table.clone()).or_insert(0) += 1;
+        }
+    }
+
+    // record() ka ulta - bounded mode mein evicted log ko totals se hatata hai
+    fn forget(&mut self, log: &QueryLog) {
+        self.count = self.count.saturating_sub(1);
+        self.total_time_ms = self.total_time_ms.saturating_sub(log.execution_time_ms);
+        self.total_rows_scanned = self.total_rows_scanned.saturating_sub(log.rows_scanned);
+        if let Some(amplification) = log.scan_amplification() {
+            self.amplification_sum = (self.amplification_sum - amplification).max(0.0);
+            self.amplification_count = self.amplification_count.saturating_sub(1);
+        }
+        self.latencies.remove(log.execution_time_ms);
+        // Exact max evict ho gaya to bache hue samples ka highest bucket hi best estimate hai
+        if log.execution_time_ms >= self.max_time_ms {
+            self.max_time_ms = self.latencies.upper_bound().min(self.max_time_ms);
+        }
+        for table in &log.tables_accessed {
+            if let Some(count) = self.tables.get_mut(table) {
+                *count -= 1;
+                if *count == 0 {
+                    self.tables.remove(table);
+                }
+            }
+        }
+    }
+
+    fn to_pattern(&self, query_type: &str, fingerprint: Option<String>, strategy: ScoringStrategy) -> QueryPattern {
+        let avg_time = self.total_time_ms as f64 / self.count as f64;
+        let slowness_score = match strategy {
+            ScoringStrategy::TimeFrequency => avg_time * self.count as f64,
+            ScoringStrategy::TimePerRowWeighted => {
+                let avg_rows = self.total_rows_scanned as f64 / self.count as f64;
+                avg_time * (1.0 + (1.0 + avg_rows).log10()) * (1.0 + (self.count as f64).ln())
+            }
+        };
+
+        QueryPattern {
+            query_type: query_type.to_string(),
+            fingerprint,
+            avg_execution_time_ms: avg_time,
+            p50_execution_time_ms: self.latencies.percentile(50.0).min(self.max_time_ms as f64),
+            p95_execution_time_ms: self.latencies.percentile(95.0).min(self.max_time_ms as f64),
+            max_execution_time_ms: self.max_time_ms,
+            frequency: self.count,
+            tables: self.tables.keys().cloned().collect(),
+            slowness_score,
+            total_rows_scanned: self.total_rows_scanned,
+            avg_scan_amplification: (self.amplification_count > 0)
+                .then(|| self.amplification_sum / self.amplification_count as f64),
+        }
+    }
+}
+
+// (query type, template, sorted tables)
+type GroupKey = (String, Arc<str>, Vec<String>);
+
+#[derive(Clone)]
 pub struct PatternAnalyzer {
-    logs: Vec<QueryLog>,
+    templates: TemplateInterner, // har distinct template ek hi baar store hota hai
+    by_type: HashMap<String, PatternAggregate>,
+    by_fingerprint: HashMap<GroupKey, PatternAggregate>,
+    by_tag: HashMap<String, HashMap<GroupKey, PatternAggregate>>, // tag -> fingerprint aggregates
+    total_queries: usize,
+    scoring_strategy: ScoringStrategy,
+    capacity: Option<usize>, // Some(n) = sirf latest n logs ke aggregates
+    recent_logs: VecDeque<QueryLog>, // bounded mode mein hi fill hota hai
+}
+
+impl Default for PatternAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PatternAnalyzer {
     pub fn new() -> Self {
-        Self { logs: Vec::new() }
+        Self {
+            templates: TemplateInterner::new(),
+            by_type: HashMap::new(),
+            by_fingerprint: HashMap::new(),
+            by_tag: HashMap::new(),
+            total_queries: 0,
+            scoring_strategy: ScoringStrategy::default(),
+            capacity: None,
+            recent_logs: VecDeque::new(),
+        }
+    }
+
+    /// Bounded mode - sirf latest `capacity` logs rakhta hai; purana log evict hone pe uske
+    /// aggregates bhi decrement hote hai, taaki long-running service mein memory fixed rahe
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: Some(capacity.max(1)),
+            recent_logs: VecDeque::with_capacity(capacity.max(1)),
+            ..Self::new()
+        }
+    }
+
+    pub fn set_scoring_strategy(&mut self, strategy: ScoringStrategy) {
+        self.scoring_strategy = strategy;
     }
 
     pub fn add_log(&mut self, log: QueryLog) {
-        self.logs.push(log);
+        let (query_type, fingerprint, tables) = self.group_key(&log);
+
+        self.by_type.entry(query_type.clone()).or_default().record(&log);
+        let key = (query_type, fingerprint, tables);
+        for tag in Self::tags_of(&log) {
+            self.by_tag.entry(tag.to_string()).or_default().entry(key.clone()).or_default().record(&log);
+        }
+        self.by_fingerprint.entry(key).or_default().record(&log);
+        self.total_queries += 1;
+
+        if let Some(capacity) = self.capacity {
+            self.recent_logs.push_back(log);
+            while self.recent_logs.len() > capacity {
+                if let Some(evicted) = self.recent_logs.pop_front() {
+                    self.evict(&evicted);
+                }
+            }
+        }
     }
 
-    pub fn add_logs(&mut self, logs: Vec<QueryLog>) {
-        self.logs.extend(logs);
+    fn group_key(&mut self, log: &QueryLog) -> GroupKey {
+        let fingerprint = self.templates.intern(&log.template());
+        let mut tables = log.tables_accessed.clone();
+        tables.sort();
+        tables.dedup();
+        (log.query_type(), fingerprint, tables)
     }
 
-    pub fn analyze(&self) -> Vec<QueryPattern> {
-        let mut patterns: HashMap<String, Vec<&QueryLog>> = HashMap::new();
+    // Untagged logs UNTAGGED class mein jate hai, taaki analyze_by_tag mein koi log chhoote nahi
+    fn tags_of(log: &QueryLog) -> Vec<&str> {
+        if log.tags.is_empty() {
+            vec![UNTAGGED]
+        } else {
+            log.tags.iter().map(|tag| tag.as_str()).collect()
+        }
+    }
+
+    fn evict(&mut self, log: &QueryLog) {
+        let key = self.group_key(log);
 
-        for log in &self.logs {
-            let query_type = log.query_type();
-            patterns.entry(query_type).or_insert_with(Vec::new).push(log);
+        if let Some(aggregate) = self.by_type.get_mut(&key.0) {
+            aggregate.forget(log);
+            if aggregate.count == 0 {
+                self.by_type.remove(&key.0);
+            }
+        }
+        for tag in Self::tags_of(log) {
+            if let Some(aggregates) = self.by_tag.get_mut(tag) {
+                if let Some(aggregate) = aggregates.get_mut(&key) {
+                    aggregate.forget(log);
+                    if aggregate.count == 0 {
+                        aggregates.remove(&key);
+                    }
+                }
+                if aggregates.is_empty() {
+                    self.by_tag.remove(tag);
+                }
+            }
+        }
+        if let Some(aggregate) = self.by_fingerprint.get_mut(&key) {
+            aggregate.forget(log);
+            if aggregate.count == 0 {
+                self.by_fingerprint.remove(&key);
+                // Template ka koi aur aggregate nahi bacha (by_tag keys by_fingerprint ka subset hai) to interner se bhi hatao
+                if !self.by_fingerprint.keys().any(|other| Arc::ptr_eq(&other.1, &key.1)) {
+                    self.templates.remove(&key.1);
+                }
+            }
         }
+        self.total_queries = self.total_queries.saturating_sub(1);
+    }
+
+    pub fn add_logs(&mut self, logs: Vec<QueryLog>) {
+        self.ingest_stream(logs);
+    }
+
+    /// Lazy ingestion - logs ek ek karke aggregates mein fold hote hai, poora Vec banana nahi padta.
+    /// Streaming log parser ke saath bade log files bhi bounded memory mein analyze ho jate hai
+    pub fn ingest_stream(&mut self, logs: impl IntoIterator<Item = QueryLog>) {
+        for log in logs {
+            self.add_log(log);
+        }
+    }
 
+    pub fn analyze(&self) -> Vec<QueryPattern> {
+        let mut patterns: Vec<QueryPattern> = self.by_type
+            .iter()
+            .map(|(query_type, aggregate)| aggregate.to_pattern(query_type, None, self.scoring_strategy))
+            .collect();
+        patterns.sort_by(QueryPattern::cmp_by_slowness);
         patterns
-            .into_iter()
-            .map(|(query_type, logs)| {
-                let total_time: u64 = logs.iter().map(|l| l.execution_time_ms).sum();
-                let total_rows: u64 = logs.iter().map(|l| l.rows_scanned).sum();
-                let avg_time = total_time as f64 / logs.len() as f64;
-                
-                let slowness_score = avg_time * logs.len() as f64;
-
-                let mut tables: Vec<String> = logs
-                    .iter()
-                    .flat_map(|l| l.tables_accessed.clone())
-                    .collect();
-                tables.sort();
-                tables.dedup();
-
-                QueryPattern {
-                    query_type,
-                    avg_execution_time_ms: avg_time,
-                    frequency: logs.len() as u64,
-                    tables,
-                    slowness_score,
-                    total_rows_scanned: total_rows,
-                }
+    }
+
+    // Same stats as analyze() but grouped per query shape, so different SELECTs stay separate
+    pub fn analyze_by_fingerprint(&self) -> Vec<QueryPattern> {
+        let mut patterns: Vec<QueryPattern> = self.by_fingerprint
+            .iter()
+            .map(|((query_type, fingerprint, _), aggregate)| {
+                aggregate.to_pattern(query_type, Some(fingerprint.to_string()), self.scoring_strategy)
             })
+            .collect();
+        patterns.sort_by(QueryPattern::cmp_by_slowness);
+        patterns
+    }
+
+    /// Per query-class fingerprint patterns - "api" aur "cron" ke patterns alag, taaki dono ki index
+    /// strategy alag ban sake. Multiple tags wala log har tag mein count hota hai
+    pub fn analyze_by_tag(&self) -> HashMap<String, Vec<QueryPattern>> {
+        self.by_tag
+            .keys()
+            .map(|tag| (tag.clone(), self.analyze_tag(tag)))
             .collect()
     }
 
+    /// Ek class ke patterns, slowest first - tag na mila to empty
+    pub fn analyze_tag(&self, tag: &str) -> Vec<QueryPattern> {
+        let Some(aggregates) = self.by_tag.get(tag) else {
+            return Vec::new();
+        };
+        let mut patterns: Vec<QueryPattern> = aggregates
+            .iter()
+            .map(|((query_type, fingerprint, _), aggregate)| {
+                aggregate.to_pattern(query_type, Some(fingerprint.to_string()), self.scoring_strategy)
+            })
+            .collect();
+        patterns.sort_by(QueryPattern::cmp_by_slowness);
+        patterns
+    }
+
     pub fn get_slow_patterns(&self, n: usize) -> Vec<QueryPattern> {
-        let mut patterns = self.analyze();
-        patterns.sort_by(|a, b| b.slowness_score.partial_cmp(&a.slowness_score).unwrap());
-        patterns.into_iter().take(n).collect()
+        let mut patterns = self.analyze(); // already slowest first
+        patterns.truncate(n);
+        patterns
     }
 
     pub fn get_frequent_patterns(&self, min_frequency: u64) -> Vec<QueryPattern> {
@@ -77,16 +445,41 @@ impl PatternAnalyzer {
             .collect()
     }
 
+    /// Distinct normalized templates seen so far
+    pub fn distinct_templates(&self) -> usize {
+        self.templates.len()
+    }
+
+    /// Sabse bade group ke latency histogram mein kitne buckets hai - stream kitni bhi lambi ho, capped rehta hai
+    pub fn max_latency_buckets(&self) -> usize {
+        self.by_type
+            .values()
+            .chain(self.by_fingerprint.values())
+            .map(|aggregate| aggregate.latencies.len())
+            .max()
+            .unwrap_or(0)
+    }
+
     pub fn total_queries(&self) -> usize {
-        self.logs.len()
+        self.total_queries
     }
 
     pub fn clear(&mut self) {
-        self.logs.clear();
+        self.by_type.clear();
+        self.by_fingerprint.clear();
+        self.by_tag.clear();
+        self.templates.clear();
+        self.recent_logs.clear();
+        self.total_queries = 0;
     }
 
     //yaha pe badme machine learning model bhi add karna ha
     pub fn get_performance_summary(&self) -> (f64, f64, usize) {
+        self.get_performance_summary_with_threshold(DEFAULT_SLOW_QUERY_THRESHOLD_MS)
+    }
+
+    /// (avg time, pattern count, slow query count) - avg time threshold_ms se upar ho to slow
+    pub fn get_performance_summary_with_threshold(&self, threshold_ms: f64) -> (f64, f64, usize) {
         let patterns = self.analyze();
         let total_queries = self.total_queries();
         
@@ -99,10 +492,11 @@ impl PatternAnalyzer {
             .sum::<f64>() / total_queries as f64;
 
         let slow_queries = patterns.iter()
-            .filter(|p| p.avg_execution_time_ms > 100.0)
+            .filter(|p| p.avg_execution_time_ms > threshold_ms)
             .map(|p| p.frequency)
             .sum::<u64>() as usize;
 
         (avg_time, patterns.len() as f64, slow_queries)
     }
 }
+
@@ -1,8 +1,10 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::mpsc::Sender;
+use super::{Clock, SystemClock};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PerformanceMetric {
     pub timestamp: u64,
     pub metric_name: String,
@@ -11,7 +13,7 @@ pub struct PerformanceMetric {
     pub severity: String, // "normal", "warning", "critical"
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PerformanceAlert {
     pub alert_id: String,
     pub metric_name: String,
@@ -22,14 +24,95 @@ pub struct PerformanceAlert {
     pub timestamp: u64,
 }
 
+// Downsampled history - raw window se bahar gaye points isme fold hote hai
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MetricBucket {
+    pub bucket_start: u64,
+    pub min: f64,
+    pub max: f64,
+    pub avg: f64,
+    pub count: u64,
+}
+
+const MAX_BUCKETS_PER_METRIC: usize = 40_320; // 4 weeks of 1-minute buckets
+
+// record_histogram ke default upper bounds (ms) - configure_histogram se per metric badal sakte hai
+const DEFAULT_HISTOGRAM_BOUNDS: [f64; 12] = [1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0];
+
+// Lifetime distribution - raw window ki tarah evict nahi hota, Prometheus histogram jaisa
+#[derive(Debug, Clone)]
+struct MetricHistogram {
+    bounds: Vec<f64>, // sorted upper bounds, +Inf implicit
+    counts: Vec<u64>, // per bucket (non-cumulative), last = +Inf bucket
+    sum: f64,
+    count: u64,
+}
+
+impl MetricHistogram {
+    fn new(bounds: &[f64]) -> Self {
+        let mut bounds: Vec<f64> = bounds.iter().copied().filter(|b| b.is_finite()).collect();
+        bounds.sort_by(|a, b| a.total_cmp(b));
+        bounds.dedup();
+        Self {
+            counts: vec![0; bounds.len() + 1],
+            bounds,
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        let index = self.bounds.iter().position(|bound| value <= *bound).unwrap_or(self.bounds.len());
+        self.counts[index] += 1;
+        self.sum += value;
+        self.count += 1;
+    }
+
+    // (upper bound, cumulative count) - Prometheus `le` semantics, last bound f64::INFINITY
+    fn cumulative(&self) -> Vec<(f64, u64)> {
+        let mut running = 0;
+        self.bounds
+            .iter()
+            .copied()
+            .chain(std::iter::once(f64::INFINITY))
+            .zip(&self.counts)
+            .map(|(bound, count)| {
+                running += count;
+                (bound, running)
+            })
+            .collect()
+    }
+}
+
+pub type AlertCallback = Box<dyn Fn(&PerformanceAlert) + Send + Sync>;
+
 pub struct PerformanceMonitor {
     metrics_history: HashMap<String, Vec<PerformanceMetric>>,
     alert_thresholds: HashMap<String, f64>,
+    dynamic_thresholds: HashMap<String, f64>, // metric_name -> k_sigma
     max_history_size: usize,
+    bucket_seconds: Option<u64>, // None = old points are dropped
+    bucketed_history: HashMap<String, Vec<MetricBucket>>,
+    active_alerts: HashMap<String, PerformanceAlert>, // metric_name -> ongoing alert
+    alert_callbacks: Vec<AlertCallback>,
+    alert_senders: Vec<Sender<PerformanceAlert>>,
+    histograms: HashMap<String, MetricHistogram>,
+    clock: Box<dyn Clock>,
+}
+
+impl Default for PerformanceMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PerformanceMonitor {
     pub fn new() -> Self {
+        Self::with_clock(Box::new(SystemClock))
+    }
+    
+    /// Custom time source - tests ManualClock se deterministic timestamps pa sakte hai
+    pub fn with_clock(clock: Box<dyn Clock>) -> Self {
         let mut thresholds = HashMap::new();
         thresholds.insert("query_time".to_string(), 1000.0); // 1 second
         thresholds.insert("cpu_usage".to_string(), 80.0); // 80%
@@ -39,54 +122,316 @@ impl PerformanceMonitor {
         Self {
             metrics_history: HashMap::new(),
             alert_thresholds: thresholds,
+            dynamic_thresholds: HashMap::new(),
             max_history_size: 1000,
+            bucket_seconds: None,
+            bucketed_history: HashMap::new(),
+            active_alerts: HashMap::new(),
+            alert_callbacks: Vec::new(),
+            alert_senders: Vec::new(),
+            histograms: HashMap::new(),
+            clock,
         }
     }
     
+    /// Push model - naya breach ya severity change hone pe callback call hota hai, har sample pe nahi
+    pub fn on_alert(&mut self, callback: AlertCallback) {
+        self.alert_callbacks.push(callback);
+    }
+    
+    /// Channel sink - on_alert jaisa hi, receiver drop ho jaye to sender hata diya jata hai
+    pub fn add_alert_sender(&mut self, sender: Sender<PerformanceAlert>) {
+        self.alert_senders.push(sender);
+    }
+    
+    fn notify_alert(&mut self, alert: &PerformanceAlert) {
+        for callback in &self.alert_callbacks {
+            callback(alert);
+        }
+        self.alert_senders.retain(|sender| sender.send(alert.clone()).is_ok());
+    }
+    
     //yaha pe badme real-time streaming bhi add karna ha
     pub fn record_metric(&mut self, metric: PerformanceMetric) {
         let metric_name = metric.metric_name.clone();
-        let entry = self.metrics_history.entry(metric_name).or_insert_with(Vec::new);
+        let entry = self.metrics_history.entry(metric_name.clone()).or_default();
         
         entry.push(metric);
         
         // Keep only recent history
         if entry.len() > self.max_history_size {
-            entry.remove(0);
+            let evicted = entry.remove(0);
+            if let Some(bucket_seconds) = self.bucket_seconds {
+                self.fold_into_bucket(&evicted, bucket_seconds);
+            }
         }
+        
+        self.refresh_alert(&metric_name);
     }
     
-    pub fn check_alerts(&self) -> Vec<PerformanceAlert> {
-        let mut alerts = Vec::new();
-        let current_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        
-        for (metric_name, metrics) in &self.metrics_history {
-            if let Some(threshold) = self.alert_thresholds.get(metric_name) {
-                if let Some(latest_metric) = metrics.last() {
-                    if latest_metric.value > *threshold {
-                        alerts.push(PerformanceAlert {
-                            alert_id: format!("{}_{}", metric_name, current_time),
-                            metric_name: metric_name.clone(),
-                            current_value: latest_metric.value,
-                            threshold_value: *threshold,
-                            severity: self.determine_severity(latest_metric.value, *threshold),
-                            message: format!(
-                                "{} exceeded threshold: {} > {}",
-                                metric_name, latest_metric.value, threshold
-                            ),
-                            timestamp: current_time,
-                        });
-                    }
+    /// Clock ke current time pe metric record karta hai - severity threshold se derive hoti hai
+    pub fn record_value(&mut self, metric_name: &str, value: f64, unit: &str) {
+        let severity = match self.effective_threshold(metric_name) {
+            Some(threshold) => self.determine_severity(value, threshold),
+            None => "normal".to_string(),
+        };
+        
+        self.record_metric(PerformanceMetric {
+            timestamp: self.clock.now_secs(),
+            metric_name: metric_name.to_string(),
+            value,
+            unit: unit.to_string(),
+            severity,
+        });
+    }
+    
+    // raw_window latest points raw rehte hai, purane bucket_seconds ke buckets mein min/max/avg ban jate hai
+    pub fn configure_retention(&mut self, raw_window: usize, bucket_seconds: u64) {
+        self.max_history_size = raw_window.max(1);
+        self.bucket_seconds = Some(bucket_seconds.max(1));
+        
+        let bucket_seconds = bucket_seconds.max(1);
+        let mut evicted = Vec::new();
+        for metrics in self.metrics_history.values_mut() {
+            if metrics.len() > self.max_history_size {
+                let overflow = metrics.len() - self.max_history_size;
+                evicted.extend(metrics.drain(..overflow));
+            }
+        }
+        for metric in &evicted {
+            self.fold_into_bucket(metric, bucket_seconds);
+        }
+    }
+    
+    fn fold_into_bucket(&mut self, metric: &PerformanceMetric, bucket_seconds: u64) {
+        let bucket_start = metric.timestamp - metric.timestamp % bucket_seconds;
+        let buckets = self.bucketed_history.entry(metric.metric_name.clone()).or_default();
+        
+        match buckets.last_mut() {
+            Some(bucket) if bucket.bucket_start == bucket_start => {
+                bucket.min = bucket.min.min(metric.value);
+                bucket.max = bucket.max.max(metric.value);
+                bucket.avg = (bucket.avg * bucket.count as f64 + metric.value) / (bucket.count + 1) as f64;
+                bucket.count += 1;
+            }
+            _ => buckets.push(MetricBucket {
+                bucket_start,
+                min: metric.value,
+                max: metric.value,
+                avg: metric.value,
+                count: 1,
+            }),
+        }
+        
+        if buckets.len() > MAX_BUCKETS_PER_METRIC {
+            buckets.remove(0);
+        }
+    }
+    
+    /// Metric ke histogram bucket upper bounds set karta hai - pehle ke counts reset ho jate hai
+    pub fn configure_histogram(&mut self, metric_name: &str, bounds: &[f64]) {
+        self.histograms.insert(metric_name.to_string(), MetricHistogram::new(bounds));
+    }
+    
+    /// Value ko metric ke histogram mein count karta hai (configure na kiya ho to default ms bounds).
+    /// Raw history aur alerts pe asar nahi - unke liye record_value
+    pub fn record_histogram(&mut self, metric_name: &str, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        self.histograms
+            .entry(metric_name.to_string())
+            .or_insert_with(|| MetricHistogram::new(&DEFAULT_HISTOGRAM_BOUNDS))
+            .observe(value);
+    }
+    
+    /// (bucket upper bound, cumulative count) - Prometheus jaisa, last entry f64::INFINITY = total count
+    pub fn get_histogram(&self, metric_name: &str) -> Vec<(f64, u64)> {
+        self.histograms.get(metric_name).map(|h| h.cumulative()).unwrap_or_default()
+    }
+    
+    /// Bucket ke andar linear interpolation se q-quantile (0.0-1.0), histogram_quantile() jaisa.
+    /// +Inf bucket mein pade to highest finite bound; koi data na ho to None
+    pub fn quantile(&self, metric_name: &str, q: f64) -> Option<f64> {
+        let histogram = self.histograms.get(metric_name).filter(|h| h.count > 0)?;
+        let rank = q.clamp(0.0, 1.0) * histogram.count as f64;
+        
+        let mut lower_bound = 0.0;
+        let mut below = 0;
+        for (bound, cumulative) in histogram.cumulative() {
+            if cumulative as f64 >= rank && cumulative > below {
+                if bound.is_infinite() {
+                    return Some(histogram.bounds.last().copied().unwrap_or(lower_bound));
                 }
+                let in_bucket = (cumulative - below) as f64;
+                return Some(lower_bound + (bound - lower_bound) * (rank - below as f64) / in_bucket);
             }
+            lower_bound = bound;
+            below = cumulative;
+        }
+        histogram.bounds.last().copied()
+    }
+    
+    pub fn get_bucketed_history(&self, metric_name: &str) -> &[MetricBucket] {
+        self.bucketed_history.get(metric_name).map(|b| b.as_slice()).unwrap_or(&[])
+    }
+    
+    // Oldest first: bucket averages followed by raw values
+    fn combined_series(&self, metric_name: &str) -> Vec<f64> {
+        let buckets = self.get_bucketed_history(metric_name).iter().map(|b| b.avg);
+        let raw = self.metrics_history.get(metric_name).into_iter().flatten().map(|m| m.value);
+        buckets.chain(raw).collect()
+    }
+    
+    // Sirf isi metric ka alert recompute hota hai - baaki cached alerts waise hi rehte hai
+    fn refresh_alert(&mut self, metric_name: &str) {
+        let latest_metric = self.metrics_history.get(metric_name).and_then(|metrics| metrics.last());
+        let threshold = self.effective_threshold(metric_name);
+        
+        let (Some(latest_metric), Some(threshold)) = (latest_metric, threshold) else {
+            return;
+        };
+        
+        if latest_metric.value <= threshold {
+            self.active_alerts.remove(metric_name);
+            return;
         }
         
+        let severity = self.determine_severity(latest_metric.value, threshold);
+        let message = format!(
+            "{} exceeded threshold: {} > {}",
+            metric_name, latest_metric.value, threshold
+        );
+        
+        // Ongoing breach keeps its alert_id and start timestamp, only the values are updated.
+        // Notification sirf transition pe - naya breach ya severity badli
+        let transitioned = match self.active_alerts.get_mut(metric_name) {
+            Some(alert) => {
+                let severity_changed = alert.severity != severity;
+                alert.current_value = latest_metric.value;
+                alert.threshold_value = threshold;
+                alert.severity = severity;
+                alert.message = message;
+                severity_changed.then(|| alert.clone())
+            }
+            None => {
+                let alert = PerformanceAlert {
+                    alert_id: format!("{}_{}", metric_name, latest_metric.timestamp),
+                    metric_name: metric_name.to_string(),
+                    current_value: latest_metric.value,
+                    threshold_value: threshold,
+                    severity,
+                    message,
+                    timestamp: latest_metric.timestamp,
+                };
+                self.active_alerts.insert(metric_name.to_string(), alert.clone());
+                Some(alert)
+            }
+        };
+        
+        if let Some(alert) = transitioned {
+            self.notify_alert(&alert);
+        }
+    }
+    
+    // Threshold = rolling mean + k * stddev of the metric's own history
+    pub fn set_dynamic_threshold(&mut self, metric_name: &str, k_sigma: f64) {
+        self.dynamic_thresholds.insert(metric_name.to_string(), k_sigma);
+        self.refresh_alert(metric_name);
+    }
+    
+    // Dynamic band jab configured ho aur kaafi history ho, warna static threshold
+    fn effective_threshold(&self, metric_name: &str) -> Option<f64> {
+        let static_threshold = self.alert_thresholds.get(metric_name).copied();
+        let Some(k_sigma) = self.dynamic_thresholds.get(metric_name) else {
+            return static_threshold;
+        };
+        
+        // Baseline excludes the latest point so a spike can't raise its own threshold
+        let metrics = self.metrics_history.get(metric_name).map(|m| m.as_slice()).unwrap_or(&[]);
+        let baseline = &metrics[..metrics.len().saturating_sub(1)];
+        if baseline.len() < 10 {
+            return static_threshold;
+        }
+        
+        let mean = baseline.iter().map(|m| m.value).sum::<f64>() / baseline.len() as f64;
+        let variance = baseline.iter()
+            .map(|m| (m.value - mean).powi(2))
+            .sum::<f64>() / baseline.len() as f64;
+        
+        Some(mean + k_sigma * variance.sqrt())
+    }
+    
+    pub fn check_alerts(&self) -> Vec<PerformanceAlert> {
+        let mut alerts: Vec<PerformanceAlert> = self.active_alerts.values().cloned().collect();
+        alerts.sort_by(|a, b| a.metric_name.cmp(&b.metric_name));
         alerts
     }
     
+    /// Prometheus text exposition format - har metric ki latest value gauge ke roop mein,
+    /// severity label ke saath, record_histogram wale metrics _histogram_bucket/_sum/_count ke
+    /// saath, plus llm_layer_active_alerts gauge
+    pub fn export_prometheus(&self) -> String {
+        let mut metric_names: Vec<&String> = self.metrics_history.keys().collect();
+        metric_names.sort();
+        
+        let mut output = String::new();
+        for metric_name in metric_names {
+            let Some(latest) = self.metrics_history.get(metric_name).and_then(|metrics| metrics.last()) else {
+                continue;
+            };
+            
+            let name = format!("llm_layer_{}", Self::prometheus_name(metric_name));
+            output.push_str(&format!("# HELP {} Latest recorded value of {} ({})\n", name, metric_name, latest.unit));
+            output.push_str(&format!("# TYPE {} gauge\n", name));
+            output.push_str(&format!(
+                "{}{{severity=\"{}\",unit=\"{}\"}} {}\n",
+                name,
+                Self::escape_label(&latest.severity),
+                Self::escape_label(&latest.unit),
+                latest.value
+            ));
+        }
+        
+        let mut histogram_names: Vec<&String> = self.histograms.keys().collect();
+        histogram_names.sort();
+        for metric_name in histogram_names {
+            let histogram = &self.histograms[metric_name];
+            // Gauge ke saath naam clash na ho isliye _histogram suffix
+            let name = format!("llm_layer_{}_histogram", Self::prometheus_name(metric_name));
+            output.push_str(&format!("# HELP {} Distribution of {}\n", name, metric_name));
+            output.push_str(&format!("# TYPE {} histogram\n", name));
+            for (bound, cumulative) in histogram.cumulative() {
+                let le = if bound.is_infinite() { "+Inf".to_string() } else { bound.to_string() };
+                output.push_str(&format!("{}_bucket{{le=\"{}\"}} {}\n", name, le, cumulative));
+            }
+            output.push_str(&format!("{}_sum {}\n", name, histogram.sum));
+            output.push_str(&format!("{}_count {}\n", name, histogram.count));
+        }
+        
+        output.push_str("# HELP llm_layer_active_alerts Number of metrics currently breaching their threshold\n");
+        output.push_str("# TYPE llm_layer_active_alerts gauge\n");
+        output.push_str(&format!("llm_layer_active_alerts {}\n", self.check_alerts().len()));
+        output
+    }
+    
+    // Prometheus names sirf [a-zA-Z0-9_:] allow karte hai aur digit se start nahi ho sakte
+    fn prometheus_name(metric_name: &str) -> String {
+        let name: String = metric_name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == ':' { c } else { '_' })
+            .collect();
+        if name.starts_with(|c: char| c.is_ascii_digit()) {
+            format!("_{}", name)
+        } else {
+            name
+        }
+    }
+    
+    fn escape_label(value: &str) -> String {
+        value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+    }
+    
     fn determine_severity(&self, current_value: f64, threshold: f64) -> String {
         let ratio = current_value / threshold;
         if ratio > 2.0 {
@@ -101,9 +446,10 @@ impl PerformanceMonitor {
     pub fn get_performance_trends(&self) -> HashMap<String, String> {
         let mut trends = HashMap::new();
         
-        for (metric_name, metrics) in &self.metrics_history {
-            if metrics.len() >= 10 {
-                let trend = self.calculate_trend(metrics);
+        for metric_name in self.metrics_history.keys() {
+            let values = self.combined_series(metric_name);
+            if values.len() >= 10 {
+                let trend = self.calculate_trend(&values);
                 trends.insert(metric_name.clone(), trend);
             }
         }
@@ -111,13 +457,13 @@ impl PerformanceMonitor {
         trends
     }
     
-    fn calculate_trend(&self, metrics: &[PerformanceMetric]) -> String {
-        if metrics.len() < 10 {
+    fn calculate_trend(&self, values: &[f64]) -> String {
+        if values.len() < 10 {
             return "insufficient_data".to_string();
         }
         
-        let recent_avg: f64 = metrics.iter().rev().take(5).map(|m| m.value).sum::<f64>() / 5.0;
-        let older_avg: f64 = metrics.iter().rev().skip(5).take(5).map(|m| m.value).sum::<f64>() / 5.0;
+        let recent_avg: f64 = values.iter().rev().take(5).sum::<f64>() / 5.0;
+        let older_avg: f64 = values.iter().rev().skip(5).take(5).sum::<f64>() / 5.0;
         
         let change_percent = ((recent_avg - older_avg) / older_avg) * 100.0;
         
@@ -132,19 +478,19 @@ impl PerformanceMonitor {
     
     pub fn get_performance_summary(&self) -> (f64, usize, usize) {
         let total_metrics: usize = self.metrics_history.values().map(|v| v.len()).sum();
-        let active_alerts = self.check_alerts().len();
-        let critical_alerts = self.check_alerts().iter()
+        let active_alerts = self.active_alerts.len();
+        let critical_alerts = self.active_alerts.values()
             .filter(|alert| alert.severity == "critical")
             .count();
             
         let health_score = if total_metrics > 0 {
-            let avg_health = self.metrics_history.values()
+            
+            self.metrics_history.values()
                 .map(|metrics| {
                     let normal_count = metrics.iter().filter(|m| m.severity == "normal").count();
                     (normal_count as f64 / metrics.len() as f64) * 100.0
                 })
-                .sum::<f64>() / self.metrics_history.len() as f64;
-            avg_health
+                .sum::<f64>() / self.metrics_history.len() as f64
         } else {
             0.0
         };
@@ -154,16 +500,13 @@ impl PerformanceMonitor {
     
     //yaha pe badme predictive analysis bhi add karna ha
     pub fn predict_performance(&self, metric_name: &str, hours_ahead: u64) -> Option<f64> {
-        if let Some(metrics) = self.metrics_history.get(metric_name) {
-            if metrics.len() >= 20 {
-                let recent_values: Vec<f64> = metrics.iter().rev().take(20).map(|m| m.value).collect();
-                let trend = self.calculate_linear_trend(&recent_values);
-                let current_value = recent_values[0];
-                let predicted_value = current_value + (trend * hours_ahead as f64);
-                Some(predicted_value)
-            } else {
-                None
-            }
+        let values = self.combined_series(metric_name);
+        if values.len() >= 20 {
+            let recent_values: Vec<f64> = values.iter().rev().take(20).copied().collect();
+            let trend = self.calculate_linear_trend(&recent_values);
+            let current_value = recent_values[0];
+            let predicted_value = current_value + (trend * hours_ahead as f64);
+            Some(predicted_value)
         } else {
             None
         }
@@ -180,7 +523,7 @@ impl PerformanceMonitor {
         let sum_xy: f64 = values.iter().enumerate().map(|(i, v)| i as f64 * v).sum();
         let sum_x2: f64 = (0..values.len()).map(|i| (i as f64).powi(2)).sum();
         
-        let slope = (n * sum_xy - sum_x * sum_y) / (n * sum_x2 - sum_x * sum_x);
-        slope
+        
+        (n * sum_xy - sum_x * sum_y) / (n * sum_x2 - sum_x * sum_x)
     }
 }
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::fmt;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PriorityScore {
     pub index_name: String,
     pub total_score: f64,
@@ -11,6 +13,26 @@ pub struct PriorityScore {
     pub priority_level: String,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum WeightError {
+    Negative { weight: String, value: f64 },
+    AllZero,
+}
+
+impl fmt::Display for WeightError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WeightError::Negative { weight, value } => write!(f, "{} weight must not be negative, got {}", weight, value),
+            WeightError::AllZero => write!(f, "at least one weight must be positive"),
+        }
+    }
+}
+
+impl std::error::Error for WeightError {}
+
+const TRAINING_EPOCHS: usize = 500;
+const LEARNING_RATE: f64 = 0.5;
+
 pub struct PriorityScoringAlgorithm {
     frequency_weight: f64,
     performance_weight: f64,
@@ -18,6 +40,12 @@ pub struct PriorityScoringAlgorithm {
     complexity_weight: f64,
 }
 
+impl Default for PriorityScoringAlgorithm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PriorityScoringAlgorithm {
     pub fn new() -> Self {
         Self {
@@ -28,6 +56,107 @@ impl PriorityScoringAlgorithm {
         }
     }
     
+    /// Custom weights - sum 1.0 pe normalize hota hai taaki total_score [0, 1] mein rahe
+    pub fn set_weights(&mut self,
+        frequency: f64,
+        performance: f64,
+        cost: f64,
+        complexity: f64) -> Result<(), WeightError> {
+        
+        for (weight, value) in [("frequency", frequency), ("performance", performance), ("cost", cost), ("complexity", complexity)] {
+            if value < 0.0 || value.is_nan() {
+                return Err(WeightError::Negative { weight: weight.to_string(), value });
+            }
+        }
+        if frequency + performance + cost + complexity <= 0.0 {
+            return Err(WeightError::AllZero);
+        }
+        
+        self.frequency_weight = frequency;
+        self.performance_weight = performance;
+        self.cost_weight = cost;
+        self.complexity_weight = complexity;
+        self.normalize_weights();
+        Ok(())
+    }
+    
+    pub fn weights(&self) -> (f64, f64, f64, f64) {
+        (self.frequency_weight, self.performance_weight, self.cost_weight, self.complexity_weight)
+    }
+    
+    fn normalize_weights(&mut self) {
+        let total = self.frequency_weight + self.performance_weight + self.cost_weight + self.complexity_weight;
+        if total > 0.0 {
+            self.frequency_weight /= total;
+            self.performance_weight /= total;
+            self.cost_weight /= total;
+            self.complexity_weight /= total;
+        }
+    }
+    
+    /// Past outcomes se weights seekhta hai - (score, was_beneficial) pairs pe logistic regression.
+    /// Features = four normalized component scores; learned coefficients clamp to >= 0 aur
+    /// normalize hoke naye weights bante hai. Returns final mean log-loss
+    pub fn train(&mut self, samples: &[(PriorityScore, bool)]) -> f64 {
+        if samples.is_empty() {
+            return 0.0;
+        }
+        
+        let features: Vec<([f64; 4], f64)> = samples
+            .iter()
+            .map(|(score, beneficial)| {
+                (
+                    [score.frequency_score, score.performance_score, score.cost_score, score.complexity_score],
+                    if *beneficial { 1.0 } else { 0.0 },
+                )
+            })
+            .collect();
+        
+        // Current weights se start - kam data pe bhi result defaults ke paas rehta hai
+        let (f, p, c, x) = self.weights();
+        let mut coefficients = [f * 4.0, p * 4.0, c * 4.0, x * 4.0];
+        let mut bias = -2.0;
+        let n = features.len() as f64;
+        
+        let predict = |coefficients: &[f64; 4], bias: f64, x: &[f64; 4]| {
+            let logit = bias + coefficients.iter().zip(x).map(|(w, v)| w * v).sum::<f64>();
+            1.0 / (1.0 + (-logit).exp())
+        };
+        
+        for _ in 0..TRAINING_EPOCHS {
+            let mut gradient = [0.0; 4];
+            let mut bias_gradient = 0.0;
+            for (x, y) in &features {
+                let error = predict(&coefficients, bias, x) - y;
+                for (g, v) in gradient.iter_mut().zip(x) {
+                    *g += error * v / n;
+                }
+                bias_gradient += error / n;
+            }
+            for (w, g) in coefficients.iter_mut().zip(gradient) {
+                *w -= LEARNING_RATE * g;
+            }
+            bias -= LEARNING_RATE * bias_gradient;
+        }
+        
+        let learned = coefficients.map(|w| w.max(0.0));
+        if learned.iter().sum::<f64>() > 0.0 {
+            self.frequency_weight = learned[0];
+            self.performance_weight = learned[1];
+            self.cost_weight = learned[2];
+            self.complexity_weight = learned[3];
+            self.normalize_weights();
+        }
+        
+        features
+            .iter()
+            .map(|(x, y)| {
+                let p = predict(&coefficients, bias, x).clamp(1e-9, 1.0 - 1e-9);
+                -(y * p.ln() + (1.0 - y) * (1.0 - p).ln())
+            })
+            .sum::<f64>() / n
+    }
+    
     //yaha pe badme machine learning weights bhi add karna ha
     pub fn calculate_priority_score(&self, 
         index_name: &str,
@@ -129,13 +258,13 @@ impl PriorityScoringAlgorithm {
         }
     }
     
-    pub fn rank_indexes(&self, scores: &[PriorityScore]) -> Vec<&PriorityScore> {
+    pub fn rank_indexes<'a>(&self, scores: &'a [PriorityScore]) -> Vec<&'a PriorityScore> {
         let mut ranked: Vec<&PriorityScore> = scores.iter().collect();
         ranked.sort_by(|a, b| b.total_score.partial_cmp(&a.total_score).unwrap());
         ranked
     }
     
-    pub fn get_top_priorities(&self, scores: &[PriorityScore], limit: usize) -> Vec<&PriorityScore> {
+    pub fn get_top_priorities<'a>(&self, scores: &'a [PriorityScore], limit: usize) -> Vec<&'a PriorityScore> {
         let ranked = self.rank_indexes(scores);
         ranked.into_iter().take(limit).collect()
     }
@@ -160,6 +289,9 @@ impl PriorityScoringAlgorithm {
             self.cost_weight = 0.5;
             self.complexity_weight = 0.2;
         }
+        
+        // Adjusted weights ka sum 1.0 se upar ja sakta hai - warna sab "Critical" ban jata
+        self.normalize_weights();
     }
     
     pub fn calculate_roi_score(&self, 
@@ -1,28 +1,68 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 
 /// Query fingerprint for grouping similar queries - ye struct similar queries group karne ke liye hai
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct QueryFingerprint {
     pub fingerprint: String,
     pub query_count: u64,
     pub avg_execution_time: f64,
-    pub sample_queries: Vec<String>,
+    pub sample_queries: Vec<String>, // most recent samples, oldest first
     pub performance_trend: String, // "improving", "degrading", "stable"
+    pub recent_execution_times: VecDeque<u64>, // bounded rolling window for trend detection
 }
 
+const SQL_KEYWORDS: &[&str] = &[
+    "SELECT", "FROM", "WHERE", "AND", "OR", "NOT", "IN", "IS", "NULL", "LIKE", "ILIKE",
+    "BETWEEN", "JOIN", "INNER", "LEFT", "RIGHT", "FULL", "OUTER", "CROSS", "ON", "AS",
+    "ORDER", "GROUP", "BY", "HAVING", "LIMIT", "OFFSET", "INSERT", "INTO", "VALUES",
+    "UPDATE", "SET", "DELETE", "DISTINCT", "UNION", "ALL", "EXISTS", "CASE", "WHEN",
+    "THEN", "ELSE", "END", "ASC", "DESC", "WITH", "RETURNING", "CONFLICT", "DO",
+    "NOTHING", "TRUE", "FALSE",
+];
+
+const DEFAULT_SAMPLE_SIZE: usize = 5;
+
 /// Groups similar queries together - ye class similar queries group karta hai
 pub struct QueryFingerprinter {
     fingerprints: HashMap<String, QueryFingerprint>,
+    window_size: usize,
+    sample_size: usize,
+    redact: bool, // sample_queries mein raw SQL ki jagah normalized template (literals -> ?)
+}
+
+impl Default for QueryFingerprinter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl QueryFingerprinter {
     pub fn new() -> Self {
+        Self::with_window_size(20)
+    }
+    
+    /// Create fingerprinter with custom trend window - kitne recent executions trend ke liye rakhne hai
+    pub fn with_window_size(window_size: usize) -> Self {
+        Self::with_window_and_sample_size(window_size, DEFAULT_SAMPLE_SIZE)
+    }
+    
+    /// sample_size = har fingerprint ke kitne most recent sample queries rakhne hai (ring buffer)
+    pub fn with_window_and_sample_size(window_size: usize, sample_size: usize) -> Self {
         Self {
             fingerprints: HashMap::new(),
+            window_size: window_size.max(2),
+            sample_size: sample_size.max(1),
+            redact: false,
         }
     }
     
+    /// Redaction on karta hai - production logs ke emails / SSNs jaise literals samples mein store nahi hote
+    pub fn set_redact(&mut self, redact: bool) {
+        self.redact = redact;
+    }
+    
     /// Add query to fingerprinting - ye method query ko fingerprint mein add karta hai
     pub fn add_query(&mut self, query: &str, execution_time: u64) {
         let fingerprint = self.generate_fingerprint(query);
@@ -34,6 +74,7 @@ impl QueryFingerprinter {
                 avg_execution_time: 0.0,
                 sample_queries: Vec::new(),
                 performance_trend: "stable".to_string(),
+                recent_execution_times: VecDeque::new(),
             }
         });
         
@@ -42,70 +83,163 @@ impl QueryFingerprinter {
         let total_time = entry.avg_execution_time * (entry.query_count - 1) as f64 + execution_time as f64;
         entry.avg_execution_time = total_time / entry.query_count as f64;
         
-        // Keep sample queries (max 5)
-        if entry.sample_queries.len() < 5 {
-            entry.sample_queries.push(query.to_string());
+        // Keep the most recent sample_size queries - debugging ke liye purane startup samples kaam ke nahi
+        let sample = if self.redact { fingerprint } else { query.to_string() };
+        entry.sample_queries.push(sample);
+        if entry.sample_queries.len() > self.sample_size {
+            entry.sample_queries.remove(0);
+        }
+        
+        // Keep only the rolling window - purane times drop karta hai
+        entry.recent_execution_times.push_back(execution_time);
+        if entry.recent_execution_times.len() > self.window_size {
+            entry.recent_execution_times.pop_front();
         }
         
         // Update performance trend - ye trend analysis ke liye hai
-        self.update_performance_trend(entry);
+        Self::update_performance_trend(entry);
     }
     
-    fn generate_fingerprint(&self, query: &str) -> String {
-        let query_upper = query.to_uppercase();
-        let mut fingerprint = String::new();
+    /// Generate fingerprint for a query - ye method query ka normalized template banata hai
+    pub fn generate_fingerprint(&self, query: &str) -> String {
+        Self::normalize_query(query)
+    }
+    
+    /// Normalize query into a canonical template - literals ko ? se replace karta hai
+    /// e.g. "select * from users where id=1" -> "SELECT * FROM users WHERE id = ?"
+    pub fn normalize_query(query: &str) -> String {
+        let chars: Vec<char> = query.chars().collect();
+        let mut tokens: Vec<String> = Vec::new();
+        let mut i = 0;
         
-        // Extract basic structure - ye query structure identify karta hai
-        if query_upper.contains("SELECT") {
-            fingerprint.push_str("SELECT_");
-        }
-        if query_upper.contains("FROM") {
-            fingerprint.push_str("FROM_");
-        }
-        if query_upper.contains("WHERE") {
-            fingerprint.push_str("WHERE_");
-        }
-        if query_upper.contains("JOIN") {
-            fingerprint.push_str("JOIN_");
-        }
-        if query_upper.contains("ORDER BY") {
-            fingerprint.push_str("ORDER_");
-        }
-        if query_upper.contains("GROUP BY") {
-            fingerprint.push_str("GROUP_");
+        while i < chars.len() {
+            let c = chars[i];
+            
+            if c.is_whitespace() {
+                i += 1;
+            } else if c == '\'' {
+                // String literal - '' escape ko bhi handle karta hai
+                i += 1;
+                while i < chars.len() {
+                    if chars[i] == '\'' {
+                        if i + 1 < chars.len() && chars[i + 1] == '\'' {
+                            i += 2;
+                            continue;
+                        }
+                        break;
+                    }
+                    i += 1;
+                }
+                i += 1;
+                tokens.push("?".to_string());
+            } else if c == '"' || c == '`' {
+                // Quoted identifier - as it is rakhta hai
+                let start = i;
+                i += 1;
+                while i < chars.len() && chars[i] != c {
+                    i += 1;
+                }
+                i = (i + 1).min(chars.len());
+                tokens.push(chars[start..i].iter().collect());
+            } else if c.is_ascii_digit() || (c == '$' && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit())) {
+                // Numeric literal or positional placeholder ($1)
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                    i += 1;
+                }
+                tokens.push("?".to_string());
+            } else if c.is_alphanumeric() || c == '_' {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.' || chars[i] == '$') {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                let word_upper = word.to_uppercase();
+                if SQL_KEYWORDS.contains(&word_upper.as_str()) {
+                    tokens.push(word_upper);
+                } else {
+                    tokens.push(word);
+                }
+            } else {
+                // Operators - multi-char operators ek token mein
+                let pair: String = chars[i..(i + 2).min(chars.len())].iter().collect();
+                if ["<=", ">=", "<>", "!=", "||", "::"].contains(&pair.as_str()) {
+                    tokens.push(pair);
+                    i += 2;
+                } else {
+                    tokens.push(c.to_string());
+                    i += 1;
+                }
+            }
         }
         
-        // Add table count - ye table count add karta hai
-        let table_count = query_upper.matches("FROM").count() + query_upper.matches("JOIN").count();
-        fingerprint.push_str(&format!("TABLES_{}", table_count));
+        // Collapse literal lists like IN (?, ?, ?) -> IN (?) so list length doesn't split groups
+        let mut collapsed: Vec<String> = Vec::new();
+        for token in tokens {
+            let len = collapsed.len();
+            if token == "?" && len >= 2 && collapsed[len - 1] == "," && collapsed[len - 2] == "?" {
+                collapsed.pop();
+                continue;
+            }
+            collapsed.push(token);
+        }
         
-        // Add condition count - ye condition count add karta hai
-        let condition_count = query_upper.matches("AND").count() + query_upper.matches("OR").count();
-        fingerprint.push_str(&format!("_CONDITIONS_{}", condition_count));
+        let mut template = String::new();
+        let mut previous: Option<&String> = None;
+        for token in &collapsed {
+            // Function calls like COUNT(*) stay glued to their parenthesis
+            let is_call = token == "(" && previous.is_some_and(|p| {
+                p.chars().all(|ch| ch.is_alphanumeric() || ch == '_') && !SQL_KEYWORDS.contains(&p.as_str())
+            });
+            let no_space_before = token == "," || token == ")" || is_call || template.ends_with('(');
+            if !template.is_empty() && !no_space_before {
+                template.push(' ');
+            }
+            template.push_str(token);
+            previous = Some(token);
+        }
         
-        fingerprint
+        template
     }
     
-    fn update_performance_trend(&self, fingerprint: &mut QueryFingerprint) {
-        // Simple trend analysis - ye basic trend analysis hai
-        if fingerprint.query_count >= 3 {
-            let recent_avg = fingerprint.avg_execution_time;
-            if recent_avg > 200.0 {
-                fingerprint.performance_trend = "degrading".to_string();
-            } else if recent_avg < 50.0 {
-                fingerprint.performance_trend = "improving".to_string();
-            } else {
-                fingerprint.performance_trend = "stable".to_string();
-            }
+    fn update_performance_trend(fingerprint: &mut QueryFingerprint) {
+        // Compare recent half vs older half of the window - lifetime average trend nahi batata
+        let times = &fingerprint.recent_execution_times;
+        if times.len() < 4 {
+            fingerprint.performance_trend = "stable".to_string();
+            return;
         }
+        
+        let half = times.len() / 2;
+        let older_avg = times.iter().take(half).sum::<u64>() as f64 / half as f64;
+        let recent_avg = times.iter().skip(times.len() - half).sum::<u64>() as f64 / half as f64;
+        
+        let change = if older_avg > 0.0 {
+            (recent_avg - older_avg) / older_avg
+        } else if recent_avg > 0.0 {
+            1.0
+        } else {
+            0.0
+        };
+        
+        fingerprint.performance_trend = if change > 0.2 {
+            "degrading".to_string()
+        } else if change < -0.2 {
+            "improving".to_string()
+        } else {
+            "stable".to_string()
+        };
     }
     
     /// Get similar query groups - ye method similar query groups return karta hai
     pub fn get_similar_groups(&self, min_count: u64) -> Vec<&QueryFingerprint> {
-        self.fingerprints
+        let mut groups: Vec<&QueryFingerprint> = self.fingerprints
             .values()
             .filter(|fp| fp.query_count >= min_count)
-            .collect()
+            .collect();
+        // Most frequent first, ties fingerprint se - HashMap order pe depend nahi karta
+        groups.sort_by(|a, b| b.query_count.cmp(&a.query_count).then_with(|| a.fingerprint.cmp(&b.fingerprint)));
+        groups
     }
     
     /// Get performance insights - ye method performance insights deta hai
@@ -151,9 +285,16 @@ impl QueryFingerprinter {
     
     /// Get optimization candidates - ye method optimization candidates suggest karta hai
     pub fn get_optimization_candidates(&self) -> Vec<&QueryFingerprint> {
-        self.fingerprints
+        let mut candidates: Vec<&QueryFingerprint> = self.fingerprints
             .values()
             .filter(|fp| fp.query_count >= 5 && fp.avg_execution_time > 100.0)
-            .collect()
+            .collect();
+        // Slowest first, ties fingerprint se
+        candidates.sort_by(|a, b| {
+            b.avg_execution_time
+                .total_cmp(&a.avg_execution_time)
+                .then_with(|| a.fingerprint.cmp(&b.fingerprint))
+        });
+        candidates
     }
 }
@@ -1,12 +1,29 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::sync::Arc;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use super::template_interner::stable_hash;
+use super::{QueryFingerprinter, TemplateInterner};
+
+// efficiency_score() ka default reference - 1ms per row pe score 0.5
+pub const DEFAULT_BASELINE_TIME_PER_ROW_MS: f64 = 1.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct QueryLog {
     pub query: String,
     pub execution_time_ms: u64,
     pub timestamp: u64,
     pub tables_accessed: Vec<String>,
     pub rows_scanned: u64,
+    #[serde(default)]
+    pub rows_returned: Option<u64>, // None = log source rows sent report nahi karta (e.g. postgres csvlog)
+    #[serde(default)]
+    pub tags: Vec<String>, // query class - "api", "cron", "adhoc"; PatternAnalyzer::analyze_by_tag inpe group karta hai
+    // Pre-computed normalized template (interned) aur uska hash - serialize nahi hote, query se derive hote hai
+    #[serde(skip)]
+    template: Option<Arc<str>>,
+    #[serde(skip)]
+    fingerprint_hash: Option<u64>,
 }
 
 impl QueryLog {
@@ -23,16 +40,98 @@ impl QueryLog {
             timestamp,
             tables_accessed,
             rows_scanned,
+            rows_returned: None,
+            tags: Vec::new(),
+            template: None,
+            fingerprint_hash: None,
+        }
+    }
+
+    pub fn with_rows_returned(mut self, rows_returned: u64) -> Self {
+        self.rows_returned = Some(rows_returned);
+        self
+    }
+
+    /// Query class tag add karta hai - duplicate tag ignore hota hai
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        if !self.has_tag(tag) {
+            self.tags.push(tag.to_string());
+        }
+        self
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// rows_scanned / rows_returned - 1M rows scan karke 10 return karna missing index ka sabse
+    /// strong signal hai. Zero rows returned ko 1 maana jata hai; rows_returned na ho to None
+    pub fn scan_amplification(&self) -> Option<f64> {
+        let rows_returned = self.rows_returned?;
+        Some(self.rows_scanned as f64 / rows_returned.max(1) as f64)
+    }
+
+    /// Literals ko ? se replace karke copy deta hai (normalized template) - exported reports mein PII
+    /// leak na ho. Fingerprint same rehta hai isliye grouping nahi badalti
+    pub fn redacted(&self) -> QueryLog {
+        let mut log = self.clone();
+        log.redact();
+        log
+    }
+
+    /// In-place redaction - redacted() jaisa, bina clone ke
+    pub fn redact(&mut self) {
+        let fingerprint = self.fingerprint();
+        let template = self.template();
+        self.query = template.to_string();
+        self.template = Some(template);
+        self.fingerprint_hash = Some(fingerprint);
+    }
+
+    /// Template aur fingerprint pehle hi compute karke interner se shared template attach karta hai
+    pub fn with_interned_template(mut self, interner: &mut TemplateInterner) -> Self {
+        let template = interner.intern_query(&self.query);
+        self.fingerprint_hash = Some(stable_hash(&template));
+        self.template = Some(template);
+        self
+    }
+
+    /// Normalized template - attached ho to wahi, warna abhi normalize karta hai
+    pub fn template(&self) -> Arc<str> {
+        match &self.template {
+            Some(template) => Arc::clone(template),
+            None => Arc::from(QueryFingerprinter::normalize_query(&self.query)),
+        }
+    }
+
+    /// Stable 64-bit hash of the normalized template - runs aur processes ke beech same rehta hai
+    pub fn fingerprint(&self) -> u64 {
+        match (self.fingerprint_hash, &self.template) {
+            (Some(hash), _) => hash,
+            (None, Some(template)) => stable_hash(template),
+            (None, None) => stable_hash(&QueryFingerprinter::normalize_query(&self.query)),
         }
     }
 
     pub fn query_type(&self) -> String {
-        let query_upper = self.query.trim().to_uppercase();
+        Self::classify(&self.query)
+    }
+
+    // Raw SQL text ka statement type - bina QueryLog banaye
+    pub fn classify(query: &str) -> String {
+        let query_upper = query.trim().to_uppercase();
         
         if query_upper.starts_with("SELECT") {
             "SELECT".to_string()
-        } else if query_upper.starts_with("INSERT") {
-            "INSERT".to_string()
+        } else if query_upper.starts_with("INSERT") || query_upper.starts_with("REPLACE") {
+            // Insert + conflict pe update - locking aur index maintenance dono jaisa behave karta hai
+            if Self::is_upsert(&query_upper) {
+                "UPSERT".to_string()
+            } else {
+                "INSERT".to_string()
+            }
+        } else if query_upper.starts_with("MERGE") {
+            "MERGE".to_string()
         } else if query_upper.starts_with("UPDATE") {
             "UPDATE".to_string()
         } else if query_upper.starts_with("DELETE") {
@@ -42,17 +141,35 @@ impl QueryLog {
         }
     }
 
+    // Postgres/SQLite ON CONFLICT, MySQL ON DUPLICATE KEY UPDATE, SQLite INSERT OR REPLACE, REPLACE INTO
+    fn is_upsert(query_upper: &str) -> bool {
+        let normalized = query_upper.split_whitespace().collect::<Vec<_>>().join(" ");
+        normalized.starts_with("REPLACE")
+            || normalized.starts_with("INSERT OR REPLACE")
+            || normalized.contains(" ON CONFLICT")
+            || normalized.contains(" ON DUPLICATE KEY UPDATE")
+    }
+
     pub fn is_slow(&self, threshold_ms: u64) -> bool {
         self.execution_time_ms > threshold_ms
     }
 
     //yaha pe badme cache hit ratio bhi add karna ha
     pub fn efficiency_score(&self) -> f64 {
-        if self.rows_scanned == 0 {
+        self.efficiency_score_with_baseline(DEFAULT_BASELINE_TIME_PER_ROW_MS)
+    }
+
+    /// 0.0-1.0 score relative to workload ka baseline: baseline / (baseline + time_per_row).
+    /// time_per_row == baseline pe 0.5; zero rows ya zero time pe 1.0 (kuch kaam nahi hua)
+    pub fn efficiency_score_with_baseline(&self, baseline_time_per_row_ms: f64) -> f64 {
+        if self.rows_scanned == 0 || self.execution_time_ms == 0 {
             return 1.0;
         }
-        
+
         let time_per_row = self.execution_time_ms as f64 / self.rows_scanned as f64;
-        1000.0 / (time_per_row + 1.0)
+        if baseline_time_per_row_ms <= 0.0 || baseline_time_per_row_ms.is_nan() {
+            return 0.0;
+        }
+        (baseline_time_per_row_ms / (baseline_time_per_row_ms + time_per_row)).clamp(0.0, 1.0)
     }
 }
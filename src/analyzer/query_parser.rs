@@ -1,134 +1,787 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::collections::HashMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ParsedQuery {
     pub where_clauses: Vec<String>,
     pub join_conditions: Vec<String>,
+    pub joins: Vec<JoinClause>,
     pub order_by_columns: Vec<String>,
-    pub select_columns: Vec<String>,
+    #[serde(default)]
+    pub group_by_columns: Vec<String>,
+    pub select_columns: Vec<String>, // expressions with aliases stripped
+    #[serde(default)]
+    pub select_items: Vec<SelectItem>,
     pub from_tables: Vec<String>,
+    pub table_aliases: HashMap<String, String>, // alias -> base table
+    pub insert_columns: Vec<String>, // INSERT INTO t (a, b) - column list na ho to empty
+    pub cte_names: Vec<String>,
+    #[serde(default)]
+    pub limit: Option<u64>, // LIMIT n / FETCH FIRST n ROWS ONLY
+    #[serde(default)]
+    pub offset: Option<u64>,
+    pub has_subquery: bool,
     pub query_fingerprint: String,
 }
 
+/// One SELECT list entry - "SUM(amount) AS total" -> expression "SUM(amount)", alias "total"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SelectItem {
+    pub expression: String,
+    pub alias: Option<String>,
+    pub is_aggregate: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct JoinClause {
+    pub join_type: String, // INNER, LEFT, RIGHT, FULL, CROSS
+    pub left_table: String,
+    pub right_table: String,
+    pub on_columns: Vec<(String, String)>, // resolved "table.column" pairs from ON / USING
+}
+
+impl Default for ParsedQuery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ParsedQuery {
     pub fn new() -> Self {
         Self {
             where_clauses: Vec::new(),
             join_conditions: Vec::new(),
+            joins: Vec::new(),
             order_by_columns: Vec::new(),
+            group_by_columns: Vec::new(),
             select_columns: Vec::new(),
+            select_items: Vec::new(),
             from_tables: Vec::new(),
+            table_aliases: HashMap::new(),
+            insert_columns: Vec::new(),
+            cte_names: Vec::new(),
+            limit: None,
+            offset: None,
+            has_subquery: false,
             query_fingerprint: String::new(),
         }
     }
+
+    pub fn resolve_table(&self, name: &str) -> String {
+        self.table_aliases.get(name).cloned().unwrap_or_else(|| name.to_string())
+    }
+
+    // Columns used in WHERE predicates and JOIN conditions, "table.column" jaha alias resolve ho sake
+    pub fn referenced_columns(&self) -> Vec<String> {
+        let mut columns: Vec<String> = Vec::new();
+        
+        for clause in &self.where_clauses {
+            let column = clause
+                .trim_start_matches('(')
+                .split(|c: char| c.is_whitespace() || "=<>!".contains(c))
+                .next()
+                .unwrap_or("");
+            if !column.is_empty() && !columns.contains(&self.resolve_column(column)) {
+                columns.push(self.resolve_column(column));
+            }
+        }
+        
+        for join in &self.joins {
+            for (left, right) in &join.on_columns {
+                for column in [left, right] {
+                    if !columns.contains(column) {
+                        columns.push(column.clone());
+                    }
+                }
+            }
+        }
+        
+        columns
+    }
+    
+    // "u.id" -> "users.id" using the FROM clause aliases
+    pub fn resolve_column(&self, column: &str) -> String {
+        match column.split_once('.') {
+            Some((table, col)) => format!("{}.{}", self.resolve_table(table), col),
+            None => column.to_string(),
+        }
+    }
 }
 
+const DERIVED_TABLE: &str = "__derived__";
+const AGGREGATE_FUNCTIONS: &[&str] = &[
+    "COUNT", "SUM", "AVG", "MIN", "MAX", "ARRAY_AGG", "STRING_AGG", "GROUP_CONCAT", "JSON_AGG",
+    "BOOL_AND", "BOOL_OR", "STDDEV", "VARIANCE",
+];
+const TRANSACTION_CONTROL_KEYWORDS: &[&str] = &[
+    "BEGIN", "START", "COMMIT", "ROLLBACK", "SAVEPOINT", "RELEASE", "END", "ABORT",
+];
+const JOIN_MODIFIERS: &[&str] = &["INNER", "LEFT", "RIGHT", "FULL", "OUTER", "CROSS", "NATURAL"];
+
 pub struct QueryParser;
 
 impl QueryParser {
     //yaha pe badme complex SQL parsing bhi add karna ha
     pub fn parse(&self, query: &str) -> ParsedQuery {
         let query_upper = query.to_uppercase();
+        // Keyword positions sirf top level pe dhoondhte hai - CTE bodies, subqueries, strings masked
+        let top_level = self.mask_nested(&query_upper);
         let mut parsed = ParsedQuery::new();
         
-        if let Some(where_start) = query_upper.find("WHERE") {
-            if let Some(where_end) = self.find_clause_end(&query[where_start..]) {
-                let where_clause = &query[where_start + 5..where_start + where_end];
-                parsed.where_clauses = self.extract_conditions(where_clause);
+        parsed.cte_names = self.extract_cte_names(query, &top_level);
+        parsed.has_subquery = self.detect_subquery(&query_upper, &top_level);
+        
+        if let Some(insert_start) = self.find_keyword(&top_level, "INSERT") {
+            self.parse_insert(query, &top_level, insert_start, &mut parsed);
+            if self.find_keyword(&top_level, "SELECT").is_none() {
+                parsed.query_fingerprint = self.generate_fingerprint(&parsed);
+                return parsed;
             }
         }
         
-        parsed.join_conditions = self.extract_joins(query);
+        if top_level.trim_start().starts_with("UPDATE") {
+            self.parse_update_target(query, &top_level, &mut parsed);
+        }
         
-        if let Some(order_start) = query_upper.find("ORDER BY") {
-            if let Some(order_end) = self.find_clause_end(&query[order_start..]) {
-                let order_clause = &query[order_start + 9..order_start + order_end];
-                parsed.order_by_columns = self.extract_columns(order_clause);
-            }
+        if let Some(where_start) = self.find_keyword(&top_level, "WHERE") {
+            let where_end = where_start + self.find_clause_end(&top_level[where_start..]);
+            let where_clause = &query[where_start + 5..where_end];
+            parsed.where_clauses = self.extract_conditions(where_clause);
         }
         
-        if let Some(select_start) = query_upper.find("SELECT") {
-            if let Some(from_start) = query_upper.find("FROM") {
-                let select_clause = &query[select_start + 6..from_start];
-                parsed.select_columns = self.extract_columns(select_clause);
-            }
+        if let Some(order_start) = self.find_keyword(&top_level, "ORDER BY") {
+            let order_end = order_start + self.find_clause_end(&top_level[order_start..]);
+            let order_clause = &query[order_start + 8..order_end];
+            parsed.order_by_columns = self.extract_columns(order_clause);
         }
         
-        if let Some(from_start) = query_upper.find("FROM") {
-            if let Some(where_start) = query_upper.find("WHERE") {
-                let from_clause = &query[from_start + 4..where_start];
-                parsed.from_tables = self.extract_tables(from_clause);
-            } else if let Some(order_start) = query_upper.find("ORDER BY") {
-                let from_clause = &query[from_start + 4..order_start];
-                parsed.from_tables = self.extract_tables(from_clause);
-            } else {
-                parsed.from_tables = self.extract_tables(&query[from_start + 4..]);
+        if let Some(group_start) = self.find_keyword(&top_level, "GROUP BY") {
+            let group_end = group_start + self.find_clause_end(&top_level[group_start..]);
+            let group_clause = &query[group_start + 8..group_end];
+            parsed.group_by_columns = self.extract_columns(group_clause);
+        }
+        
+        if let Some(select_start) = self.find_keyword(&top_level, "SELECT")
+            && let Some(from_start) = self.find_keyword(&top_level, "FROM") {
+                let select_clause = &query[select_start + 6..from_start];
+                parsed.select_items = self.extract_select_items(select_clause);
+                parsed.select_columns = parsed.select_items.iter().map(|item| item.expression.clone()).collect();
             }
+        
+        if let Some(from_start) = self.find_keyword(&top_level, "FROM") {
+            let from_end = from_start + self.find_clause_end(&top_level[from_start..]);
+            let from_clause = self.collapse_subqueries(&query[from_start + 4..from_end]);
+            self.parse_from_clause(&from_clause, &mut parsed);
+            // Derived tables real tables nahi hai - alias rehta hai, from_tables se hata do
+            parsed.from_tables.retain(|t| t != DERIVED_TABLE);
         }
         
+        self.parse_limit_offset(&query_upper, &top_level, &mut parsed);
+        
         parsed.query_fingerprint = self.generate_fingerprint(&parsed);
         
         parsed
     }
     
-    fn extract_conditions(&self, where_clause: &str) -> Vec<String> {
-        where_clause
-            .split("AND")
-            .chain(where_clause.split("OR"))
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
+    /// Multi-statement SQL ("BEGIN; UPDATE ...; UPDATE ...; COMMIT;") ko har statement ke
+    /// ParsedQuery mein todta hai - transaction control statements skip hote hai
+    pub fn parse_batch(&self, sql: &str) -> Vec<ParsedQuery> {
+        self.split_statements(sql)
+            .iter()
+            .filter(|statement| !Self::is_transaction_control(statement))
+            .map(|statement| self.parse(statement))
             .collect()
     }
     
-    fn extract_joins(&self, query: &str) -> Vec<String> {
-        let query_upper = query.to_uppercase();
-        let mut joins = Vec::new();
-        
+    /// Top-level semicolons pe split - string literals, quoted identifiers aur parentheses ke andar wale ';' ignore
+    pub fn split_statements(&self, sql: &str) -> Vec<String> {
+        let mut statements = Vec::new();
+        let mut depth = 0usize;
+        let mut quote: Option<char> = None;
         let mut start = 0;
-        while let Some(join_pos) = query_upper[start..].find("JOIN") {
-            let actual_pos = start + join_pos;
-            if let Some(end) = self.find_clause_end(&query[actual_pos..]) {
-                let join_clause = &query[actual_pos..actual_pos + end];
-                joins.push(join_clause.trim().to_string());
-                start = actual_pos + end;
+        
+        for (pos, c) in sql.char_indices() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None => match c {
+                    '\'' | '"' | '`' => quote = Some(c),
+                    '(' => depth += 1,
+                    ')' => depth = depth.saturating_sub(1),
+                    ';' if depth == 0 => {
+                        statements.push(sql[start..pos].trim().to_string());
+                        start = pos + 1;
+                    }
+                    _ => {}
+                },
+            }
+        }
+        statements.push(sql[start..].trim().to_string());
+        statements.retain(|statement| !statement.is_empty());
+        statements
+    }
+    
+    // BEGIN, START TRANSACTION, COMMIT, ROLLBACK, SAVEPOINT ... - inme koi table access nahi hota
+    fn is_transaction_control(statement: &str) -> bool {
+        let first_word = statement.split_whitespace().next().unwrap_or("").to_uppercase();
+        TRANSACTION_CONTROL_KEYWORDS.contains(&first_word.as_str())
+    }
+    
+    // Same-length copy with everything inside parentheses and string literals blanked out
+    fn mask_nested(&self, text: &str) -> String {
+        let mut masked = String::with_capacity(text.len());
+        let mut depth = 0usize;
+        let mut in_string = false;
+        
+        for c in text.chars() {
+            let keep = if in_string {
+                if c == '\'' {
+                    in_string = false;
+                }
+                false
+            } else if c == '\'' {
+                in_string = true;
+                false
+            } else if c == '(' {
+                depth += 1;
+                depth == 1
+            } else if c == ')' {
+                depth = depth.saturating_sub(1);
+                depth == 0
             } else {
+                depth == 0
+            };
+            
+            if keep {
+                masked.push(c);
+            } else {
+                // Preserve byte length so positions line up with the original query
+                masked.extend(std::iter::repeat_n(' ', c.len_utf8()));
+            }
+        }
+        
+        masked
+    }
+    
+    // WITH a AS (...), b AS (...) SELECT ... -> ["a", "b"]
+    fn extract_cte_names(&self, query: &str, top_level: &str) -> Vec<String> {
+        let mut names = Vec::new();
+        if !top_level.trim_start().starts_with("WITH") {
+            return names;
+        }
+        
+        let with_start = top_level.find("WITH").unwrap_or(0);
+        let tokens: Vec<(usize, &str)> = top_level[with_start + 4..]
+            .split_whitespace()
+            .map(|t| (t.as_ptr() as usize - top_level.as_ptr() as usize, t))
+            .collect();
+        
+        let mut expect_name = true;
+        for (pos, token) in tokens {
+            if ["SELECT", "INSERT", "UPDATE", "DELETE"].contains(&token) {
+                break;
+            }
+            if token == "RECURSIVE" {
+                continue;
+            }
+            if expect_name {
+                let name = query[pos..pos + token.len()].trim_end_matches(['(', ')', ',']);
+                if !name.is_empty() {
+                    names.push(name.to_string());
+                }
+                expect_name = false;
+            } else if token.ends_with(',') || token == "," {
+                expect_name = true;
+            }
+        }
+        
+        names
+    }
+    
+    // SELECT inside parentheses of the main statement (CTE bodies ko chhod ke)
+    fn detect_subquery(&self, query_upper: &str, top_level: &str) -> bool {
+        let main_start = ["SELECT", "INSERT", "UPDATE", "DELETE"]
+            .iter()
+            .filter_map(|keyword| self.find_keyword(top_level, keyword))
+            .min()
+            .unwrap_or(0);
+        
+        let count = |text: &str| {
+            let mut total = 0;
+            let mut offset = 0;
+            while let Some(pos) = self.find_keyword(&text[offset..], "SELECT") {
+                total += 1;
+                offset += pos + "SELECT".len();
+            }
+            total
+        };
+        
+        count(&query_upper[main_start..]) > count(&top_level[main_start..])
+    }
+    
+    // FROM (SELECT ...) sub -> FROM __derived__ sub
+    fn collapse_subqueries(&self, from_clause: &str) -> String {
+        let mut result = String::new();
+        let mut rest = from_clause;
+        
+        while let Some(open) = rest.find('(') {
+            let mut depth = 0;
+            let mut close = None;
+            for (i, c) in rest[open..].char_indices() {
+                match c {
+                    '(' => depth += 1,
+                    ')' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            close = Some(open + i);
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            let Some(close) = close else {
                 break;
+            };
+            
+            let inner = rest[open + 1..close].trim_start().to_uppercase();
+            result.push_str(&rest[..open]);
+            if inner.starts_with("SELECT") || inner.starts_with("WITH") {
+                result.push_str(&format!(" {} ", DERIVED_TABLE));
+            } else {
+                result.push_str(&rest[open..=close]);
             }
+            rest = &rest[close + 1..];
         }
         
-        joins
+        result.push_str(rest);
+        result
+    }
+    
+    // INSERT INTO t (a, b, c) VALUES (...) - target table aur column list
+    fn parse_insert(&self, query: &str, top_level: &str, insert_start: usize, parsed: &mut ParsedQuery) {
+        let Some(into_start) = self.find_keyword(&top_level[insert_start..], "INTO").map(|pos| insert_start + pos) else {
+            return;
+        };
+        
+        let rest = query[into_start + 4..].trim_start();
+        let table_end = rest
+            .find(|c: char| c.is_whitespace() || c == '(')
+            .unwrap_or(rest.len());
+        let table = rest[..table_end].trim_matches(|c| c == '"' || c == '`');
+        if table.is_empty() {
+            return;
+        }
+        parsed.from_tables.push(table.to_string());
+        
+        let after_table = rest[table_end..].trim_start();
+        if let Some(column_list) = after_table.strip_prefix('(')
+            && let Some(list_end) = column_list.find(')') {
+                parsed.insert_columns = column_list[..list_end]
+                    .split(',')
+                    .map(|c| c.trim().trim_matches(|c| c == '"' || c == '`').to_string())
+                    .filter(|c| !c.is_empty())
+                    .collect();
+            }
+    }
+    
+    // UPDATE [ONLY | LOW_PRIORITY | IGNORE] t [[AS] alias] SET ... - target table FROM clause mein nahi hota
+    fn parse_update_target(&self, query: &str, top_level: &str, parsed: &mut ParsedQuery) {
+        let update_start = top_level.find("UPDATE").unwrap_or(0) + 6;
+        let set_start = self.find_keyword(top_level, "SET").unwrap_or(query.len());
+        if set_start <= update_start {
+            return;
+        }
+        
+        let tokens: Vec<&str> = query[update_start..set_start]
+            .split_whitespace()
+            .filter(|token| !matches!(token.to_uppercase().as_str(), "ONLY" | "LOW_PRIORITY" | "IGNORE" | "AS"))
+            .map(|token| token.trim_matches(|c| c == '"' || c == '`'))
+            .collect();
+        let Some(table) = tokens.first().filter(|table| !table.is_empty()) else {
+            return;
+        };
+        parsed.from_tables.push(table.to_string());
+        // Sirf "t alias" - MySQL multi-table UPDATE (a JOIN b ...) mein doosra token JOIN hota hai
+        if let [_, alias] = tokens.as_slice() {
+            parsed.table_aliases.insert(alias.to_string(), table.to_string());
+        }
+    }
+    
+    // Top-level AND / OR pe split karta hai (whole word, case-insensitive) - parentheses ke andar nahi
+    fn extract_conditions(&self, where_clause: &str) -> Vec<String> {
+        let mut conditions = Vec::new();
+        let mut current: Vec<&str> = Vec::new();
+        let mut depth: i32 = 0;
+        
+        for token in where_clause.split_whitespace() {
+            let at_top_level = depth == 0;
+            depth += token.matches('(').count() as i32 - token.matches(')').count() as i32;
+            
+            if at_top_level && (token.eq_ignore_ascii_case("AND") || token.eq_ignore_ascii_case("OR")) {
+                conditions.push(current.join(" "));
+                current.clear();
+            } else {
+                current.push(token);
+            }
+        }
+        conditions.push(current.join(" "));
+        
+        conditions.retain(|c| !c.is_empty());
+        conditions
+    }
+    
+    // FROM clause se tables, aliases aur structured joins nikalta hai
+    fn parse_from_clause(&self, from_clause: &str, parsed: &mut ParsedQuery) {
+        let spaced = from_clause.replace(',', " , ");
+        let tokens: Vec<&str> = spaced.split_whitespace().collect();
+        let mut i = 0;
+        
+        let mut previous_table = match self.parse_table_ref(&tokens, &mut i, parsed) {
+            Some(table) => table,
+            None => return,
+        };
+        
+        while i < tokens.len() {
+            if tokens[i] == "," {
+                // Comma join - join condition WHERE mein ho sakti hai, warna Cartesian product hai
+                i += 1;
+                if let Some(table) = self.parse_table_ref(&tokens, &mut i, parsed) {
+                    let mut on_columns: Vec<(String, String)> = Vec::new();
+                    for pair in parsed.where_clauses.iter().flat_map(|clause| self.extract_join_columns(clause, parsed)) {
+                        let left_table = pair.0.split_once('.').map(|(t, _)| t).unwrap_or("");
+                        let right_table = pair.1.split_once('.').map(|(t, _)| t).unwrap_or("");
+                        if (left_table == table) != (right_table == table) && !on_columns.contains(&pair) {
+                            on_columns.push(pair);
+                        }
+                    }
+                    let left_table = on_columns
+                        .iter()
+                        .flat_map(|(l, r)| [l, r])
+                        .filter_map(|col| col.split_once('.').map(|(t, _)| t.to_string()))
+                        .find(|t| *t != table)
+                        .unwrap_or_else(|| previous_table.clone());
+                    let join_type = if on_columns.is_empty() { "CROSS" } else { "INNER" };
+                    
+                    parsed.join_conditions.push(format!("{} JOIN {}", join_type, table));
+                    parsed.joins.push(JoinClause {
+                        join_type: join_type.to_string(),
+                        left_table,
+                        right_table: table.clone(),
+                        on_columns,
+                    });
+                    previous_table = table;
+                }
+                continue;
+            }
+            
+            // Collect join modifiers up to the JOIN keyword
+            let mut modifiers = Vec::new();
+            while i < tokens.len() && JOIN_MODIFIERS.contains(&tokens[i].to_uppercase().as_str()) {
+                modifiers.push(tokens[i].to_uppercase());
+                i += 1;
+            }
+            if i >= tokens.len() || !tokens[i].eq_ignore_ascii_case("JOIN") {
+                i += 1;
+                continue;
+            }
+            i += 1;
+            
+            let right_table = match self.parse_table_ref(&tokens, &mut i, parsed) {
+                Some(table) => table,
+                None => break,
+            };
+            
+            let mut condition_tokens = Vec::new();
+            let mut using_columns = Vec::new();
+            if i < tokens.len() && tokens[i].eq_ignore_ascii_case("ON") {
+                i += 1;
+                while i < tokens.len() && !self.is_join_start(tokens[i]) {
+                    condition_tokens.push(tokens[i]);
+                    i += 1;
+                }
+            } else if i < tokens.len() && tokens[i].eq_ignore_ascii_case("USING") {
+                i += 1;
+                while i < tokens.len() && !self.is_join_start(tokens[i]) {
+                    let column = tokens[i].trim_matches(|c| c == '(' || c == ')');
+                    if !column.is_empty() {
+                        using_columns.push(column.to_string());
+                    }
+                    i += 1;
+                }
+            }
+            
+            let condition = condition_tokens.join(" ");
+            let mut on_columns = self.extract_join_columns(&condition, parsed);
+            
+            // Left side is whichever other table the ON clause references, else the previous one
+            let left_table = on_columns
+                .iter()
+                .flat_map(|(l, r)| [l, r])
+                .filter_map(|col| col.split_once('.').map(|(t, _)| t.to_string()))
+                .find(|t| *t != right_table)
+                .unwrap_or_else(|| previous_table.clone());
+            
+            for column in using_columns {
+                on_columns.push((format!("{}.{}", left_table, column), format!("{}.{}", right_table, column)));
+            }
+            
+            let join_type = if modifiers.iter().any(|m| m == "LEFT") {
+                "LEFT"
+            } else if modifiers.iter().any(|m| m == "RIGHT") {
+                "RIGHT"
+            } else if modifiers.iter().any(|m| m == "FULL") {
+                "FULL"
+            } else if modifiers.iter().any(|m| m == "CROSS") {
+                "CROSS"
+            } else {
+                "INNER"
+            };
+            
+            parsed.join_conditions.push(if condition.is_empty() {
+                format!("JOIN {}", right_table)
+            } else {
+                condition
+            });
+            parsed.joins.push(JoinClause {
+                join_type: join_type.to_string(),
+                left_table,
+                right_table: right_table.clone(),
+                on_columns,
+            });
+            
+            previous_table = right_table;
+        }
+    }
+    
+    // "users u" / "users AS u" parse karta hai aur alias register karta hai
+    fn parse_table_ref(&self, tokens: &[&str], i: &mut usize, parsed: &mut ParsedQuery) -> Option<String> {
+        let table = tokens.get(*i)?.to_string();
+        if table == "," || self.is_join_start(&table) {
+            return None;
+        }
+        *i += 1;
+        
+        if tokens.get(*i).is_some_and(|t| t.eq_ignore_ascii_case("AS")) {
+            *i += 1;
+        }
+        if let Some(alias) = tokens.get(*i) {
+            let alias_upper = alias.to_uppercase();
+            if *alias != "," && !self.is_join_start(alias) && alias_upper != "ON" && alias_upper != "USING" {
+                parsed.table_aliases.insert(alias.to_string(), table.clone());
+                *i += 1;
+            }
+        }
+        
+        if !parsed.from_tables.contains(&table) {
+            parsed.from_tables.push(table.clone());
+        }
+        Some(table)
+    }
+    
+    fn is_join_start(&self, token: &str) -> bool {
+        let upper = token.to_uppercase();
+        upper == "JOIN" || upper == "," || JOIN_MODIFIERS.contains(&upper.as_str())
+    }
+    
+    fn extract_join_columns(&self, condition: &str, parsed: &ParsedQuery) -> Vec<(String, String)> {
+        let condition_upper = condition.to_uppercase();
+        let mut columns = Vec::new();
+        let mut rest = condition;
+        let mut rest_upper = condition_upper.as_str();
+        
+        loop {
+            let (part, next) = match rest_upper.find(" AND ") {
+                Some(pos) => (&rest[..pos], Some(pos + 5)),
+                None => (rest, None),
+            };
+            
+            if let Some((left, right)) = part.split_once('=') {
+                let left = left.trim().trim_matches(|c| c == '(' || c == ')');
+                let right = right.trim().trim_matches(|c| c == '(' || c == ')');
+                if left.contains('.') && right.contains('.') {
+                    columns.push((parsed.resolve_column(left), parsed.resolve_column(right)));
+                }
+            }
+            
+            match next {
+                Some(pos) => {
+                    rest = &rest[pos..];
+                    rest_upper = &rest_upper[pos..];
+                }
+                None => break,
+            }
+        }
+        
+        columns
+    }
+    
+    // LIMIT n, LIMIT n OFFSET m, MySQL ka LIMIT m, n aur FETCH FIRST n ROWS ONLY
+    fn parse_limit_offset(&self, query_upper: &str, top_level: &str, parsed: &mut ParsedQuery) {
+        let number_after = |keyword_end: usize| -> Option<u64> {
+            let digits: String = query_upper[keyword_end..]
+                .trim_start()
+                .chars()
+                .take_while(|c| c.is_ascii_digit())
+                .collect();
+            digits.parse().ok()
+        };
+        
+        if let Some(limit_start) = self.find_keyword(top_level, "LIMIT") {
+            let after_limit = limit_start + 5;
+            parsed.limit = number_after(after_limit);
+            
+            // MySQL "LIMIT offset, count" - pehla number offset hai
+            let rest = query_upper[after_limit..].trim_start();
+            if let Some((first, second)) = rest.split_once(',') {
+                let first = first.trim();
+                if !first.is_empty() && first.chars().all(|c| c.is_ascii_digit()) {
+                    parsed.offset = first.parse().ok();
+                    parsed.limit = number_after(query_upper.len() - second.len());
+                }
+            }
+        } else if let Some(fetch_start) = self.find_keyword(top_level, "FETCH") {
+            let after_fetch = query_upper[fetch_start + 5..].trim_start();
+            let skip = if after_fetch.starts_with("FIRST") || after_fetch.starts_with("NEXT") {
+                after_fetch.find(char::is_whitespace).unwrap_or(0)
+            } else {
+                0
+            };
+            parsed.limit = number_after(query_upper.len() - after_fetch.len() + skip);
+        }
+        
+        if let Some(offset_start) = self.find_keyword(top_level, "OFFSET") {
+            parsed.offset = number_after(offset_start + 6);
+        }
     }
     
     fn extract_columns(&self, clause: &str) -> Vec<String> {
-        clause
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
+        self.split_top_level(clause)
+    }
+    
+    // Comma split jo parentheses aur string literals ke andar nahi todta - SUM(a, b) ek hi item hai
+    fn split_top_level(&self, clause: &str) -> Vec<String> {
+        let masked = self.mask_nested(clause);
+        let mut items = Vec::new();
+        let mut start = 0;
+        
+        for (pos, c) in masked.char_indices() {
+            if c == ',' {
+                items.push(clause[start..pos].trim().to_string());
+                start = pos + 1;
+            }
+        }
+        items.push(clause[start..].trim().to_string());
+        items.retain(|item| !item.is_empty());
+        items
+    }
+    
+    // "DISTINCT a", "SUM(x) AS total", "a.b bb" -> expression + alias + aggregate flag
+    fn extract_select_items(&self, select_clause: &str) -> Vec<SelectItem> {
+        let mut clause = select_clause.trim();
+        for prefix in ["DISTINCT ", "ALL "] {
+            if clause.len() >= prefix.len() && clause[..prefix.len()].eq_ignore_ascii_case(prefix) {
+                clause = clause[prefix.len()..].trim_start();
+            }
+        }
+        
+        self.split_top_level(clause)
+            .into_iter()
+            .map(|item| {
+                let (expression, alias) = self.split_alias(&item);
+                let function = expression.split('(').next().unwrap_or("").trim().to_uppercase();
+                let is_aggregate = expression.contains('(') && AGGREGATE_FUNCTIONS.contains(&function.as_str());
+                SelectItem { expression, alias, is_aggregate }
+            })
             .collect()
     }
     
+    // Byte positions of whitespace outside parentheses and string literals
+    fn top_level_whitespace(&self, text: &str) -> Vec<usize> {
+        let mut depth = 0usize;
+        let mut in_string = false;
+        let mut positions = Vec::new();
+        
+        for (pos, c) in text.char_indices() {
+            match c {
+                '\'' => in_string = !in_string,
+                '(' if !in_string => depth += 1,
+                ')' if !in_string => depth = depth.saturating_sub(1),
+                c if c.is_whitespace() && depth == 0 && !in_string => positions.push(pos),
+                _ => {}
+            }
+        }
+        
+        positions
+    }
+    
+    fn split_alias(&self, item: &str) -> (String, Option<String>) {
+        let masked = self.mask_nested(&item.to_uppercase());
+        let is_identifier = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '"' || c == '`');
+        
+        // Explicit "expr AS alias" - sirf top level pe, CAST(x AS INT) ka AS nahi
+        if let Some(pos) = masked.rfind(" AS ") {
+            let alias = item[pos + 4..].trim();
+            if is_identifier(alias) {
+                return (item[..pos].trim().to_string(), Some(alias.trim_matches(|c| c == '"' || c == '`').to_string()));
+            }
+        }
+        
+        // Implicit "expr alias" - exactly do top-level tokens, pehla identifier ya ")" pe khatam
+        let item = item.trim();
+        let spaces = self.top_level_whitespace(item);
+        if let (Some(&first), Some(&last)) = (spaces.first(), spaces.last()) {
+            let expression = item[..first].trim_end();
+            let alias = item[last..].trim();
+            let single_gap = item[first..last].trim().is_empty();
+            if single_gap
+                && expression.ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == ')' || c == '"')
+                && is_identifier(alias)
+                && !JOIN_MODIFIERS.contains(&alias.to_uppercase().as_str()) {
+                return (expression.to_string(), Some(alias.trim_matches(|c| c == '"' || c == '`').to_string()));
+            }
+        }
+        
+        (item.trim().to_string(), None)
+    }
+    
+    #[allow(dead_code)]
     fn extract_tables(&self, clause: &str) -> Vec<String> {
         clause
             .split(',')
-            .map(|s| s.trim().split_whitespace().next().unwrap_or("").to_string())
+            .map(|s| s.split_whitespace().next().unwrap_or("").to_string())
             .filter(|s| !s.is_empty())
             .collect()
     }
     
-    fn find_clause_end(&self, text: &str) -> Option<usize> {
-        let keywords = ["WHERE", "ORDER BY", "GROUP BY", "HAVING", "LIMIT"];
-        let mut min_pos = None;
+    // Returns clause length - next clause keyword tak, warna text ke end tak
+    fn find_clause_end(&self, text_upper: &str) -> usize {
+        let keywords = ["WHERE", "ORDER BY", "GROUP BY", "HAVING", "LIMIT", "OFFSET", "FETCH"];
+        let mut min_pos = text_upper.len();
         
         for keyword in keywords {
-            if let Some(pos) = text[1..].find(keyword) {
-                let actual_pos = pos + 1;
-                min_pos = Some(min_pos.map_or(actual_pos, |m| m.min(actual_pos)));
+            if let Some(pos) = self.find_keyword(&text_upper[1..], keyword) {
+                min_pos = min_pos.min(pos + 1);
             }
         }
         
         min_pos
     }
     
+    // Whole-word keyword search so "FROM" doesn't match "from_date"
+    fn find_keyword(&self, text_upper: &str, keyword: &str) -> Option<usize> {
+        let bytes = text_upper.as_bytes();
+        let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+        
+        text_upper.match_indices(keyword).map(|(pos, _)| pos).find(|&pos| {
+            let before_ok = pos == 0 || !is_word(bytes[pos - 1]);
+            let end = pos + keyword.len();
+            let after_ok = end >= bytes.len() || !is_word(bytes[end]);
+            before_ok && after_ok
+        })
+    }
+    
     fn generate_fingerprint(&self, parsed: &ParsedQuery) -> String {
         let mut fingerprint = String::new();
         fingerprint.push_str(&format!("SELECT_{}", parsed.select_columns.len()));
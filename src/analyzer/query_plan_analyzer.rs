@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::analyzer::percent_improvement;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct QueryPlan {
     pub plan_id: String,
     pub query_text: String,
@@ -10,7 +12,7 @@ pub struct QueryPlan {
     pub optimization_suggestions: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PlanOperation {
     pub operation_type: String, // "Seq Scan", "Index Scan", "Hash Join", etc.
     pub table_name: String,
@@ -22,9 +24,16 @@ pub struct PlanOperation {
 
 pub struct QueryPlanAnalyzer {
     cost_threshold: f64,
+    #[allow(dead_code)] // execution time based suggestions abhi nahi hai
     time_threshold: u64,
 }
 
+impl Default for QueryPlanAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl QueryPlanAnalyzer {
     pub fn new() -> Self {
         Self {
@@ -90,8 +99,8 @@ impl QueryPlanAnalyzer {
     }
     
     pub fn compare_plans(&self, plan1: &QueryPlan, plan2: &QueryPlan) -> (f64, String) {
-        let cost_improvement = ((plan1.cost_estimate - plan2.cost_estimate) / plan1.cost_estimate) * 100.0;
-        let time_improvement = ((plan1.execution_time - plan2.execution_time) as f64 / plan1.execution_time as f64) * 100.0;
+        let cost_improvement = percent_improvement(plan1.cost_estimate, plan2.cost_estimate);
+        let time_improvement = percent_improvement(plan1.execution_time as f64, plan2.execution_time as f64);
         
         let recommendation = if cost_improvement > 50.0 && time_improvement > 30.0 {
             "Excellent improvement - implement this plan".to_string()
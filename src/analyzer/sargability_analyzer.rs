@@ -0,0 +1,256 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use crate::analyzer::{FilterCondition, FilterValue, ParsedQuery, SqlDialect, TableSchema};
+
+/// WHERE predicate jo column ko function / expression mein wrap karta hai - plain index use nahi ho sakta
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NonSargablePredicate {
+    pub predicate: String,
+    pub column: String,
+    pub wrapper: String, // "LOWER", "DATE", "arithmetic", ...
+    pub recommendation: String,
+    pub functional_index_sql: Option<String>,
+}
+
+/// WHERE predicate jiska literal column type se match nahi karta - implicit cast index ko bekaar kar
+/// sakta hai (engine-dependent: MySQL varchar = 123 har row cast karta hai)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TypeMismatch {
+    pub predicate: String,
+    pub column: String,
+    pub column_type: String,
+    pub literal_type: String, // "text", "numeric", "boolean"
+    pub recommendation: String,
+    pub matching_index_sql: Option<String>, // sirf jab engine column ko cast karta hai - literal cast hota hai to index already use hota hai
+}
+
+// Ye keywords "(" ke pehle aate hai lekin column wrap nahi karte
+const NON_FUNCTION_WORDS: &[&str] = &["EXISTS", "NOT", "IN", "ANY", "ALL", "AND", "OR", "SELECT", "VALUES"];
+
+const COMPARISON_OPERATORS: &[&str] = &[">=", "<=", "<>", "!=", "=", ">", "<", " LIKE ", " ILIKE ", " BETWEEN ", " IN "];
+
+/// Detects function-wrapped / computed columns in WHERE clauses - ye non-sargable predicates dhoondhta hai
+pub struct SargabilityAnalyzer;
+
+impl SargabilityAnalyzer {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn get_non_sargable_predicates(&self, parsed: &ParsedQuery) -> Vec<String> {
+        self.analyze(parsed).into_iter().map(|p| p.predicate).collect()
+    }
+
+    pub fn analyze(&self, parsed: &ParsedQuery) -> Vec<NonSargablePredicate> {
+        parsed
+            .where_clauses
+            .iter()
+            .filter_map(|clause| self.check_predicate(parsed, clause))
+            .collect()
+    }
+
+    fn check_predicate(&self, parsed: &ParsedQuery, clause: &str) -> Option<NonSargablePredicate> {
+        let predicate = clause.trim();
+        let upper = predicate.to_uppercase();
+        let (position, operator) = COMPARISON_OPERATORS
+            .iter()
+            .filter_map(|op| upper.find(op).map(|pos| (pos, *op)))
+            .min_by_key(|(pos, op)| (*pos, std::cmp::Reverse(op.len())))?;
+
+        let left = predicate[..position].trim().trim_start_matches('(');
+        let right = predicate[position + operator.len()..].trim();
+
+        // Column kisi bhi side ho sakta hai: 'x' = LOWER(email)
+        let (wrapper, column, expression) = Self::wrapped_column(left).or_else(|| Self::wrapped_column(right))?;
+        let (recommendation, functional_index_sql) = self.recommend(parsed, &wrapper, &column, &expression);
+
+        Some(NonSargablePredicate {
+            predicate: predicate.to_string(),
+            column,
+            wrapper,
+            recommendation,
+            functional_index_sql,
+        })
+    }
+
+    // "LOWER(u.email)" -> ("LOWER", "u.email", "LOWER(u.email)"); "price * 2" -> ("arithmetic", "price", ..)
+    fn wrapped_column(side: &str) -> Option<(String, String, String)> {
+        let side = side.trim();
+        if let Some(open) = side.find('(') {
+            let function = side[..open].trim();
+            let is_function = !function.is_empty()
+                && function.chars().all(|c| c.is_alphanumeric() || c == '_')
+                && !NON_FUNCTION_WORDS.contains(&function.to_uppercase().as_str());
+            let close = side.rfind(')')?;
+            if !is_function || close < open {
+                return None;
+            }
+
+            // Pehla argument jo identifier ho wahi column hai - EXTRACT(YEAR FROM col) bhi cover
+            let arguments = &side[open + 1..close];
+            let column = arguments
+                .split(|c: char| c == ',' || c.is_whitespace() || c == '(' || c == ')')
+                .map(str::trim)
+                .filter(|token| Self::is_column(token))
+                .find(|token| !matches!(token.to_uppercase().as_str(), "YEAR" | "MONTH" | "DAY" | "HOUR" | "FROM" | "EPOCH"))?;
+            return Some((function.to_uppercase(), column.to_string(), side.to_string()));
+        }
+
+        // price * 1.1 > 100 - computed column bhi index skip karta hai
+        let arithmetic = side.find(|c: char| "+-*/".contains(c))?;
+        let column = side[..arithmetic].trim();
+        (Self::is_column(column) && !side.starts_with('\'')).then(|| ("arithmetic".to_string(), column.to_string(), side.to_string()))
+    }
+
+    /// Schema ke column types ke against "column <op> literal" predicates check karta hai -
+    /// user_id = '123' (integer column, text literal) jaise implicit casts flag hote hai. Cast index
+    /// sirf tab suggest hota hai jab dialect column side ko cast karta hai
+    pub fn get_type_mismatch_predicates(&self, parsed: &ParsedQuery, schema: &TableSchema, dialect: SqlDialect) -> Vec<TypeMismatch> {
+        parsed
+            .where_clauses
+            .iter()
+            .filter_map(|clause| self.check_type_mismatch(parsed, schema, clause, dialect))
+            .collect()
+    }
+
+    fn check_type_mismatch(&self, parsed: &ParsedQuery, schema: &TableSchema, clause: &str, dialect: SqlDialect) -> Option<TypeMismatch> {
+        let condition = FilterCondition::parse(clause.trim())?;
+        let resolved = parsed.resolve_column(&condition.column);
+        let column_name = match resolved.split_once('.') {
+            Some((table, column_name)) if table == schema.table_name => column_name,
+            Some(_) => return None,
+            None if parsed.from_tables.contains(&schema.table_name) => resolved.as_str(),
+            None => return None,
+        };
+        let column = schema.columns.iter().find(|c| c.name.eq_ignore_ascii_case(column_name))?;
+
+        let column_family = Self::type_family(&column.data_type)?;
+        let (literal_family, fixed_literal) = match &condition.value {
+            FilterValue::Text(text) => ("text", text.parse::<f64>().is_ok().then(|| text.clone())),
+            FilterValue::Number(number) => ("numeric", Some(format!("'{}'", number))),
+            FilterValue::Boolean(_) => ("boolean", None),
+            FilterValue::Null => return None,
+        };
+        // MySQL BOOLEAN = TINYINT(1) - flag = 1 normal hai
+        if column_family == literal_family || (column_family == "boolean" && literal_family == "numeric") {
+            return None;
+        }
+
+        let rewrite = match fixed_literal.filter(|_| column_family != "boolean") {
+            Some(literal) => format!(
+                "Rewrite as {} {} {} so the literal matches the {} column",
+                condition.column, condition.operator, literal, column.data_type
+            ),
+            None => format!("Compare {} with a {} value instead of a {} literal", condition.column, column_family, literal_family),
+        };
+        let (recommendation, matching_index_sql) = match Self::column_cast_type(dialect, column_family, literal_family) {
+            Some(cast_type) => (
+                format!(
+                    "{} - the engine casts every {} value, so its index is skipped; if the query can't change, index CAST({} AS {})",
+                    rewrite, column.name, column.name, cast_type
+                ),
+                Some(format!(
+                    "CREATE INDEX idx_{}_{}_{} ON {} ((CAST({} AS {})))",
+                    schema.table_name,
+                    column.name,
+                    literal_family,
+                    schema.table_name,
+                    column.name,
+                    cast_type
+                )),
+            ),
+            // Literal hi column type mein convert hota hai (ya query error deti hai) - cast index se kuch nahi milta
+            None => (rewrite, None),
+        };
+
+        Some(TypeMismatch {
+            predicate: clause.trim().to_string(),
+            column: column.name.clone(),
+            column_type: column.data_type.clone(),
+            literal_type: literal_family.to_string(),
+            recommendation,
+            matching_index_sql,
+        })
+    }
+
+    // Engine column ko cast karta hai to kis type mein - MySQL string vs number dono ko number banata hai,
+    // isliye varchar_col = 123 har row cast karta hai. int_col = '123' mein sirf literal convert hota hai.
+    // Postgres varchar = integer pe error deta hai, SQLite column affinity literal pe lagata hai
+    fn column_cast_type(dialect: SqlDialect, column_family: &str, literal_family: &str) -> Option<&'static str> {
+        match (dialect, column_family, literal_family) {
+            // Plain DECIMAL = DECIMAL(10,0), fractions kat jate
+            (SqlDialect::MySql, "text", "numeric") => Some("DECIMAL(65,30)"),
+            _ => None,
+        }
+    }
+
+    // Column data type ka comparison family - dates / json / unknown types None (skip)
+    fn type_family(data_type: &str) -> Option<&'static str> {
+        let base = data_type.to_uppercase();
+        let base = base.split(['(', ' ']).next().unwrap_or("");
+        match base {
+            "INT" | "INTEGER" | "BIGINT" | "SMALLINT" | "TINYINT" | "MEDIUMINT" | "SERIAL" | "BIGSERIAL"
+            | "SMALLSERIAL" | "DECIMAL" | "NUMERIC" | "REAL" | "FLOAT" | "DOUBLE" | "INT2" | "INT4" | "INT8" => Some("numeric"),
+            "CHAR" | "VARCHAR" | "TEXT" | "CHARACTER" | "NVARCHAR" | "NCHAR" | "CITEXT" | "TINYTEXT"
+            | "MEDIUMTEXT" | "LONGTEXT" => Some("text"),
+            "BOOL" | "BOOLEAN" => Some("boolean"),
+            _ => None,
+        }
+    }
+
+    fn is_column(token: &str) -> bool {
+        !token.is_empty()
+            && !token.starts_with(|c: char| c.is_ascii_digit())
+            && token.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+            && !matches!(token.to_uppercase().as_str(), "NULL" | "TRUE" | "FALSE")
+    }
+
+    fn recommend(&self, parsed: &ParsedQuery, wrapper: &str, column: &str, expression: &str) -> (String, Option<String>) {
+        let resolved = parsed.resolve_column(column);
+        let (table, column_name) = match resolved.split_once('.') {
+            Some((table, column_name)) => (Some(table.to_string()), column_name.to_string()),
+            None => (parsed.from_tables.first().cloned(), resolved.clone()),
+        };
+        // Expression ke andar bhi alias hatana padega, index base table pe banta hai
+        let index_expression = expression.replace(column, &column_name);
+        let functional_index_sql = table.as_ref().map(|table| {
+            format!(
+                "CREATE INDEX idx_{}_{}_{} ON {} (({}))",
+                table,
+                wrapper.to_lowercase(),
+                column_name,
+                table,
+                index_expression
+            )
+        });
+
+        let recommendation = match wrapper {
+            "DATE" | "YEAR" | "MONTH" | "EXTRACT" | "DATE_TRUNC" | "TO_CHAR" => format!(
+                "Rewrite as a range on {} (e.g. {} >= start AND {} < end) so a plain index can be used",
+                column_name, column_name, column_name
+            ),
+            "LOWER" | "UPPER" => format!(
+                "Add a functional index on {} or store {} in a case-insensitive type (citext / _ci collation)",
+                index_expression, column_name
+            ),
+            "COALESCE" | "IFNULL" | "ISNULL" | "NVL" => format!(
+                "Rewrite as ({} = value OR {} IS NULL) instead of wrapping the column",
+                column_name, column_name
+            ),
+            "arithmetic" => format!(
+                "Move the arithmetic to the constant side so {} stays bare in the predicate",
+                column_name
+            ),
+            _ => format!("Add a functional index on {} or rewrite the predicate to compare {} directly", index_expression, column_name),
+        };
+
+        (recommendation, functional_index_sql)
+    }
+}
+
+impl Default for SargabilityAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,16 +1,29 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use std::collections::HashMap;
+use super::ColumnStats;
+use crate::recommender::ReasonCode;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TableSchema {
     pub table_name: String,
     pub columns: Vec<ColumnInfo>,
     pub indexes: Vec<String>,
     pub row_count: u64,
     pub avg_row_size: f64,
+    #[serde(default)]
+    pub foreign_keys: Vec<ForeignKey>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// Declared foreign key - table.column -> referenced_table.referenced_column
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ForeignKey {
+    pub column: String,
+    pub referenced_table: String,
+    pub referenced_column: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ColumnInfo {
     pub name: String,
     pub data_type: String,
@@ -20,23 +33,50 @@ pub struct ColumnInfo {
     pub usage_frequency: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SchemaOptimization {
     pub table_name: String,
     pub optimization_type: String,
+    #[serde(default)]
+    pub reason_code: ReasonCode,
     pub description: String,
     pub estimated_benefit: f64,
     pub sql_statement: String,
     pub priority: u32,
+    #[serde(default)]
+    pub rollback_statement: Option<String>, // sql_statement ka inverse; None = advisory ya irreversible
+}
+
+// Target database engine - type swaps engine ke hisaab se alag hote hai
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+pub enum SqlDialect {
+    #[default]
+    Postgres,
+    MySql,
+    Sqlite,
 }
 
 pub struct SchemaOptimizer {
     data_type_sizes: HashMap<String, u64>,
+    dialect: SqlDialect,
+}
+
+impl Default for SchemaOptimizer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SchemaOptimizer {
+    /// Postgres ke liye suggestions - dusre engine ke liye with_dialect use karo
     pub fn new() -> Self {
+        Self::with_dialect(SqlDialect::default())
+    }
+    
+    pub fn with_dialect(dialect: SqlDialect) -> Self {
         let mut sizes = HashMap::new();
+        sizes.insert("TINYINT".to_string(), 1);
+        sizes.insert("SMALLINT".to_string(), 2);
         sizes.insert("INT".to_string(), 4);
         sizes.insert("BIGINT".to_string(), 8);
         sizes.insert("VARCHAR".to_string(), 1);
@@ -47,34 +87,50 @@ impl SchemaOptimizer {
         
         Self {
             data_type_sizes: sizes,
+            dialect,
         }
     }
     
     //yaha pe badme advanced schema analysis bhi add karna ha
     pub fn analyze_schema(&self, schemas: &[TableSchema]) -> Vec<SchemaOptimization> {
+        self.analyze_schema_with_stats(schemas, &HashMap::new())
+    }
+    
+    // column_stats key = "table.column"
+    pub fn analyze_schema_with_stats(&self, 
+        schemas: &[TableSchema], 
+        column_stats: &HashMap<String, ColumnStats>) -> Vec<SchemaOptimization> {
+        
         let mut optimizations = Vec::new();
         
         for schema in schemas {
             // Analyze data types
-            optimizations.extend(self.analyze_data_types(schema));
+            optimizations.extend(self.analyze_data_types(schema, column_stats));
+            
+            // Analyze nullability against observed statistics
+            optimizations.extend(self.analyze_nullability(schema, column_stats));
             
             // Analyze table structure
             optimizations.extend(self.analyze_table_structure(schema));
             
             // Analyze normalization opportunities
             optimizations.extend(self.analyze_normalization(schema));
+            
+            // Analyze declared and inferred foreign keys
+            optimizations.extend(self.analyze_foreign_keys(schema, schemas));
         }
         
         // Sort by priority
-        optimizations.sort_by(|a, b| b.priority.cmp(&a.priority));
+        optimizations.sort_by_key(|opt| std::cmp::Reverse(opt.priority));
         optimizations
     }
     
-    fn analyze_data_types(&self, schema: &TableSchema) -> Vec<SchemaOptimization> {
+    fn analyze_data_types(&self, schema: &TableSchema, column_stats: &HashMap<String, ColumnStats>) -> Vec<SchemaOptimization> {
         let mut optimizations = Vec::new();
         
         for column in &schema.columns {
-            if let Some(optimization) = self.suggest_data_type_optimization(column) {
+            let stats = column_stats.get(&format!("{}.{}", schema.table_name, column.name));
+            if let Some(optimization) = self.suggest_data_type_optimization(&schema.table_name, column, stats) {
                 optimizations.push(optimization);
             }
         }
@@ -82,31 +138,76 @@ impl SchemaOptimizer {
         optimizations
     }
     
-    fn suggest_data_type_optimization(&self, column: &ColumnInfo) -> Option<SchemaOptimization> {
-        let current_size = self.estimate_column_size(column);
-        let suggested_type = self.get_optimal_data_type(column);
-        let suggested_size = self.estimate_type_size(&suggested_type);
+    fn suggest_data_type_optimization(&self, 
+        table_name: &str, 
+        column: &ColumnInfo, 
+        stats: Option<&ColumnStats>) -> Option<SchemaOptimization> {
         
-        if suggested_size < current_size {
-            let savings = current_size - suggested_size;
-            let benefit = (savings / current_size) * 100.0;
-            
-            Some(SchemaOptimization {
-                table_name: "".to_string(), // Will be set by caller
-                optimization_type: "Data Type Optimization".to_string(),
-                description: format!(
-                    "Change {} from {} to {} - save {} bytes per row",
-                    column.name, column.data_type, suggested_type, savings
-                ),
-                estimated_benefit: benefit,
-                sql_statement: format!(
-                    "ALTER TABLE {} ALTER COLUMN {} TYPE {}",
-                    "", column.name, suggested_type
-                ),
-                priority: if benefit > 50.0 { 100 } else if benefit > 20.0 { 80 } else { 60 },
-            })
-        } else {
-            None
+        let (suggested_type, current_size, suggested_size) = self.get_optimal_data_type(column, stats)?;
+        
+        // Never suggest a migration that grows the column
+        if suggested_size >= current_size {
+            return None;
+        }
+        
+        let savings = current_size - suggested_size;
+        let benefit = (savings as f64 / current_size as f64) * 100.0;
+        // MODIFY COLUMN poori definition replace karta hai - NOT NULL dobara na likha to column nullable ho jata hai
+        let mysql_null = if column.is_nullable { "NULL" } else { "NOT NULL" };
+        let sql_statement = match self.dialect {
+            SqlDialect::MySql => format!(
+                "ALTER TABLE {} MODIFY COLUMN {} {} {}",
+                table_name, column.name, suggested_type, mysql_null
+            ),
+            // Postgres can't cast integer -> boolean implicitly
+            _ if suggested_type == "BOOLEAN" => format!(
+                "ALTER TABLE {} ALTER COLUMN {} TYPE BOOLEAN USING {} <> 0",
+                table_name, column.name, column.name
+            ),
+            _ => format!("ALTER TABLE {} ALTER COLUMN {} TYPE {}", table_name, column.name, suggested_type),
+        };
+        // Original type wapas - narrowing out-of-range values pe fail hoti hai (Postgres / strict MySQL) to values widening
+        // mein exact wapas aati hai. BOOLEAN conversion lossy hai (non-zero sab true), uska rollback nahi
+        let rollback_statement = match self.dialect {
+            _ if suggested_type == "BOOLEAN" => None,
+            SqlDialect::MySql => Some(format!(
+                "ALTER TABLE {} MODIFY COLUMN {} {} {}",
+                table_name, column.name, column.data_type, mysql_null
+            )),
+            _ => Some(format!("ALTER TABLE {} ALTER COLUMN {} TYPE {}", table_name, column.name, column.data_type)),
+        };
+        
+        Some(SchemaOptimization {
+            table_name: table_name.to_string(),
+            optimization_type: "Data Type Optimization".to_string(),
+            reason_code: ReasonCode::OversizedDataType,
+            description: format!(
+                "Change {} from {} to {} - save {} bytes per row",
+                column.name, column.data_type, suggested_type, savings
+            ),
+            estimated_benefit: benefit,
+            sql_statement,
+            priority: if benefit > 50.0 { 100 } else if benefit > 20.0 { 80 } else { 60 },
+            rollback_statement,
+        })
+    }
+    
+    // Per-row byte width of a column - index maintenance cost bhi isi se scale hota hai
+    pub fn column_width(&self, column: &ColumnInfo) -> u64 {
+        if column.max_length.is_some() {
+            return self.estimate_column_size(column);
+        }
+        
+        // "VARCHAR(255)" / "TEXT(4000)" style declarations carry their own width
+        let declared = column.data_type
+            .split_once('(')
+            .and_then(|(_, rest)| rest.split([',', ')']).next())
+            .and_then(|len| len.trim().parse::<u64>().ok());
+        let base_type = column.data_type.split('(').next().unwrap_or("").trim();
+        
+        match declared {
+            Some(len) if matches!(base_type.to_uppercase().as_str(), "VARCHAR" | "CHAR" | "TEXT") => len,
+            _ => self.estimate_type_size(base_type),
         }
     }
     
@@ -122,14 +223,130 @@ impl SchemaOptimizer {
         self.data_type_sizes.get(&data_type.to_uppercase()).copied().unwrap_or(8)
     }
     
-    fn get_optimal_data_type(&self, column: &ColumnInfo) -> String {
-        match column.data_type.to_uppercase().as_str() {
-            "VARCHAR" if column.max_length.unwrap_or(0) <= 255 => "TINYTEXT".to_string(),
-            "INT" if column.usage_frequency < 1000 => "SMALLINT".to_string(),
-            "BIGINT" if column.usage_frequency < 10000 => "INT".to_string(),
-            "TEXT" if column.max_length.unwrap_or(0) <= 65535 => "VARCHAR(65535)".to_string(),
-            _ => column.data_type.clone(),
+    // (suggested type, current bytes, suggested bytes) - max_length = observed max length
+    fn get_optimal_data_type(&self, column: &ColumnInfo, stats: Option<&ColumnStats>) -> Option<(String, u64, u64)> {
+        // SQLite type affinity ignores declared sizes - swaps se storage nahi bachta
+        if self.dialect == SqlDialect::Sqlite {
+            return None;
+        }
+        
+        let data_type = column.data_type.to_uppercase();
+        let base_type = data_type.split('(').next().unwrap_or("").trim();
+        let declared_length = data_type
+            .split_once('(')
+            .and_then(|(_, rest)| rest.trim_end_matches(')').trim().parse::<u64>().ok());
+        
+        // Sirf 0/1 wala integer column hi flag hai - {1, 2} status ko BOOLEAN banaya to dono true ho jayenge
+        let is_flag = stats
+            .map(|s| {
+                s.total_rows > 0
+                    && !s.observed_values.is_empty()
+                    && s.observed_values.iter().all(|value| matches!(value.trim(), "0" | "1"))
+            })
+            .unwrap_or(false);
+        
+        match (base_type, declared_length, column.max_length) {
+            ("INT" | "BIGINT" | "SMALLINT", _, _) if is_flag && !column.is_primary_key => {
+                Some(("BOOLEAN".to_string(), self.estimate_type_size(base_type), self.estimate_type_size("BOOLEAN")))
+            }
+            // Shrink VARCHAR(n) to the observed max length
+            ("VARCHAR", Some(declared), Some(observed)) if observed > 0 && observed < declared => {
+                Some((format!("VARCHAR({})", observed), declared, observed))
+            }
+            ("INT", _, _) if column.usage_frequency < 1000 => {
+                Some(("SMALLINT".to_string(), self.estimate_type_size("INT"), self.estimate_type_size("SMALLINT")))
+            }
+            ("BIGINT", _, _) if column.usage_frequency < 10000 => {
+                Some(("INT".to_string(), self.estimate_type_size("BIGINT"), self.estimate_type_size("INT")))
+            }
+            // MySQL stores TEXT off-page; short values fit inline as VARCHAR. Postgres TEXT = VARCHAR, no gain
+            ("TEXT", _, Some(observed)) if self.dialect == SqlDialect::MySql && observed > 0 && observed <= 255 => {
+                Some((format!("VARCHAR({})", observed), observed + self.estimate_type_size("TEXT"), observed))
+            }
+            _ => None,
+        }
+    }
+    
+    // Nullable columns that are never NULL -> SET NOT NULL; NOT NULL columns full of placeholders -> DROP NOT NULL
+    fn analyze_nullability(&self, schema: &TableSchema, column_stats: &HashMap<String, ColumnStats>) -> Vec<SchemaOptimization> {
+        let mut optimizations = Vec::new();
+        
+        for column in &schema.columns {
+            let Some(stats) = column_stats.get(&format!("{}.{}", schema.table_name, column.name)) else {
+                continue;
+            };
+            if stats.total_rows == 0 || column.is_primary_key {
+                continue;
+            }
+            
+            if column.is_nullable && stats.null_fraction <= 0.0 {
+                optimizations.push(SchemaOptimization {
+                    table_name: schema.table_name.clone(),
+                    optimization_type: "Nullability".to_string(),
+                    reason_code: ReasonCode::Nullability,
+                    description: format!(
+                        "Column {} is nullable but always populated across {} rows - declare NOT NULL",
+                        column.name, stats.total_rows
+                    ),
+                    estimated_benefit: 10.0,
+                    sql_statement: match self.dialect {
+                        SqlDialect::MySql => format!(
+                            "ALTER TABLE {} MODIFY COLUMN {} {} NOT NULL",
+                            schema.table_name, column.name, column.data_type
+                        ),
+                        _ => format!(
+                            "ALTER TABLE {} ALTER COLUMN {} SET NOT NULL",
+                            schema.table_name, column.name
+                        ),
+                    },
+                    priority: 50,
+                    rollback_statement: Some(match self.dialect {
+                        SqlDialect::MySql => format!(
+                            "ALTER TABLE {} MODIFY COLUMN {} {} NULL",
+                            schema.table_name, column.name, column.data_type
+                        ),
+                        _ => format!(
+                            "ALTER TABLE {} ALTER COLUMN {} DROP NOT NULL",
+                            schema.table_name, column.name
+                        ),
+                    }),
+                });
+            } else if !column.is_nullable && stats.sentinel_fraction > 0.2 {
+                optimizations.push(SchemaOptimization {
+                    table_name: schema.table_name.clone(),
+                    optimization_type: "Nullability".to_string(),
+                    reason_code: ReasonCode::Nullability,
+                    description: format!(
+                        "Column {} is NOT NULL but {:.0}% of rows hold placeholder values - allow NULL and store NULL instead",
+                        column.name, stats.sentinel_fraction * 100.0
+                    ),
+                    estimated_benefit: 15.0,
+                    sql_statement: match self.dialect {
+                        SqlDialect::MySql => format!(
+                            "ALTER TABLE {} MODIFY COLUMN {} {} NULL",
+                            schema.table_name, column.name, column.data_type
+                        ),
+                        _ => format!(
+                            "ALTER TABLE {} ALTER COLUMN {} DROP NOT NULL",
+                            schema.table_name, column.name
+                        ),
+                    },
+                    priority: 55,
+                    rollback_statement: Some(match self.dialect {
+                        SqlDialect::MySql => format!(
+                            "ALTER TABLE {} MODIFY COLUMN {} {} NOT NULL",
+                            schema.table_name, column.name, column.data_type
+                        ),
+                        _ => format!(
+                            "ALTER TABLE {} ALTER COLUMN {} SET NOT NULL",
+                            schema.table_name, column.name
+                        ),
+                    }),
+                });
+            }
         }
+        
+        optimizations
     }
     
     fn analyze_table_structure(&self, schema: &TableSchema) -> Vec<SchemaOptimization> {
@@ -141,10 +358,12 @@ impl SchemaOptimizer {
             optimizations.push(SchemaOptimization {
                 table_name: schema.table_name.clone(),
                 optimization_type: "Primary Key".to_string(),
+                reason_code: ReasonCode::SchemaDesign,
                 description: "Add primary key for better performance".to_string(),
                 estimated_benefit: 30.0,
                 sql_statement: format!("ALTER TABLE {} ADD COLUMN id SERIAL PRIMARY KEY", schema.table_name),
                 priority: 90,
+                rollback_statement: Some(format!("ALTER TABLE {} DROP COLUMN id", schema.table_name)),
             });
         }
         
@@ -153,10 +372,12 @@ impl SchemaOptimizer {
             optimizations.push(SchemaOptimization {
                 table_name: schema.table_name.clone(),
                 optimization_type: "Table Partitioning".to_string(),
+                reason_code: ReasonCode::SchemaDesign,
                 description: "Consider partitioning large table".to_string(),
                 estimated_benefit: 40.0,
                 sql_statement: format!("-- Partition {} by date or range", schema.table_name),
                 priority: 85,
+                rollback_statement: None,
             });
         }
         
@@ -175,17 +396,159 @@ impl SchemaOptimizer {
             optimizations.push(SchemaOptimization {
                 table_name: schema.table_name.clone(),
                 optimization_type: "Normalization".to_string(),
+                reason_code: ReasonCode::SchemaDesign,
                 description: "Consider normalizing table with many text columns".to_string(),
                 estimated_benefit: 25.0,
                 sql_statement: format!("-- Normalize {} table structure", schema.table_name),
                 priority: 70,
+                rollback_statement: None,
+            });
+        }
+        
+        optimizations.extend(self.find_repeating_groups(schema));
+        
+        optimizations
+    }
+    
+    // phone1, phone2, phone3 jaise columns - child table banana chahiye
+    fn find_repeating_groups(&self, schema: &TableSchema) -> Vec<SchemaOptimization> {
+        let mut groups: Vec<(String, Vec<&ColumnInfo>)> = Vec::new();
+        
+        for column in &schema.columns {
+            let stem = column.name.trim_end_matches(|c: char| c.is_ascii_digit()).trim_end_matches('_');
+            if stem.len() == column.name.len() || stem.is_empty() {
+                continue;
+            }
+            match groups.iter_mut().find(|(s, _)| s == stem) {
+                Some((_, columns)) => columns.push(column),
+                None => groups.push((stem.to_string(), vec![column])),
+            }
+        }
+        
+        let primary_key = schema.columns.iter().find(|c| c.is_primary_key);
+        let (pk_name, pk_type) = primary_key
+            .map(|c| (c.name.as_str(), c.data_type.as_str()))
+            .unwrap_or(("id", "BIGINT"));
+        
+        groups
+            .into_iter()
+            .filter(|(_, columns)| columns.len() >= 2)
+            .map(|(stem, columns)| {
+                let child_table = format!("{}_{}s", schema.table_name, stem);
+                let names: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+                SchemaOptimization {
+                    table_name: schema.table_name.clone(),
+                    optimization_type: "Repeating Group".to_string(),
+                    reason_code: ReasonCode::SchemaDesign,
+                    description: format!(
+                        "Columns {} repeat the same attribute - move them to child table {}",
+                        names.join(", "), child_table
+                    ),
+                    estimated_benefit: 25.0 + columns.len() as f64 * 5.0,
+                    sql_statement: format!(
+                        "CREATE TABLE {} (id BIGSERIAL PRIMARY KEY, {}_{} {} NOT NULL REFERENCES {}({}), {} {})",
+                        child_table, schema.table_name, pk_name, pk_type,
+                        schema.table_name, pk_name, stem, columns[0].data_type
+                    ),
+                    priority: 75,
+                    rollback_statement: Some(format!("DROP TABLE {}", child_table)),
+                }
+            })
+            .collect()
+    }
+    
+    // (a) declared FKs without index, (b) "*_id" columns that look like FKs but have no index
+    fn analyze_foreign_keys(&self, schema: &TableSchema, schemas: &[TableSchema]) -> Vec<SchemaOptimization> {
+        let mut optimizations = Vec::new();
+        
+        for foreign_key in &schema.foreign_keys {
+            if !self.is_column_indexed(schema, &foreign_key.column) {
+                optimizations.push(SchemaOptimization {
+                    table_name: schema.table_name.clone(),
+                    optimization_type: "Foreign Key Index".to_string(),
+                    reason_code: ReasonCode::UnindexedForeignKey,
+                    description: format!(
+                        "Foreign key {}.{} -> {}.{} has no index - joins and cascading deletes scan the table",
+                        schema.table_name, foreign_key.column,
+                        foreign_key.referenced_table, foreign_key.referenced_column
+                    ),
+                    estimated_benefit: 50.0,
+                    sql_statement: format!(
+                        "CREATE INDEX idx_{}_{} ON {} ({})",
+                        schema.table_name, foreign_key.column, schema.table_name, foreign_key.column
+                    ),
+                    priority: 95,
+                    rollback_statement: Some(crate::sql::drop_index_sql(
+                        &format!("idx_{}_{}", schema.table_name, foreign_key.column),
+                        &schema.table_name,
+                        self.dialect,
+                    )),
+                });
+            }
+        }
+        
+        for column in &schema.columns {
+            let Some(prefix) = column.name.strip_suffix("_id") else {
+                continue;
+            };
+            let declared = schema.foreign_keys.iter().any(|fk| fk.column == column.name);
+            if prefix.is_empty() || column.is_primary_key || declared || self.is_column_indexed(schema, &column.name) {
+                continue;
+            }
+            
+            let referenced = schemas.iter()
+                .map(|s| s.table_name.as_str())
+                .find(|t| *t == prefix || *t == format!("{}s", prefix) || *t == format!("{}es", prefix));
+            let description = match referenced {
+                Some(table) => format!(
+                    "Column {} looks like a foreign key to {} but has no index",
+                    column.name, table
+                ),
+                None => format!("Column {} looks like a foreign key but has no index", column.name),
+            };
+            
+            optimizations.push(SchemaOptimization {
+                table_name: schema.table_name.clone(),
+                optimization_type: "Inferred Foreign Key Index".to_string(),
+                reason_code: ReasonCode::UnindexedForeignKey,
+                description,
+                estimated_benefit: 35.0,
+                sql_statement: format!(
+                    "CREATE INDEX idx_{}_{} ON {} ({})",
+                    schema.table_name, column.name, schema.table_name, column.name
+                ),
+                priority: if referenced.is_some() { 85 } else { 65 },
+                rollback_statement: Some(crate::sql::drop_index_sql(
+                    &format!("idx_{}_{}", schema.table_name, column.name),
+                    &schema.table_name,
+                    self.dialect,
+                )),
             });
         }
         
         optimizations
     }
     
-    //yaha pe badme foreign key analysis bhi add karna ha
+    // indexes entries "idx_name", "(col, col)" ya "col, col" ho sakti hai - leading column match hona chahiye
+    fn is_column_indexed(&self, schema: &TableSchema, column: &str) -> bool {
+        if schema.columns.iter().any(|c| c.is_primary_key && c.name == column) {
+            return true;
+        }
+        
+        schema.indexes.iter().any(|index| {
+            let column_list = match (index.find('('), index.rfind(')')) {
+                (Some(start), Some(end)) if start < end => &index[start + 1..end],
+                _ => index.as_str(),
+            };
+            let leading = column_list.split(',').next().unwrap_or("").trim();
+            leading.eq_ignore_ascii_case(column)
+                || index.ends_with(&format!("_{}", column))
+                || index.contains(&format!("_{}_", column))
+        })
+    }
+    
+    
+    //yaha pe badme foreign key health scoring bhi add karna ha
     pub fn get_schema_health_score(&self, schemas: &[TableSchema]) -> f64 {
         let mut total_score = 0.0;
         let mut table_count = 0;
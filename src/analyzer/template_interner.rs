@@ -0,0 +1,57 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use super::QueryFingerprinter;
+
+/// Normalized query templates ka interning table - same template ek hi baar memory mein rehta hai,
+/// chahe lakhon parameterized logs us pe map ho
+#[derive(Debug, Clone, Default)]
+pub struct TemplateInterner {
+    templates: HashSet<Arc<str>>,
+}
+
+impl TemplateInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Existing Arc return karta hai agar template pehle se hai, warna naya insert karta hai
+    pub fn intern(&mut self, template: &str) -> Arc<str> {
+        if let Some(existing) = self.templates.get(template) {
+            return Arc::clone(existing);
+        }
+        let template: Arc<str> = Arc::from(template);
+        self.templates.insert(Arc::clone(&template));
+        template
+    }
+
+    /// Raw SQL ko normalize karke intern karta hai
+    pub fn intern_query(&mut self, query: &str) -> Arc<str> {
+        self.intern(&QueryFingerprinter::normalize_query(query))
+    }
+
+    /// Template ko release karta hai - bounded analyzers last user evict hone pe call karte hai
+    pub fn remove(&mut self, template: &str) -> bool {
+        self.templates.remove(template)
+    }
+
+    pub fn len(&self) -> usize {
+        self.templates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.templates.clear();
+    }
+}
+
+// FNV-1a 64 - DefaultHasher runs ke beech stable nahi hai, fingerprints persist karne hai
+pub(crate) fn stable_hash(text: &str) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    text.bytes().fold(OFFSET_BASIS, |hash, byte| (hash ^ byte as u64).wrapping_mul(PRIME))
+}
@@ -1,8 +1,9 @@
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 
 /// Time-based query patterns - ye struct time-based patterns store karta hai
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TimePattern {
     pub hour: u8,
     pub day_of_week: u8, // 0 = Sunday, 1 = Monday, etc.
@@ -17,6 +18,12 @@ pub struct TimeAnalyzer {
     daily_stats: HashMap<u8, (u64, f64)>,  // day -> (count, total_time)
 }
 
+impl Default for TimeAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TimeAnalyzer {
     pub fn new() -> Self {
         Self {
@@ -70,7 +77,7 @@ impl TimeAnalyzer {
             });
         }
         
-        patterns.sort_by(|a, b| b.query_count.cmp(&a.query_count));
+        patterns.sort_by_key(|pattern| (std::cmp::Reverse(pattern.query_count), pattern.hour));
         patterns
     }
     
@@ -91,7 +98,7 @@ impl TimeAnalyzer {
             });
         }
         
-        patterns.sort_by(|a, b| b.query_count.cmp(&a.query_count));
+        patterns.sort_by_key(|pattern| (std::cmp::Reverse(pattern.query_count), pattern.day_of_week));
         patterns
     }
     
@@ -110,11 +117,12 @@ impl TimeAnalyzer {
         }
         
         // Check for slow periods
-        let slow_hours: Vec<_> = self.hourly_stats
+        let mut slow_hours: Vec<_> = self.hourly_stats
             .iter()
             .filter(|(_, (_, total_time))| *total_time > 1000.0)
             .map(|(hour, _)| *hour)
             .collect();
+        slow_hours.sort();
             
         if !slow_hours.is_empty() {
             recommendations.push(format!(
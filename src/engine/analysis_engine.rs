@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use crate::analyzer::{
+    AnomalyDetector, AnomalyResult, ColumnTracker, ColumnUsage, CostCalculator, ExistingIndex,
+    ExistingIndexChecker, JoinAnalyzer, JoinChain, JoinPattern, PatternAnalyzer, QueryLog, QueryParser, QueryPattern,
+};
+use crate::engine::{AnalyzerPlugin, CustomFinding};
+use crate::recommender::{IndexRecommendation, IndexRecommender};
+
+/// Whole pipeline ka combined output - ek hi struct mein sab results
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AnalysisReport {
+    pub total_queries: usize,
+    pub patterns: Vec<QueryPattern>,
+    pub column_candidates: Vec<ColumnUsage>,
+    pub slow_joins: Vec<JoinPattern>,
+    pub expensive_chains: Vec<JoinChain>,
+    pub join_recommendations: Vec<String>,
+    pub anomalies: Vec<AnomalyResult>,
+    pub high_cost_queries: usize,
+    pub index_recommendations: Vec<IndexRecommendation>,
+    #[serde(default)]
+    pub custom_findings: Vec<CustomFinding>, // registered plugins ke findings, priority ke order mein
+}
+
+/// Single entry point - har log ko saare sub-analyzers tak pahunchata hai
+pub struct AnalysisEngine {
+    parser: QueryParser,
+    pattern_analyzer: PatternAnalyzer,
+    column_tracker: ColumnTracker,
+    join_analyzer: JoinAnalyzer,
+    cost_calculator: CostCalculator,
+    anomaly_detector: AnomalyDetector,
+    index_recommender: IndexRecommender,
+    existing_indexes: ExistingIndexChecker,
+    anomalies: Vec<AnomalyResult>,
+    high_cost_queries: usize, // per-log QueryCost rakhne se memory log size ke saath badhti
+    slow_join_threshold_ms: f64,
+    plugins: Vec<Box<dyn AnalyzerPlugin>>,
+}
+
+impl Default for AnalysisEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnalysisEngine {
+    pub fn new() -> Self {
+        Self::with_thresholds(1000.0, 10, 100.0)
+    }
+
+    /// slowness/frequency thresholds IndexRecommender ke liye, slow_join_threshold_ms JoinAnalyzer ke liye
+    pub fn with_thresholds(slowness_threshold: f64, frequency_threshold: u64, slow_join_threshold_ms: f64) -> Self {
+        Self {
+            parser: QueryParser,
+            pattern_analyzer: PatternAnalyzer::new(),
+            column_tracker: ColumnTracker::new(),
+            join_analyzer: JoinAnalyzer::new(),
+            cost_calculator: CostCalculator::new(),
+            anomaly_detector: AnomalyDetector::new(),
+            index_recommender: IndexRecommender::new(slowness_threshold, frequency_threshold),
+            existing_indexes: ExistingIndexChecker::new(),
+            anomalies: Vec::new(),
+            high_cost_queries: 0,
+            slow_join_threshold_ms,
+            plugins: Vec::new(),
+        }
+    }
+
+    /// Schema ka existing index - join recommendations aur join fan-out dono isse padhte hai
+    pub fn add_existing_index(&mut self, index: ExistingIndex) {
+        self.cost_calculator.add_existing_index(index.clone());
+        self.existing_indexes.add_existing_index(index);
+    }
+
+    /// Custom analyzer pipeline mein jodta hai - registration ke baad wale logs hi milte hai
+    pub fn register_plugin(&mut self, plugin: Box<dyn AnalyzerPlugin>) {
+        self.plugins.push(plugin);
+    }
+
+    pub fn ingest(&mut self, log: QueryLog) {
+        let parsed = self.parser.parse(&log.query);
+
+        self.column_tracker.track_usage(&parsed, log.execution_time_ms);
+        self.join_analyzer.analyze_join(&parsed, log.execution_time_ms);
+        let cost = self.cost_calculator
+            .calculate_from_parsed(&parsed, log.execution_time_ms, log.rows_scanned);
+        if cost.cost_category == "high" {
+            self.high_cost_queries += 1;
+        }
+
+        // Anomaly pehle check hoti hai, phir time history mein jata hai - warna spike apna hi baseline badha deta
+        let anomaly = self.anomaly_detector.detect_anomaly(log.execution_time_ms);
+        if anomaly.is_anomaly {
+            self.anomalies.push(anomaly);
+        }
+        self.anomaly_detector.add_execution_time(log.execution_time_ms);
+
+        for plugin in &mut self.plugins {
+            plugin.on_log(&log, &parsed);
+        }
+
+        self.pattern_analyzer.add_log(log);
+    }
+
+    pub fn ingest_all(&mut self, logs: Vec<QueryLog>) {
+        self.ingest_stream(logs);
+    }
+
+    /// Iterator se lazily ingest - e.g. QueryLog::stream_postgres_csvlog_file ke saath
+    pub fn ingest_stream(&mut self, logs: impl IntoIterator<Item = QueryLog>) {
+        for log in logs {
+            self.ingest(log);
+        }
+    }
+
+    //yaha pe badme partial index aur schema recommendations bhi add karna ha
+    pub fn report(&self) -> AnalysisReport {
+        let patterns = self.pattern_analyzer.analyze();
+
+        let slow_joins: Vec<JoinPattern> = self
+            .join_analyzer
+            .get_slow_joins(self.slow_join_threshold_ms)
+            .into_iter()
+            .cloned()
+            .collect();
+
+        let mut index_recommendations = self
+            .index_recommender
+            .recommend_with_usage(&self.pattern_analyzer.analyze_by_fingerprint(), &self.column_tracker);
+        index_recommendations.extend(self.index_recommender.recommend_for_joins(&slow_joins, &self.existing_indexes));
+        index_recommendations.sort_by_key(|rec| std::cmp::Reverse(rec.priority));
+
+        let mut custom_findings: Vec<CustomFinding> = self
+            .plugins
+            .iter()
+            .flat_map(|plugin| {
+                plugin.finish().into_iter().map(|mut finding| {
+                    finding.plugin = plugin.name().to_string();
+                    finding
+                })
+            })
+            .collect();
+        custom_findings.sort_by_key(|finding| std::cmp::Reverse(finding.priority)); // stable - plugin order tie pe
+
+        AnalysisReport {
+            total_queries: self.pattern_analyzer.total_queries(),
+            patterns,
+            column_candidates: self.column_tracker.get_indexing_candidates().into_iter().cloned().collect(),
+            slow_joins,
+            expensive_chains: self
+                .join_analyzer
+                .get_expensive_chains(self.slow_join_threshold_ms)
+                .into_iter()
+                .cloned()
+                .collect(),
+            join_recommendations: self.join_analyzer.get_join_recommendations(),
+            anomalies: self.anomalies.clone(),
+            high_cost_queries: self.high_cost_queries,
+            index_recommendations,
+            custom_findings,
+        }
+    }
+}
@@ -0,0 +1,7 @@
+pub mod analysis_engine;
+pub mod plugin;
+pub mod shared;
+
+pub use analysis_engine::{AnalysisEngine, AnalysisReport};
+pub use plugin::{AnalyzerPlugin, CustomFinding};
+pub use shared::{SharedAnalysisEngine, SharedPatternAnalyzer};
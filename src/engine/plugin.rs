@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use crate::analyzer::{ParsedQuery, QueryLog};
+use crate::recommender::{ReasonCode, Recommendation};
+
+/// Plugin ka ek result - AnalysisReport::custom_findings mein aata hai
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CustomFinding {
+    pub plugin: String, // engine report banate waqt AnalyzerPlugin::name() se bharta hai
+    pub table: Option<String>,
+    pub priority: u32,
+    pub message: String,
+    pub sql: Option<String>, // suggested fix, agar plugin ke paas ho
+}
+
+/// Schema-specific heuristics bina fork kiye pipeline mein chalane ke liye. Engine har ingested log
+/// ke saath on_log call karta hai (parse ek hi baar hota hai), aur report() pe finish().
+/// Send + Sync isliye ki SharedAnalysisEngine mein bhi engine threads ke beech share ho sake
+pub trait AnalyzerPlugin: Send + Sync {
+    fn name(&self) -> &str;
+    fn on_log(&mut self, log: &QueryLog, parsed: &ParsedQuery);
+    fn finish(&self) -> Vec<CustomFinding>;
+}
+
+impl Recommendation for CustomFinding {
+    fn priority(&self) -> u32 {
+        self.priority
+    }
+
+    fn table(&self) -> &str {
+        self.table.as_deref().unwrap_or("")
+    }
+
+    fn sql(&self) -> Option<&str> {
+        self.sql.as_deref()
+    }
+
+    fn description(&self) -> &str {
+        &self.message
+    }
+
+    fn reason_code(&self) -> ReasonCode {
+        ReasonCode::Other
+    }
+}
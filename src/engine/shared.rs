@@ -0,0 +1,121 @@
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::analyzer::{PatternAnalyzer, QueryLog, QueryPattern};
+use crate::engine::{AnalysisEngine, AnalysisReport};
+
+/// Multiple ingestion threads ke liye PatternAnalyzer - `Arc<SharedPatternAnalyzer>` share karo.
+///
+/// Locking granularity: poore analyzer pe ek `RwLock` hai. `add_log` ek log ke liye write lock leta
+/// hai, `add_logs` / `ingest_stream` poore batch ke liye ek hi baar - batch snapshot ke beech mein
+/// aadha dikhai nahi deta. `snapshot` / `analyze` read lock lete hai, isliye readers ek saath chal
+/// sakte hai par writers ko block karte hai. Kisi writer ka panic lock poison nahi karta - aggregates
+/// har log ke baad consistent rehte hai, isliye poisoned lock ka data use hota hai
+#[derive(Default)]
+pub struct SharedPatternAnalyzer {
+    inner: RwLock<PatternAnalyzer>,
+}
+
+impl SharedPatternAnalyzer {
+    pub fn new() -> Self {
+        Self::from_analyzer(PatternAnalyzer::new())
+    }
+
+    /// Configured analyzer wrap karta hai (e.g. with_capacity ya scoring strategy set karke)
+    pub fn from_analyzer(analyzer: PatternAnalyzer) -> Self {
+        Self { inner: RwLock::new(analyzer) }
+    }
+
+    pub fn add_log(&self, log: QueryLog) {
+        self.write().add_log(log);
+    }
+
+    pub fn add_logs(&self, logs: Vec<QueryLog>) {
+        self.write().add_logs(logs);
+    }
+
+    /// Write lock poore iterator ke liye hold hota hai - lazy file streams ko chhote batches mein bhejo
+    /// agar readers ko beech mein chalna hai
+    pub fn ingest_stream(&self, logs: impl IntoIterator<Item = QueryLog>) {
+        self.write().ingest_stream(logs);
+    }
+
+    pub fn analyze(&self) -> Vec<QueryPattern> {
+        self.read().analyze()
+    }
+
+    pub fn total_queries(&self) -> usize {
+        self.read().total_queries()
+    }
+
+    /// Ek consistent point-in-time copy - reporting lock ke bahar hoti hai, ingestion nahi rukta
+    pub fn snapshot(&self) -> PatternAnalyzer {
+        self.read().clone()
+    }
+
+    /// Lock ke andar read-only access, bina clone kiye
+    pub fn with<R>(&self, f: impl FnOnce(&PatternAnalyzer) -> R) -> R {
+        f(&self.read())
+    }
+
+    pub fn into_inner(self) -> PatternAnalyzer {
+        self.inner.into_inner().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, PatternAnalyzer> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, PatternAnalyzer> {
+        self.inner.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// AnalysisEngine ka thread-safe version - same locking: ek RwLock, har ingest / batch ek write lock,
+/// `snapshot` read lock ke andar poori AnalysisReport banata hai taaki saare sections ek hi point ke ho
+#[derive(Default)]
+pub struct SharedAnalysisEngine {
+    inner: RwLock<AnalysisEngine>,
+}
+
+impl SharedAnalysisEngine {
+    pub fn new() -> Self {
+        Self::from_engine(AnalysisEngine::new())
+    }
+
+    pub fn from_engine(engine: AnalysisEngine) -> Self {
+        Self { inner: RwLock::new(engine) }
+    }
+
+    pub fn ingest(&self, log: QueryLog) {
+        self.write().ingest(log);
+    }
+
+    pub fn ingest_all(&self, logs: Vec<QueryLog>) {
+        self.write().ingest_all(logs);
+    }
+
+    pub fn ingest_stream(&self, logs: impl IntoIterator<Item = QueryLog>) {
+        self.write().ingest_stream(logs);
+    }
+
+    /// Consistent report - ingestion report banne tak wait karta hai
+    pub fn snapshot(&self) -> AnalysisReport {
+        self.read().report()
+    }
+
+    pub fn with<R>(&self, f: impl FnOnce(&AnalysisEngine) -> R) -> R {
+        f(&self.read())
+    }
+
+    pub fn into_inner(self) -> AnalysisEngine {
+        self.inner.into_inner().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, AnalysisEngine> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, AnalysisEngine> {
+        self.inner.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -1,20 +1,33 @@
 pub mod analyzer;
 pub mod recommender;
 pub mod predictor;
+pub mod engine;
+pub mod sql;
+pub mod schema;
+pub mod reporting;
+pub mod stats;
+#[cfg(feature = "testing")]
+pub mod testing;
 
 pub use analyzer::{
-    QueryLog, PatternAnalyzer, QueryPattern, QueryParser, ParsedQuery,
-    ColumnTracker, ColumnUsage, TimeAnalyzer, TimePattern,
-    QueryFingerprinter, QueryFingerprint, JoinAnalyzer, JoinPattern,
-    CostCalculator, QueryCost, AnomalyDetector, AnomalyResult,
-    DataExporter, ExistingIndexChecker, ExistingIndex, IndexConflict,
-    IndexUsageSimulator, IndexSimulation, PartialIndexRecommender, PartialIndexRecommendation,
-    IndexMaintenanceCostAnalyzer, MaintenanceCost, PriorityScoringAlgorithm, PriorityScore,
-    IndexRemovalRecommender, IndexUsageStats, RemovalRecommendation,
-    QueryPlanAnalyzer, QueryPlan, PlanOperation, SchemaOptimizer, TableSchema, ColumnInfo, SchemaOptimization,
-    PerformanceMonitor, PerformanceMetric, PerformanceAlert, DeadlockDetector, DeadlockInfo, DeadlockPrevention
+    QueryLog, LogParseError, PatternAnalyzer, QueryPattern, ScoringStrategy, QueryParser, ParsedQuery, JoinClause, SelectItem,
+    SargabilityAnalyzer, NonSargablePredicate, TypeMismatch,
+    ColumnTracker, ColumnUsage, PredicateKind, TimeAnalyzer, TimePattern,
+    QueryFingerprinter, QueryFingerprint, TemplateInterner, JoinAnalyzer, JoinPattern, JoinChain,
+    Confidence, percent_improvement, Clock, SystemClock, ManualClock, CostCalculator, QueryCost, BudgetBreach, AnomalyDetector, AnomalyResult, BaselineMode,
+    DataExporter, SqlExportOptions, ExistingIndexChecker, ExistingIndex, IndexConflict,
+    IndexCostModel, BTreeCostModel, HashCostModel, GinCostModel, IndexUsageSimulator, IndexSimulation, PartialIndexRecommender, PartialIndexRecommendation, ColumnStats, FilterCondition, FilterValue,
+    IndexMaintenanceCostAnalyzer, MaintenanceCost, PriorityScoringAlgorithm, PriorityScore, WeightError,
+    IndexRemovalRecommender, IndexUsageStats, RemovalRecommendation, RemovalImpact,
+    QueryPlanAnalyzer, QueryPlan, PlanOperation, PaginationAdvisor, PaginationRecommendation, SchemaOptimizer, SqlDialect, TableSchema, ColumnInfo, SchemaOptimization, ForeignKey,
+    PerformanceMonitor, PerformanceMetric, PerformanceAlert, MetricBucket, AlertCallback, DeadlockDetector, DeadlockInfo, DeadlockPrevention, IsolationLevel, LockOrderViolation
 };
-pub use recommender::{IndexRecommender, IndexRecommendation};
+pub use recommender::{IndexRecommender, IndexRecommendation, ReasonCode, Recommendation, RecommendationSet};
 pub use predictor::{PerformancePredictor, PerformancePrediction};
+pub use engine::{AnalysisEngine, AnalysisReport, AnalyzerPlugin, CustomFinding, SharedAnalysisEngine, SharedPatternAnalyzer};
+pub use sql::SqlSyntaxError;
+pub use schema::result_type_schemas;
+pub use reporting::{build_table_reports, TableReport};
+pub use stats::{ColumnStatistics, StatsParseError, TableStatistics};
 
 
@@ -1,17 +1,35 @@
-use crate::analyzer::QueryLog;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::collections::HashMap;
+use crate::analyzer::{Confidence, QueryFingerprinter, QueryLog};
 
 /// Performance prediction result - ye struct prediction ka result store karta hai
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PerformancePrediction {
     pub estimated_time_ms: u64,
+    pub lower_bound_ms: u64,
+    pub upper_bound_ms: u64,
     pub confidence: f64,
+    #[serde(default)]
+    pub confidence_factors: Vec<String>, // e.g. "only 2 historical samples"
     pub recommendation: String,
 }
 
+impl PerformancePrediction {
+    pub fn confidence(&self) -> Confidence {
+        Confidence::new(self.confidence, self.confidence_factors.clone())
+    }
+}
+
+const MIN_FINGERPRINT_MATCHES: usize = 3;
+const WARM_TIME_FRACTION: f64 = 0.1; // cache hit pe query cold time ka ~10% leti hai
+
 /// Predicts query performance based on historical data - historical data se performance predict karta hai
 pub struct PerformancePredictor {
     historical_data: Vec<QueryLog>,
     cache_enabled: bool,
+    decay_half_life_secs: Option<u64>,
+    cache_hit_ratio: Option<f64>, // None = history mein fingerprint recurrence se estimate
 }
 
 impl PerformancePredictor {
@@ -19,6 +37,8 @@ impl PerformancePredictor {
         Self {
             historical_data: Vec::new(),
             cache_enabled,
+            decay_half_life_secs: None,
+            cache_hit_ratio: None,
         }
     }
 
@@ -33,24 +53,124 @@ impl PerformancePredictor {
             .filter(|log| log.query_type() == query_type)
             .collect();
 
+        self.predict_from(&similar_queries, rows_to_scan)
+    }
+
+    /// Bulk prediction - history ek hi baar query_type se index hoti hai, har request pe
+    /// re-filter nahi. Output order input order jaisa hi rehta hai
+    pub fn predict_batch(&self, requests: &[(String, u64)]) -> Vec<PerformancePrediction> {
+        let mut by_type: HashMap<String, Vec<&QueryLog>> = HashMap::new();
+        for log in &self.historical_data {
+            by_type.entry(log.query_type()).or_default().push(log);
+        }
+
+        requests
+            .iter()
+            .map(|(query_type, rows_to_scan)| {
+                let similar_queries = by_type.get(query_type).map(|logs| logs.as_slice()).unwrap_or(&[]);
+                self.predict_from(similar_queries, *rows_to_scan)
+            })
+            .collect()
+    }
+
+    /// Predict from logs with the same fingerprint - kam matches ho to query_type average pe fallback
+    pub fn predict_for_query(&self, query: &str, rows_to_scan: u64) -> PerformancePrediction {
+        let fingerprint = QueryFingerprinter::normalize_query(query);
+        let matching_queries: Vec<&QueryLog> = self
+            .historical_data
+            .iter()
+            .filter(|log| QueryFingerprinter::normalize_query(&log.query) == fingerprint)
+            .collect();
+
+        if matching_queries.len() >= MIN_FINGERPRINT_MATCHES {
+            return self.predict_from(&matching_queries, rows_to_scan);
+        }
+
+        // Coarse fallback - doosri queries ka average, isliye confidence aadha
+        let mut prediction = self.predict(&QueryLog::classify(query), rows_to_scan);
+        prediction.confidence *= 0.5;
+        prediction.confidence_factors.push(format!(
+            "only {} runs of this exact query - fell back to the {} average",
+            matching_queries.len(),
+            QueryLog::classify(query)
+        ));
+        prediction
+    }
+
+    /// Predict using only logs from the last window_secs - window latest log ke timestamp se measure hota hai
+    pub fn predict_with_window(&self, query_type: &str, rows_to_scan: u64, window_secs: u64) -> PerformancePrediction {
+        let latest = self.historical_data.iter().map(|log| log.timestamp).max().unwrap_or(0);
+        let window_start = latest.saturating_sub(window_secs);
+
+        let similar_queries: Vec<&QueryLog> = self
+            .historical_data
+            .iter()
+            .filter(|log| log.query_type() == query_type && log.timestamp >= window_start)
+            .collect();
+
+        self.predict_from(&similar_queries, rows_to_scan)
+    }
+
+    /// Older samples ka weight har half_life_secs mein aadha ho jata hai
+    pub fn set_decay_half_life(&mut self, half_life_secs: u64) {
+        self.decay_half_life_secs = Some(half_life_secs.max(1));
+    }
+
+    fn predict_from(&self, similar_queries: &[&QueryLog], rows_to_scan: u64) -> PerformancePrediction {
         if similar_queries.is_empty() {
+            let baseline = self.estimate_baseline(rows_to_scan);
             return PerformancePrediction {
-                estimated_time_ms: self.estimate_baseline(rows_to_scan),
+                estimated_time_ms: baseline,
+                lower_bound_ms: baseline / 2,
+                upper_bound_ms: baseline * 2,
                 confidence: 0.3,
+                confidence_factors: vec!["no historical data - row-count baseline estimate".to_string()],
                 recommendation: "No historical data available".to_string(),
             };
         }
 
-        let avg_time: u64 = similar_queries.iter().map(|l| l.execution_time_ms).sum::<u64>()
-            / similar_queries.len() as u64;
+        let (avg_time, std_dev, effective_samples) = self.weighted_stats(similar_queries);
 
         let row_factor = (rows_to_scan as f64 / 1000.0).max(1.0);
-        let estimated_time = (avg_time as f64 * row_factor) as u64;
+        let estimated_time = (avg_time * row_factor) as u64;
 
-        let cache_factor = if self.cache_enabled { 0.6 } else { 1.0 };
+        // effective = cold * (1 - hit) + warm * hit, warm = cold * WARM_TIME_FRACTION
+        let cache_factor = if self.cache_enabled {
+            let hit_ratio = self.cache_hit_ratio.unwrap_or_else(|| Self::estimate_hit_ratio(similar_queries));
+            (1.0 - hit_ratio) + WARM_TIME_FRACTION * hit_ratio
+        } else {
+            1.0
+        };
         let final_estimate = (estimated_time as f64 * cache_factor) as u64;
 
-        let confidence = (similar_queries.len() as f64 / 10.0).min(0.95);
+        // 95% interval = mean +- 1.96 sigma; 1-2 samples pe sigma bharosemand nahi, interval wide rakhte hai
+        let few_samples = similar_queries.len() <= 2;
+        let (z, spread) = if few_samples {
+            (3.0, std_dev.max(avg_time * 0.5))
+        } else {
+            (1.96, std_dev)
+        };
+        let scale = row_factor * cache_factor;
+        let lower_bound = ((avg_time - z * spread) * scale).max(0.0) as u64;
+        let upper_bound = ((avg_time + z * spread) * scale) as u64;
+
+        let confidence = if few_samples {
+            (effective_samples / 10.0).min(0.2)
+        } else {
+            (effective_samples / 10.0).min(0.95)
+        };
+
+        let mut confidence_factors = vec![if few_samples {
+            format!("only {} historical samples", similar_queries.len())
+        } else {
+            format!("{} historical samples", similar_queries.len())
+        }];
+        if self.decay_half_life_secs.is_some() {
+            confidence_factors.push(format!("time decay reduces effective samples to {:.1}", effective_samples));
+        }
+        if avg_time > 0.0 && std_dev / avg_time > 1.0 {
+            confidence_factors.push("high variance in historical execution times".to_string());
+        }
 
         let recommendation = if final_estimate > 100 {
             "Consider adding index or optimizing query".to_string()
@@ -60,16 +180,57 @@ impl PerformancePredictor {
 
         PerformancePrediction {
             estimated_time_ms: final_estimate,
+            lower_bound_ms: lower_bound,
+            upper_bound_ms: upper_bound,
             confidence,
+            confidence_factors,
             recommendation,
         }
     }
 
+    // Exponential time-decay weighted (mean, std dev, effective sample count); decay off ho to plain stats
+    fn weighted_stats(&self, logs: &[&QueryLog]) -> (f64, f64, f64) {
+        let latest = logs.iter().map(|log| log.timestamp).max().unwrap_or(0);
+        let weight = |log: &QueryLog| match self.decay_half_life_secs {
+            Some(half_life) => 0.5_f64.powf((latest - log.timestamp) as f64 / half_life as f64),
+            None => 1.0,
+        };
+
+        let total_weight: f64 = logs.iter().map(|log| weight(log)).sum();
+        let average = logs.iter().map(|log| weight(log) * log.execution_time_ms as f64).sum::<f64>() / total_weight;
+        let variance = logs
+            .iter()
+            .map(|log| weight(log) * (log.execution_time_ms as f64 - average).powi(2))
+            .sum::<f64>()
+            / total_weight;
+        (average, variance.sqrt(), total_weight)
+    }
+
+    // Repeat executions of an already-seen fingerprint count as likely cache hits
+    fn estimate_hit_ratio(logs: &[&QueryLog]) -> f64 {
+        if logs.is_empty() {
+            return 0.0;
+        }
+
+        let mut fingerprints: Vec<String> = logs
+            .iter()
+            .map(|log| QueryFingerprinter::normalize_query(&log.query))
+            .collect();
+        fingerprints.sort();
+        fingerprints.dedup();
+
+        1.0 - fingerprints.len() as f64 / logs.len() as f64
+    }
+
     fn estimate_baseline(&self, rows_to_scan: u64) -> u64 {
         let base_time = 10;
         base_time + (rows_to_scan / 100)
     }
 
+    pub fn set_cache_hit_ratio(&mut self, hit_ratio: f64) {
+        self.cache_hit_ratio = Some(hit_ratio.clamp(0.0, 1.0));
+    }
+
     pub fn enable_cache(&mut self) {
         self.cache_enabled = true;
     }
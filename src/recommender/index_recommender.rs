@@ -1,7 +1,15 @@
-use crate::analyzer::QueryPattern;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use crate::recommender::ReasonCode;
+use crate::stats::TableStatistics;
+use crate::analyzer::{
+    ColumnTracker, ColumnUsage, ExistingIndexChecker, JoinPattern, ParsedQuery, PredicateKind, QueryPattern, SqlDialect,
+};
 
 /// Index recommendation for database optimization - database optimization ke liye index recommend karta hai
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct IndexRecommendation {
     pub table: String,
     pub column: String,
@@ -9,18 +17,111 @@ pub struct IndexRecommendation {
     pub priority: u32,
     pub estimated_improvement_percent: f64,
     pub reason: String,
+    #[serde(default)]
+    pub reason_code: ReasonCode,
+    #[serde(default)]
+    pub include_columns: Vec<String>, // covering index ke non-key columns, empty = plain index
+    #[serde(default)]
+    pub operator_class: Option<String>, // e.g. "gin_trgm_ops" - Postgres key column ke baad lagta hai
+    #[serde(default)]
+    pub composite_columns: Vec<String>, // `column` ke baad wale key columns, index order mein
+    #[serde(default)]
+    pub column_order_rationale: Option<String>, // composite key ka order kyun chuna gaya
 }
 
-#[derive(Debug, Clone)]
+impl IndexRecommendation {
+    /// CREATE INDEX statement - Postgres INCLUDE (...) use karta hai, baaki engines included
+    /// columns ko key ke end mein append karte hai
+    pub fn create_index_sql(&self, dialect: SqlDialect) -> String {
+        self.build_index_sql(dialect)
+    }
+
+    /// Full index key - leading column + composite_columns
+    pub fn key_columns(&self) -> Vec<String> {
+        std::iter::once(self.column.clone()).chain(self.composite_columns.iter().cloned()).collect()
+    }
+
+    pub fn index_name(&self) -> String {
+        format!("idx_{}_{}", self.table, self.key_columns().join("_"))
+    }
+
+    fn build_index_sql(&self, dialect: SqlDialect) -> String {
+        let key_columns = self.key_columns();
+        let index_name = self.index_name();
+        let using = match (&self.index_type, dialect) {
+            (IndexType::Hash, SqlDialect::Postgres) => " USING HASH",
+            (IndexType::Gin, SqlDialect::Postgres) => " USING GIN",
+            (IndexType::Brin, SqlDialect::Postgres) => " USING BRIN",
+            _ => "",
+        };
+
+        if self.include_columns.is_empty() {
+            let key = match (&self.operator_class, dialect) {
+                (Some(operator_class), SqlDialect::Postgres) => format!("{} {}", self.column, operator_class),
+                _ => key_columns.join(", "),
+            };
+            return format!("CREATE INDEX {} ON {}{} ({})", index_name, self.table, using, key);
+        }
+
+        match dialect {
+            SqlDialect::Postgres => format!(
+                "CREATE INDEX {} ON {}{} ({}) INCLUDE ({})",
+                index_name, self.table, using, key_columns.join(", "), self.include_columns.join(", ")
+            ),
+            SqlDialect::MySql | SqlDialect::Sqlite => format!(
+                "CREATE INDEX {} ON {} ({}, {})",
+                index_name, self.table, key_columns.join(", "), self.include_columns.join(", ")
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum IndexType {
     BTree,
     Hash,
+    Gin,  // full-text / containment predicates
+    Brin, // append-only large tables, range scans on naturally ordered columns
+}
+
+const BRIN_MIN_ROWS_PER_QUERY: f64 = 1_000_000.0;
+const MAX_COVERING_COLUMNS: usize = 5; // key + included - isse bada index table jitna mehenga ho jata hai
+const TRIGRAM_OPERATOR_CLASS: &str = "gin_trgm_ops"; // pg_trgm extension chahiye
+// WHERE predicate jo composite key mein ja sakta hai
+struct KeyPredicate {
+    column: String,
+    is_equality: bool,
+    value: Option<String>, // equality literal - MCV lookup ke liye
 }
 
+// GROUP BY / ORDER BY columns jo index order se serve ho sakte hai
+struct OrderingColumns {
+    clause: &'static str, // "GROUP BY" / "ORDER BY"
+    columns: Vec<String>,
+}
+
+impl OrderingColumns {
+    fn reason_code(&self) -> ReasonCode {
+        if self.clause == "GROUP BY" { ReasonCode::Grouping } else { ReasonCode::Sorting }
+    }
+
+    fn label(&self) -> &'static str {
+        if self.clause == "GROUP BY" { "grouping" } else { "sorting" }
+    }
+
+    fn benefit(&self) -> &'static str {
+        if self.clause == "GROUP BY" { "avoids a sort/hash aggregate" } else { "avoids a sort" }
+    }
+}
+
+const HIGH_SCAN_AMPLIFICATION: f64 = 100.0; // 100 rows scan per row returned se upar index lagbhag pakka chahiye
+
 /// Recommends database indexes based on query patterns - query patterns ke basis pe indexes suggest karta hai
 pub struct IndexRecommender {
     slowness_threshold: f64,
     frequency_threshold: u64,
+    recommend_covering: bool,
+    table_statistics: HashMap<String, (TableStatistics, u64)>, // table -> (pg_stats, total_rows)
 }
 
 impl IndexRecommender {
@@ -28,10 +129,326 @@ impl IndexRecommender {
         Self {
             slowness_threshold,
             frequency_threshold,
+            recommend_covering: false,
+            table_statistics: HashMap::new(),
+        }
+    }
+
+    /// pg_stats se composite key columns selectivity ke hisaab se order hote hai - total_rows
+    /// pg_class.reltuples se (pg_stats mein row count nahi hota)
+    pub fn add_table_statistics(&mut self, statistics: TableStatistics, total_rows: u64) {
+        self.table_statistics.insert(statistics.table_name.clone(), (statistics, total_rows));
+    }
+
+    /// Covering indexes on karta hai - recommend_for_query SELECT columns ko include_columns mein daalta hai
+    pub fn set_recommend_covering(&mut self, enabled: bool) {
+        self.recommend_covering = enabled;
+    }
+
+    /// Single-table query ke WHERE column pe index; covering mode mein SELECT list bhi include hoti hai
+    /// taaki table lookup hi na karna pade. Sirf equality filters ho to GROUP BY / ORDER BY columns key
+    /// ke end mein jate hai - rows index order mein aati hai, sort / hash aggregate nahi lagta
+    pub fn recommend_for_query(&self, parsed: &ParsedQuery, pattern: &QueryPattern) -> Option<IndexRecommendation> {
+        if pattern.slowness_score <= self.slowness_threshold && pattern.frequency <= self.frequency_threshold {
+            return None;
+        }
+
+        let [table] = parsed.from_tables.as_slice() else {
+            return None;
+        };
+
+        // "users.email" / "email" -> "email" for the target table
+        let column_of = |qualified: &str| -> Option<String> {
+            let resolved = parsed.resolve_column(qualified.trim());
+            let column = match resolved.split_once('.') {
+                Some((owner, column)) if owner == table => column,
+                Some(_) => return None,
+                None => resolved.as_str(),
+            };
+            let is_identifier = !column.is_empty() && column.chars().all(|c| c.is_alphanumeric() || c == '_');
+            is_identifier.then(|| column.to_string())
+        };
+
+        let referenced_columns: Vec<String> = parsed.referenced_columns().iter().filter_map(|c| column_of(c)).collect();
+        let ordering = Self::ordering_columns(parsed, &column_of);
+        let key_column = match referenced_columns.first() {
+            Some(column) => column.clone(),
+            None => ordering.as_ref()?.columns[0].clone(),
+        };
+
+        // LIKE '%x%' / @@ pe BTree bekaar hai - GIN chahiye, aur GIN INCLUDE support nahi karta
+        let text_predicate = parsed.where_clauses.iter().find_map(|condition| {
+            let (column, kind) = ColumnTracker::extract_column_from_condition(condition)?;
+            let is_text = matches!(kind, PredicateKind::FullText | PredicateKind::PatternMatch);
+            (is_text && column_of(&column).as_deref() == Some(key_column.as_str())).then_some(kind)
+        });
+        if let Some(kind) = text_predicate {
+            return Some(self.gin_recommendation(table, &key_column, kind, pattern));
+        }
+
+        // 2+ WHERE columns - composite key, order selectivity se
+        let predicates = Self::key_predicates(parsed, &column_of);
+        let (mut key_columns, mut column_order_rationale) = if predicates.len() > 1 {
+            let (ordered, rationale) = self.order_composite_columns(table, &predicates);
+            (ordered, Some(rationale))
+        } else if referenced_columns.is_empty() {
+            (Vec::new(), None)
+        } else {
+            (vec![key_column], None)
+        };
+        let filter_columns = key_columns.clone();
+
+        // Range ya unknown filter ke baad wale key columns order deliver nahi karte
+        let only_equality = key_columns
+            .iter()
+            .all(|column| predicates.iter().any(|p| &p.column == column && p.is_equality));
+        let ordering = ordering.filter(|_| only_equality).filter(|ordering| {
+            ordering.columns.iter().any(|column| !key_columns.contains(column))
+        });
+        if let Some(ordering) = &ordering {
+            let appended: Vec<String> =
+                ordering.columns.iter().filter(|column| !key_columns.contains(column)).cloned().collect();
+            let note = format!(
+                "{} column {} after the equality columns - rows come out in index order",
+                ordering.clause,
+                appended.join(", ")
+            );
+            column_order_rationale = Some(match column_order_rationale {
+                Some(rationale) => format!("{}; {}", rationale, note),
+                None => note,
+            });
+            key_columns.extend(appended);
+        }
+
+        let include_columns = if self.recommend_covering {
+            self.covering_columns(parsed, &key_columns, &referenced_columns, &column_of).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+
+        let base_reason = match &ordering {
+            Some(ordering) if filter_columns.is_empty() => format!(
+                "{}, {}: {} {} - index order {}",
+                self.generate_reason(pattern),
+                ordering.label(),
+                ordering.clause,
+                ordering.columns.join(", "),
+                ordering.benefit()
+            ),
+            Some(ordering) => format!(
+                "{}, filtering on {} then {} {} - index order {}",
+                self.generate_reason(pattern),
+                filter_columns.join(", "),
+                ordering.clause,
+                ordering.columns.join(", "),
+                ordering.benefit()
+            ),
+            None => self.generate_reason(pattern),
+        };
+        let reason = if include_columns.is_empty() {
+            base_reason
+        } else {
+            format!("{}, covering index avoids table lookups for {}", base_reason, include_columns.join(", "))
+        };
+        // Koi filter nahi - index sirf grouping / sorting ke liye hai
+        let reason_code = match &ordering {
+            Some(ordering) if filter_columns.is_empty() => ordering.reason_code(),
+            _ => self.classify_reason(pattern),
+        };
+
+        Some(IndexRecommendation {
+            table: table.clone(),
+            column: key_columns[0].clone(),
+            index_type: IndexType::BTree, // hash/GIN/BRIN INCLUDE support nahi karte
+            priority: self.calculate_priority(pattern),
+            estimated_improvement_percent: self.calculate_improvement(pattern),
+            reason,
+            reason_code,
+            include_columns,
+            operator_class: None,
+            composite_columns: key_columns[1..].to_vec(),
+            column_order_rationale,
+        })
+    }
+
+    // GROUP BY columns (warna ORDER BY) - sab target table ke plain columns hone chahiye, aur ORDER BY
+    // mein mixed ASC/DESC ek simple index se serve nahi hota
+    fn ordering_columns(parsed: &ParsedQuery, column_of: &dyn Fn(&str) -> Option<String>) -> Option<OrderingColumns> {
+        if !parsed.group_by_columns.is_empty() {
+            let columns = parsed.group_by_columns.iter().map(|c| column_of(c)).collect::<Option<Vec<String>>>()?;
+            return Some(OrderingColumns { clause: "GROUP BY", columns });
+        }
+        if parsed.order_by_columns.is_empty() {
+            return None;
+        }
+
+        let mut directions = Vec::new();
+        let mut columns = Vec::new();
+        for item in &parsed.order_by_columns {
+            let mut parts = item.split_whitespace();
+            columns.push(column_of(parts.next()?)?);
+            directions.push(parts.next().is_some_and(|d| d.eq_ignore_ascii_case("DESC")));
+        }
+        directions.dedup();
+        (directions.len() == 1).then_some(OrderingColumns { clause: "ORDER BY", columns })
+    }
+
+    // WHERE ke equality / range columns (target table ke), query order mein; same column dono
+    // tarah aaye to equality jeetti hai
+    fn key_predicates(parsed: &ParsedQuery, column_of: &dyn Fn(&str) -> Option<String>) -> Vec<KeyPredicate> {
+        let mut predicates: Vec<KeyPredicate> = Vec::new();
+        for condition in &parsed.where_clauses {
+            let Some((column, kind)) = ColumnTracker::extract_column_from_condition(condition) else {
+                continue;
+            };
+            let Some(column) = column_of(&column) else {
+                continue;
+            };
+            if !matches!(kind, PredicateKind::Equality | PredicateKind::Range) {
+                continue;
+            }
+
+            let is_equality = matches!(kind, PredicateKind::Equality);
+            let value = if is_equality { Self::literal_value(condition) } else { None };
+            match predicates.iter_mut().find(|p| p.column == column) {
+                Some(existing) => {
+                    if is_equality && !existing.is_equality {
+                        existing.is_equality = true;
+                        existing.value = value;
+                    }
+                }
+                None => predicates.push(KeyPredicate { column, is_equality, value }),
+            }
+        }
+        predicates
+    }
+
+    // "status = 'open'" -> "open", "qty = 5" -> "5"; IN lists aur bind params ($1, ?) -> None
+    fn literal_value(condition: &str) -> Option<String> {
+        if condition.to_uppercase().contains(" IN ") {
+            return None;
+        }
+        let (_, value) = condition.split_once('=')?;
+        let value = value.trim().trim_end_matches(')').trim();
+        if let Some(quoted) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+            return Some(quoted.to_string());
+        }
+        value.parse::<f64>().is_ok().then(|| value.to_string())
+    }
+
+    /// Composite key order: equality columns sabse selective pehle (kam matching rows = pehle),
+    /// range columns last kyunki range ke baad wale key columns seek mein use nahi hote.
+    /// TableStatistics na ho to equality columns query order mein rehte hai
+    fn order_composite_columns(&self, table: &str, predicates: &[KeyPredicate]) -> (Vec<String>, String) {
+        let statistics = self.table_statistics.get(table);
+        let selectivity_of = |predicate: &KeyPredicate| -> Option<f64> {
+            let (stats, total_rows) = statistics?;
+            Some(stats.column(&predicate.column)?.equality_selectivity(predicate.value.as_deref(), *total_rows))
+        };
+
+        let mut equality: Vec<(&KeyPredicate, Option<f64>)> = predicates
+            .iter()
+            .filter(|p| p.is_equality)
+            .map(|p| (p, selectivity_of(p)))
+            .collect();
+        // Known selectivity ascending, unknown unke baad query order mein (stable sort)
+        equality.sort_by(|(_, a), (_, b)| match (a, b) {
+            (Some(a), Some(b)) => a.total_cmp(b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        });
+        let range: Vec<&KeyPredicate> = predicates.iter().filter(|p| !p.is_equality).collect();
+
+        let mut parts: Vec<String> = Vec::new();
+        if !equality.is_empty() {
+            let described: Vec<String> = equality
+                .iter()
+                .map(|(p, selectivity)| match selectivity {
+                    Some(selectivity) => format!("{} (selectivity {:.4})", p.column, selectivity),
+                    None => p.column.clone(),
+                })
+                .collect();
+            let basis = if statistics.is_some() {
+                "most selective equality column first"
+            } else {
+                "no table statistics, equality columns in query order"
+            };
+            parts.push(format!("{}: {}", basis, described.join(", ")));
+        }
+        if !range.is_empty() {
+            let columns: Vec<&str> = range.iter().map(|p| p.column.as_str()).collect();
+            parts.push(format!(
+                "range column {} last - a range predicate stops further key columns from being used",
+                columns.join(", ")
+            ));
+        }
+
+        let ordered = equality
+            .iter()
+            .map(|(p, _)| p.column.clone())
+            .chain(range.iter().map(|p| p.column.clone()))
+            .collect();
+        (ordered, parts.join("; "))
+    }
+
+    fn gin_recommendation(&self, table: &str, column: &str, kind: PredicateKind, pattern: &QueryPattern) -> IndexRecommendation {
+        let (operator_class, detail) = match kind {
+            PredicateKind::PatternMatch => (
+                Some(TRIGRAM_OPERATOR_CLASS.to_string()),
+                "leading-wildcard LIKE needs a trigram GIN index (CREATE EXTENSION pg_trgm)",
+            ),
+            _ => (None, "text-search predicate needs a GIN index"),
+        };
+
+        IndexRecommendation {
+            table: table.to_string(),
+            column: column.to_string(),
+            index_type: IndexType::Gin,
+            priority: self.calculate_priority(pattern),
+            estimated_improvement_percent: self.calculate_improvement(pattern),
+            reason: format!("{}, {}", self.generate_reason(pattern), detail),
+            reason_code: ReasonCode::TextSearch,
+            include_columns: Vec::new(),
+            operator_class,
+            composite_columns: Vec::new(),
+            column_order_rationale: None,
         }
     }
 
+    // SELECT list + WHERE columns chhote ho tabhi covering banta hai; "*" ya expressions ho to None
+    fn covering_columns(
+        &self,
+        parsed: &ParsedQuery,
+        key_columns: &[String],
+        where_columns: &[String],
+        column_of: &dyn Fn(&str) -> Option<String>,
+    ) -> Option<Vec<String>> {
+        // Key mein na aaye WHERE columns pehle - appended-key engines mein ye filter ke kaam aate hai
+        let mut include_columns: Vec<String> = where_columns
+            .iter()
+            .filter(|column| !key_columns.contains(column))
+            .cloned()
+            .collect();
+        include_columns.dedup();
+        for select_column in &parsed.select_columns {
+            let column = column_of(select_column)?;
+            if !key_columns.contains(&column) && !include_columns.contains(&column) {
+                include_columns.push(column);
+            }
+        }
+
+        let total_columns = key_columns.len() + include_columns.len();
+        (!include_columns.is_empty() && total_columns <= MAX_COVERING_COLUMNS).then_some(include_columns)
+    }
+
     pub fn recommend(&self, patterns: &[QueryPattern]) -> Vec<IndexRecommendation> {
+        self.recommend_with_usage(patterns, &ColumnTracker::new())
+    }
+
+    /// ColumnTracker ke predicate stats se column aur index type choose karta hai - tracker mein
+    /// table ka koi column na ho to "id" pe BTree
+    pub fn recommend_with_usage(&self, patterns: &[QueryPattern], tracker: &ColumnTracker) -> Vec<IndexRecommendation> {
         let mut recommendations = Vec::new();
 
         for pattern in patterns {
@@ -42,13 +459,60 @@ impl IndexRecommender {
                     let improvement = self.calculate_improvement(pattern);
                     let priority = self.calculate_priority(pattern);
 
+                    let columns = tracker.get_table_columns(table);
+                    let usage = columns.iter().copied().find(|col| col.in_where_clause > 0 || col.in_join_condition > 0);
+                    // Filter column na ho to analytics query ka GROUP BY / ORDER BY column
+                    let ordering_usage = match usage {
+                        Some(_) => None,
+                        None => columns.iter().copied().find(|col| col.in_group_by > 0 || col.in_order_by > 0),
+                    };
+                    let column = usage
+                        .or(ordering_usage)
+                        .map(|u| u.column_name.clone())
+                        .unwrap_or_else(|| "id".to_string());
+
+                    // Sirf LIKE '%x%' wale column pe trigram opclass; @@ / @> plain GIN se chal jata hai
+                    let operator_class = usage
+                        .filter(|u| u.pattern_match_predicates > 0 && u.full_text_predicates == 0)
+                        .map(|_| TRIGRAM_OPERATOR_CLASS.to_string());
+                    // Sort bachane ke liye ordered index chahiye - hash / BRIN nahi
+                    let index_type = match ordering_usage {
+                        Some(_) => IndexType::BTree,
+                        None => self.suggest_index_type(pattern, usage),
+                    };
+                    let (reason_code, reason) = match (&index_type, ordering_usage) {
+                        (IndexType::Gin, _) => (ReasonCode::TextSearch, self.generate_reason(pattern)),
+                        (_, Some(ordering)) if ordering.in_group_by > 0 => (
+                            ReasonCode::Grouping,
+                            format!(
+                                "{}, grouping: GROUP BY {} - index order avoids a sort/hash aggregate",
+                                self.generate_reason(pattern),
+                                ordering.column_name
+                            ),
+                        ),
+                        (_, Some(ordering)) => (
+                            ReasonCode::Sorting,
+                            format!(
+                                "{}, sorting: ORDER BY {} - index order avoids a sort",
+                                self.generate_reason(pattern),
+                                ordering.column_name
+                            ),
+                        ),
+                        _ => (self.classify_reason(pattern), self.generate_reason(pattern)),
+                    };
+
                     let recommendation = IndexRecommendation {
                         table: table.clone(),
-                        column: "id".to_string(),
-                        index_type: self.suggest_index_type(pattern),
+                        column,
+                        index_type,
                         priority,
                         estimated_improvement_percent: improvement,
-                        reason: self.generate_reason(pattern),
+                        reason,
+                        reason_code,
+                        include_columns: Vec::new(),
+                        operator_class,
+                        composite_columns: Vec::new(),
+                        column_order_rationale: None,
                     };
 
                     recommendations.push(recommendation);
@@ -56,7 +520,104 @@ impl IndexRecommender {
             }
         }
 
-        recommendations.sort_by(|a, b| b.priority.cmp(&a.priority));
+        recommendations.sort_by_key(|rec| std::cmp::Reverse(rec.priority));
+        recommendations
+    }
+
+    /// Existing indexes ke against recommend karta hai - returns (new, already covered).
+    /// Covered ones ke reason mein covering index ka naam add hota hai
+    pub fn recommend_with_existing(
+        &self,
+        patterns: &[QueryPattern],
+        checker: &ExistingIndexChecker,
+    ) -> (Vec<IndexRecommendation>, Vec<IndexRecommendation>) {
+        Self::split_covered(self.recommend(patterns), checker)
+    }
+
+    /// Existing indexes se already covered recommendations alag karta hai - (new, covered)
+    pub fn split_covered(
+        recommendations: Vec<IndexRecommendation>,
+        checker: &ExistingIndexChecker,
+    ) -> (Vec<IndexRecommendation>, Vec<IndexRecommendation>) {
+        let mut new_recommendations = Vec::new();
+        let mut covered = Vec::new();
+
+        for mut recommendation in recommendations {
+            // Poori key (composite bhi) aur INCLUDE columns - sirf leading column match hona kaafi nahi
+            let mut columns = recommendation.key_columns();
+            columns.extend(recommendation.include_columns.iter().cloned());
+            let covering_index = checker
+                .check_for_conflicts(&recommendation.table, &columns)
+                .into_iter()
+                .find(|c| c.conflict_type == "duplicate" || c.conflict_type == "prefix_redundant");
+
+            match covering_index {
+                Some(conflict) => {
+                    recommendation.reason = format!(
+                        "{} (already covered by {} - {})",
+                        recommendation.reason, conflict.existing_index, conflict.conflict_type
+                    );
+                    covered.push(recommendation);
+                }
+                None => new_recommendations.push(recommendation),
+            }
+        }
+
+        (new_recommendations, covered)
+    }
+
+    /// Recommend indexes on join columns - jo join column kisi existing index se cover nahi hota uske liye.
+    /// Primary keys bhi checker mein registered honi chahiye, warna unka side bhi recommend hoga
+    pub fn recommend_for_joins(&self, joins: &[JoinPattern], checker: &ExistingIndexChecker) -> Vec<IndexRecommendation> {
+        let mut recommendations: Vec<IndexRecommendation> = Vec::new();
+
+        for join in joins {
+            let slowness_score = join.avg_execution_time * join.join_count as f64;
+            if slowness_score <= self.slowness_threshold && join.join_count <= self.frequency_threshold {
+                continue;
+            }
+
+            for join_column in &join.join_columns {
+                let Some((table, column)) = join_column.split_once('.') else {
+                    continue;
+                };
+
+                if recommendations.iter().any(|r| r.table == table && r.column == column) {
+                    continue;
+                }
+
+                // Leading column wala index ho to join lookup already serve ho jata hai
+                let is_indexed = checker
+                    .check_for_conflicts(table, &[column.to_string()])
+                    .iter()
+                    .any(|c| c.conflict_type == "duplicate" || c.conflict_type == "prefix_redundant");
+                if is_indexed {
+                    continue;
+                }
+
+                let base = if slowness_score > 10000.0 { 100 } else { 50 };
+                let freq_bonus = (join.join_count / 10).min(50) as u32;
+
+                recommendations.push(IndexRecommendation {
+                    table: table.to_string(),
+                    column: column.to_string(),
+                    index_type: IndexType::BTree,
+                    priority: base + freq_bonus,
+                    estimated_improvement_percent: 40.0 + (join.join_count as f64 / 100.0).min(30.0),
+                    reason: format!(
+                        "{} JOIN between {} and {} on {}, Frequency: {}, Avg time: {:.2}ms",
+                        join.join_type, join.table1, join.table2, join_column, join.join_count, join.avg_execution_time
+                    ),
+                    reason_code: ReasonCode::UnindexedJoin,
+                    include_columns: Vec::new(),
+                    operator_class: None,
+                    composite_columns: Vec::new(),
+                    column_order_rationale: None,
+                });
+            }
+        }
+
+        recommendations.sort_by_key(|rec| std::cmp::Reverse(rec.priority));
         recommendations
     }
 
@@ -75,21 +636,58 @@ impl IndexRecommender {
         };
 
         let freq_bonus = (pattern.frequency / 10).min(50);
-        base + freq_bonus as u32
+        // Har 10x amplification pe +10, max 50 - 1M scan / 10 returned = 1e5 = +50
+        let amplification_bonus = pattern
+            .avg_scan_amplification
+            .map(|amplification| (amplification.max(1.0).log10() * 10.0).min(50.0) as u32)
+            .unwrap_or(0);
+        base + freq_bonus as u32 + amplification_bonus
+    }
+
+    // Hash sirf pure equality lookups ke liye - range ya ORDER BY ho to BTree
+    fn suggest_index_type(&self, pattern: &QueryPattern, usage: Option<&ColumnUsage>) -> IndexType {
+        let Some(usage) = usage else {
+            return IndexType::BTree;
+        };
+
+        if usage.full_text_predicates > 0 || usage.pattern_match_predicates > 0 {
+            return IndexType::Gin;
+        }
+        if usage.is_equality_only() {
+            return IndexType::Hash;
+        }
+
+        // Time-ordered column on a huge table - BRIN chhota hai aur range scans ke liye kaafi
+        let rows_per_query = pattern.total_rows_scanned as f64 / pattern.frequency.max(1) as f64;
+        let column = usage.column_name.to_lowercase();
+        let time_ordered = column.ends_with("_at") || column.ends_with("_time") || column.contains("timestamp");
+        if time_ordered && usage.equality_predicates == 0 && rows_per_query >= BRIN_MIN_ROWS_PER_QUERY {
+            return IndexType::Brin;
+        }
+
+        IndexType::BTree
     }
 
-    fn suggest_index_type(&self, pattern: &QueryPattern) -> IndexType {
-        if pattern.query_type == "SELECT" && pattern.frequency > 100 {
-            IndexType::Hash
+    // Slowness threshold cross hua to SlowScan, warna sirf frequency ki wajah se aaya
+    fn classify_reason(&self, pattern: &QueryPattern) -> ReasonCode {
+        let high_amplification = pattern.avg_scan_amplification.is_some_and(|a| a >= HIGH_SCAN_AMPLIFICATION);
+        if pattern.slowness_score > self.slowness_threshold || high_amplification {
+            ReasonCode::SlowScan
         } else {
-            IndexType::BTree
+            ReasonCode::HighFrequency
         }
     }
 
     fn generate_reason(&self, pattern: &QueryPattern) -> String {
-        format!(
+        let reason = format!(
             "Query type: {}, Frequency: {}, Avg time: {:.2}ms",
             pattern.query_type, pattern.frequency, pattern.avg_execution_time_ms
-        )
+        );
+        match pattern.avg_scan_amplification {
+            Some(amplification) if amplification >= HIGH_SCAN_AMPLIFICATION => {
+                format!("{}, scans {:.0} rows per row returned", reason, amplification)
+            }
+            _ => reason,
+        }
     }
 }
\ No newline at end of file
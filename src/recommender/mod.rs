@@ -1,3 +1,5 @@
 pub mod index_recommender;
+pub mod recommendation;
 
-pub use index_recommender::{IndexRecommender, IndexRecommendation};
\ No newline at end of file
+pub use index_recommender::{IndexRecommender, IndexRecommendation};
+pub use recommendation::{ReasonCode, Recommendation, RecommendationSet};
\ No newline at end of file
@@ -0,0 +1,429 @@
+use std::fmt::Debug;
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use crate::analyzer::{
+    DeadlockPrevention, ExistingIndex, ExistingIndexChecker, PaginationRecommendation, PartialIndexRecommendation,
+    RemovalRecommendation, SchemaOptimization, SqlDialect,
+};
+use crate::recommender::IndexRecommendation;
+
+/// Machine-readable "kyun" - reason strings pe string matching ki jagah isse filter/group karo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize, JsonSchema)]
+pub enum ReasonCode {
+    HighFrequency,
+    SlowScan,
+    UnindexedJoin,
+    LowSelectivity, // filter bahut kam rows match karta hai - partial index
+    TextSearch,
+    UnusedIndex,
+    LowBenefit,
+    RedundantIndex,
+    OversizedDataType,
+    Nullability,
+    UnindexedForeignKey,
+    SchemaDesign,
+    DeadlockProneQuery,
+    LockOrder,
+    Grouping, // GROUP BY column - index order se sort / hash aggregate bachta hai
+    Sorting,  // ORDER BY column - index order se sort bachta hai
+    UnboundedResult, // bina LIMIT ka bada SELECT - query rewrite, index nahi
+    #[default]
+    Other,
+}
+
+impl ReasonCode {
+    /// Generic English text for the code - detailed reason field ka short version
+    pub fn description(&self) -> &'static str {
+        match self {
+            ReasonCode::HighFrequency => "Frequently executed query would benefit from an index",
+            ReasonCode::SlowScan => "Slow query scans too many rows",
+            ReasonCode::UnindexedJoin => "Join column is not indexed",
+            ReasonCode::LowSelectivity => "Filter matches a small fraction of rows",
+            ReasonCode::TextSearch => "Text search predicate needs a GIN index",
+            ReasonCode::UnusedIndex => "Index is rarely or never used",
+            ReasonCode::LowBenefit => "Index maintenance cost outweighs its benefit",
+            ReasonCode::RedundantIndex => "Index is redundant with another index",
+            ReasonCode::OversizedDataType => "Column data type is larger than needed",
+            ReasonCode::Nullability => "Column nullability does not match its data",
+            ReasonCode::UnindexedForeignKey => "Foreign key column is not indexed",
+            ReasonCode::SchemaDesign => "Table design can be improved",
+            ReasonCode::DeadlockProneQuery => "Query is repeatedly involved in deadlocks",
+            ReasonCode::LockOrder => "Tables are locked in inconsistent order",
+            ReasonCode::Grouping => "Index on grouping columns avoids a sort or hash aggregate",
+            ReasonCode::Sorting => "Index on ORDER BY columns avoids a sort",
+            ReasonCode::UnboundedResult => "Frequent SELECT returns many rows without a LIMIT",
+            ReasonCode::Other => "Other",
+        }
+    }
+}
+
+/// Common shape for every analyzer's output - dashboard ek hi list render kar sake isliye
+pub trait Recommendation: Debug {
+    fn priority(&self) -> u32;
+    fn table(&self) -> &str;
+    fn sql(&self) -> Option<&str>;
+    fn description(&self) -> &str;
+    fn reason_code(&self) -> ReasonCode;
+
+    /// Columns of the index this recommendation would create - None agar ye index create nahi karta
+    fn index_columns(&self) -> Option<Vec<String>> {
+        None
+    }
+
+    /// WHERE condition for partial index recommendations
+    fn index_filter(&self) -> Option<&str> {
+        None
+    }
+
+    /// Short human-readable benefit, migration file ke comment header mein jata hai
+    fn estimated_benefit(&self) -> Option<String> {
+        None
+    }
+
+    /// Runnable statement for the dialect - default sql() hai, generated DDL wale types override karte hai
+    fn migration_sql(&self, _dialect: SqlDialect) -> Option<String> {
+        self.sql().map(|sql| sql.to_string())
+    }
+
+    /// migration_sql ka inverse (CREATE INDEX -> DROP INDEX, type change -> original type) -
+    /// None = reverse nahi ho sakta ya koi SQL hi nahi hai
+    fn rollback_sql(&self, _dialect: SqlDialect) -> Option<String> {
+        None
+    }
+}
+
+// RecommendationSet::into_sorted ka output bhi export / generic APIs mein pass ho sake
+impl<R: Recommendation + ?Sized> Recommendation for Box<R> {
+    fn priority(&self) -> u32 {
+        (**self).priority()
+    }
+
+    fn table(&self) -> &str {
+        (**self).table()
+    }
+
+    fn sql(&self) -> Option<&str> {
+        (**self).sql()
+    }
+
+    fn description(&self) -> &str {
+        (**self).description()
+    }
+
+    fn reason_code(&self) -> ReasonCode {
+        (**self).reason_code()
+    }
+
+    fn index_columns(&self) -> Option<Vec<String>> {
+        (**self).index_columns()
+    }
+
+    fn index_filter(&self) -> Option<&str> {
+        (**self).index_filter()
+    }
+
+    fn estimated_benefit(&self) -> Option<String> {
+        (**self).estimated_benefit()
+    }
+
+    fn migration_sql(&self, dialect: SqlDialect) -> Option<String> {
+        (**self).migration_sql(dialect)
+    }
+
+    fn rollback_sql(&self, dialect: SqlDialect) -> Option<String> {
+        (**self).rollback_sql(dialect)
+    }
+}
+
+impl Recommendation for IndexRecommendation {
+    fn priority(&self) -> u32 {
+        self.priority
+    }
+
+    fn table(&self) -> &str {
+        &self.table
+    }
+
+    fn sql(&self) -> Option<&str> {
+        None // DDL abhi generate nahi hota
+    }
+
+    fn description(&self) -> &str {
+        &self.reason
+    }
+
+    fn reason_code(&self) -> ReasonCode {
+        self.reason_code
+    }
+
+    fn index_columns(&self) -> Option<Vec<String>> {
+        Some(self.key_columns())
+    }
+
+    fn estimated_benefit(&self) -> Option<String> {
+        Some(format!("~{:.0}% faster matching queries", self.estimated_improvement_percent))
+    }
+
+    fn migration_sql(&self, dialect: SqlDialect) -> Option<String> {
+        Some(self.create_index_sql(dialect))
+    }
+
+    fn rollback_sql(&self, dialect: SqlDialect) -> Option<String> {
+        Some(crate::sql::drop_index_sql(&self.index_name(), &self.table, dialect))
+    }
+}
+
+impl Recommendation for PartialIndexRecommendation {
+    fn priority(&self) -> u32 {
+        self.performance_impact.round().max(0.0) as u32
+    }
+
+    fn table(&self) -> &str {
+        &self.table_name
+    }
+
+    fn sql(&self) -> Option<&str> {
+        Some(&self.sql_statement)
+    }
+
+    fn description(&self) -> &str {
+        &self.reason
+    }
+
+    fn reason_code(&self) -> ReasonCode {
+        self.reason_code
+    }
+
+    fn index_columns(&self) -> Option<Vec<String>> {
+        Some(self.column_names.clone())
+    }
+
+    fn index_filter(&self) -> Option<&str> {
+        Some(&self.filter_condition)
+    }
+
+    fn estimated_benefit(&self) -> Option<String> {
+        Some(format!(
+            "~{:.0}% smaller than a full index, performance impact {:.1}",
+            self.estimated_storage_savings, self.performance_impact
+        ))
+    }
+
+    fn rollback_sql(&self, dialect: SqlDialect) -> Option<String> {
+        crate::sql::inverse_create_index(&self.sql_statement, dialect)
+    }
+}
+
+impl Recommendation for RemovalRecommendation {
+    fn priority(&self) -> u32 {
+        (self.confidence_score * 100.0).round().max(0.0) as u32
+    }
+
+    fn table(&self) -> &str {
+        &self.table_name
+    }
+
+    fn sql(&self) -> Option<&str> {
+        Some(&self.sql_statement)
+    }
+
+    fn description(&self) -> &str {
+        &self.removal_reason
+    }
+
+    fn reason_code(&self) -> ReasonCode {
+        self.reason_code
+    }
+
+    fn estimated_benefit(&self) -> Option<String> {
+        Some(format!("~{:.0}% write/storage savings (risk: {})", self.estimated_savings, self.risk_level))
+    }
+
+    // Dropped index ki definition IndexUsageStats mein nahi hai - recreate SQL nahi ban sakta
+}
+
+impl Recommendation for SchemaOptimization {
+    fn priority(&self) -> u32 {
+        self.priority
+    }
+
+    fn table(&self) -> &str {
+        &self.table_name
+    }
+
+    fn sql(&self) -> Option<&str> {
+        Some(&self.sql_statement)
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn reason_code(&self) -> ReasonCode {
+        self.reason_code
+    }
+
+    fn estimated_benefit(&self) -> Option<String> {
+        Some(format!("~{:.0}% estimated benefit", self.estimated_benefit))
+    }
+
+    fn rollback_sql(&self, _dialect: SqlDialect) -> Option<String> {
+        self.rollback_statement.clone() // optimizer ke dialect mein already generate hota hai
+    }
+}
+
+impl Recommendation for DeadlockPrevention {
+    fn priority(&self) -> u32 {
+        match self.risk_level.as_str() {
+            "Critical" => 100,
+            "High" => 80,
+            "Medium" => 60,
+            _ => 40,
+        }
+    }
+
+    fn table(&self) -> &str {
+        self.tables.first().map(|t| t.as_str()).unwrap_or("")
+    }
+
+    fn sql(&self) -> Option<&str> {
+        None
+    }
+
+    fn description(&self) -> &str {
+        &self.prevention_strategy
+    }
+
+    fn reason_code(&self) -> ReasonCode {
+        self.reason_code
+    }
+}
+
+impl Recommendation for PaginationRecommendation {
+    fn priority(&self) -> u32 {
+        self.priority
+    }
+
+    fn table(&self) -> &str {
+        &self.table_name
+    }
+
+    fn sql(&self) -> Option<&str> {
+        None // query rewrite hai, migration mein chalane wala DDL nahi
+    }
+
+    fn description(&self) -> &str {
+        &self.reason
+    }
+
+    fn reason_code(&self) -> ReasonCode {
+        self.reason_code
+    }
+
+    fn estimated_benefit(&self) -> Option<String> {
+        Some(format!(
+            "~{:.0}% fewer rows per run, ~{:.0}ms saved across {} runs",
+            self.estimated_savings_percent, self.estimated_time_saved_ms, self.frequency
+        ))
+    }
+}
+
+/// Merges recommendations from all analyzers into one deduplicated, priority-sorted list
+pub struct RecommendationSet {
+    recommendations: Vec<Box<dyn Recommendation>>,
+    existing_indexes: ExistingIndexChecker,
+}
+
+impl Default for RecommendationSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RecommendationSet {
+    pub fn new() -> Self {
+        Self::with_existing_indexes(ExistingIndexChecker::new())
+    }
+
+    pub fn with_existing_indexes(existing_indexes: ExistingIndexChecker) -> Self {
+        Self {
+            recommendations: Vec::new(),
+            existing_indexes,
+        }
+    }
+
+    pub fn add<R: Recommendation + 'static>(&mut self, recommendation: R) {
+        self.recommendations.push(Box::new(recommendation));
+    }
+
+    pub fn extend<R: Recommendation + 'static>(&mut self, recommendations: Vec<R>) {
+        for recommendation in recommendations {
+            self.add(recommendation);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.recommendations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recommendations.is_empty()
+    }
+
+    //yaha pe badme conflict resolution strategies bhi add karna ha
+    pub fn into_sorted(self) -> Vec<Box<dyn Recommendation>> {
+        let mut recommendations = self.recommendations;
+        recommendations.sort_by_key(|rec| std::cmp::Reverse(rec.priority()));
+
+        // Highest priority wins - accepted index suggestions are registered so that lower
+        // priority duplicates or redundant prefixes of them get dropped
+        let mut accepted_indexes = ExistingIndexChecker::new();
+        let mut result: Vec<Box<dyn Recommendation>> = Vec::new();
+
+        for recommendation in recommendations {
+            if let Some(columns) = recommendation.index_columns() {
+                let table = recommendation.table();
+                let filter = recommendation.index_filter();
+                if Self::is_covered(&self.existing_indexes, table, &columns, filter)
+                    || Self::is_covered(&accepted_indexes, table, &columns, filter)
+                {
+                    continue;
+                }
+
+                accepted_indexes.add_existing_index(ExistingIndex {
+                    table_name: table.to_string(),
+                    column_names: columns,
+                    index_name: format!("recommendation_{}", result.len()),
+                    index_type: "btree".to_string(),
+                    is_unique: false,
+                    is_partial: filter.is_some(),
+                    filter_condition: filter.map(|f| f.to_string()),
+                });
+            } else if result.iter().any(|r| {
+                r.table() == recommendation.table()
+                    && r.sql() == recommendation.sql()
+                    && r.description() == recommendation.description()
+            }) {
+                continue;
+            }
+
+            result.push(recommendation);
+        }
+
+        result
+    }
+
+    fn is_covered(
+        checker: &ExistingIndexChecker,
+        table: &str,
+        columns: &[String],
+        filter: Option<&str>,
+    ) -> bool {
+        checker
+            .check_for_conflicts_with_filter(table, columns, filter)
+            .iter()
+            .any(|c| c.conflict_type == "duplicate" || c.conflict_type == "prefix_redundant")
+    }
+}
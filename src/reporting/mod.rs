@@ -0,0 +1,3 @@
+pub mod table_report;
+
+pub use table_report::{build_table_reports, TableReport};
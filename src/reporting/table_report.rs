@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use crate::analyzer::{
+    ColumnTracker, ExistingIndex, ExistingIndexChecker, IndexRemovalRecommender, IndexUsageStats,
+    PatternAnalyzer, QueryLog, QueryParser, RemovalRecommendation, SchemaOptimizer, TableSchema,
+};
+use crate::recommender::{IndexRecommendation, IndexRecommender};
+
+// AnalysisEngine::new() wale hi thresholds
+const SLOWNESS_THRESHOLD: f64 = 1000.0;
+const FREQUENCY_THRESHOLD: u64 = 10;
+
+/// Per-table rollup - DBA ka view, saare analyzers ka output ek table ke liye
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TableReport {
+    pub table_name: String,
+    pub total_queries: u64,
+    pub avg_execution_time_ms: f64,
+    pub existing_index_count: usize,
+    pub recommended_indexes: Vec<IndexRecommendation>, // existing indexes se covered wale hata diye
+    pub removal_candidates: Vec<RemovalRecommendation>,
+    pub schema_health_score: Option<f64>, // None jab table ka schema nahi diya
+}
+
+impl TableReport {
+    fn new(table_name: &str) -> Self {
+        Self {
+            table_name: table_name.to_string(),
+            total_queries: 0,
+            avg_execution_time_ms: 0.0,
+            existing_index_count: 0,
+            recommended_indexes: Vec::new(),
+            removal_candidates: Vec::new(),
+            schema_health_score: None,
+        }
+    }
+}
+
+/// Table name -> TableReport. PatternAnalyzer, ColumnTracker, ExistingIndexChecker,
+/// IndexRecommender, IndexRemovalRecommender aur SchemaOptimizer ko ek saath chalata hai
+//yaha pe badme partial index aur join recommendations bhi add karna ha
+pub fn build_table_reports(
+    logs: &[QueryLog],
+    schemas: &[TableSchema],
+    existing_indexes: &[ExistingIndex],
+) -> HashMap<String, TableReport> {
+    let parser = QueryParser;
+    let mut pattern_analyzer = PatternAnalyzer::new();
+    let mut column_tracker = ColumnTracker::new();
+
+    for log in logs {
+        let parsed = parser.parse(&log.query);
+        column_tracker.track_usage(&parsed, log.execution_time_ms);
+
+        // Raw log files mein tables_accessed khali hota hai - parser ke FROM tables se bhar dete hai
+        let mut log = log.clone();
+        for table in parsed.from_tables {
+            if !log.tables_accessed.contains(&table) {
+                log.tables_accessed.push(table);
+            }
+        }
+        pattern_analyzer.add_log(log);
+    }
+
+    let mut reports: HashMap<String, TableReport> = HashMap::new();
+
+    // Ek pattern jitni tables touch karta hai, sab ke totals mein count hota hai
+    let patterns = pattern_analyzer.analyze_by_fingerprint();
+    for pattern in &patterns {
+        for table in &pattern.tables {
+            let report = reports.entry(table.clone()).or_insert_with(|| TableReport::new(table));
+            let total_time = report.avg_execution_time_ms * report.total_queries as f64
+                + pattern.avg_execution_time_ms * pattern.frequency as f64;
+            report.total_queries += pattern.frequency;
+            report.avg_execution_time_ms = total_time / report.total_queries as f64;
+        }
+    }
+
+    let mut checker = ExistingIndexChecker::new();
+    for index in existing_indexes {
+        checker.add_existing_index(index.clone());
+        reports
+            .entry(index.table_name.clone())
+            .or_insert_with(|| TableReport::new(&index.table_name))
+            .existing_index_count += 1;
+    }
+
+    let recommender = IndexRecommender::new(SLOWNESS_THRESHOLD, FREQUENCY_THRESHOLD);
+    let (new_recommendations, _) =
+        IndexRecommender::split_covered(recommender.recommend_with_usage(&patterns, &column_tracker), &checker);
+    for recommendation in new_recommendations {
+        reports
+            .entry(recommendation.table.clone())
+            .or_insert_with(|| TableReport::new(&recommendation.table))
+            .recommended_indexes
+            .push(recommendation);
+    }
+
+    let usage_stats = IndexUsageStats::from_logs(existing_indexes, logs);
+    for candidate in IndexRemovalRecommender::new().analyze_index_usage(&usage_stats) {
+        reports
+            .entry(candidate.table_name.clone())
+            .or_insert_with(|| TableReport::new(&candidate.table_name))
+            .removal_candidates
+            .push(candidate);
+    }
+
+    let schema_optimizer = SchemaOptimizer::new();
+    for schema in schemas {
+        reports
+            .entry(schema.table_name.clone())
+            .or_insert_with(|| TableReport::new(&schema.table_name))
+            .schema_health_score = Some(schema_optimizer.get_schema_health_score(std::slice::from_ref(schema)));
+    }
+
+    reports
+}
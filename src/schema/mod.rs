@@ -0,0 +1,3 @@
+pub mod result_schemas;
+
+pub use result_schemas::{result_type_schemas, RootSchema};
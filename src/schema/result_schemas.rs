@@ -0,0 +1,47 @@
+pub use schemars::schema::RootSchema;
+use schemars::schema_for;
+
+use crate::analyzer::{
+    AnomalyResult, BudgetBreach, ColumnUsage, DeadlockPrevention, IndexConflict, IndexSimulation,
+    JoinChain, JoinPattern, LockOrderViolation, NonSargablePredicate, PaginationRecommendation, PartialIndexRecommendation,
+    PerformanceAlert, PerformanceMetric, PriorityScore, QueryCost, QueryLog, QueryPattern,
+    RemovalImpact, RemovalRecommendation, SchemaOptimization, TimePattern, TypeMismatch,
+};
+use crate::engine::AnalysisReport;
+use crate::predictor::PerformancePrediction;
+use crate::reporting::TableReport;
+use crate::recommender::IndexRecommendation;
+
+/// Har public result type ka JSON Schema (type name, schema) - front-end codegen ke liye
+//yaha pe badme OpenAPI document bhi generate karna ha
+pub fn result_type_schemas() -> Vec<(&'static str, RootSchema)> {
+    vec![
+        ("AnalysisReport", schema_for!(AnalysisReport)),
+        ("TableReport", schema_for!(TableReport)),
+        ("QueryLog", schema_for!(QueryLog)),
+        ("QueryPattern", schema_for!(QueryPattern)),
+        ("ColumnUsage", schema_for!(ColumnUsage)),
+        ("TimePattern", schema_for!(TimePattern)),
+        ("JoinPattern", schema_for!(JoinPattern)),
+        ("JoinChain", schema_for!(JoinChain)),
+        ("QueryCost", schema_for!(QueryCost)),
+        ("BudgetBreach", schema_for!(BudgetBreach)),
+        ("AnomalyResult", schema_for!(AnomalyResult)),
+        ("PriorityScore", schema_for!(PriorityScore)),
+        ("NonSargablePredicate", schema_for!(NonSargablePredicate)),
+        ("TypeMismatch", schema_for!(TypeMismatch)),
+        ("IndexRecommendation", schema_for!(IndexRecommendation)),
+        ("IndexConflict", schema_for!(IndexConflict)),
+        ("IndexSimulation", schema_for!(IndexSimulation)),
+        ("PartialIndexRecommendation", schema_for!(PartialIndexRecommendation)),
+        ("PaginationRecommendation", schema_for!(PaginationRecommendation)),
+        ("RemovalRecommendation", schema_for!(RemovalRecommendation)),
+        ("RemovalImpact", schema_for!(RemovalImpact)),
+        ("SchemaOptimization", schema_for!(SchemaOptimization)),
+        ("DeadlockPrevention", schema_for!(DeadlockPrevention)),
+        ("LockOrderViolation", schema_for!(LockOrderViolation)),
+        ("PerformanceMetric", schema_for!(PerformanceMetric)),
+        ("PerformanceAlert", schema_for!(PerformanceAlert)),
+        ("PerformancePrediction", schema_for!(PerformancePrediction)),
+    ]
+}
@@ -0,0 +1,5 @@
+pub mod rollback;
+pub mod validator;
+
+pub use rollback::{drop_index_sql, inverse_create_index};
+pub use validator::{validate, SqlSyntaxError};
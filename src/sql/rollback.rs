@@ -0,0 +1,40 @@
+use crate::analyzer::SqlDialect;
+
+/// DROP INDEX for the dialect - MySQL ko table ka naam bhi chahiye, Postgres/SQLite ko nahi
+pub fn drop_index_sql(index_name: &str, table: &str, dialect: SqlDialect) -> String {
+    match dialect {
+        SqlDialect::MySql => format!("DROP INDEX {} ON {}", index_name, table),
+        SqlDialect::Postgres | SqlDialect::Sqlite => format!("DROP INDEX {}", index_name),
+    }
+}
+
+/// "CREATE [UNIQUE] INDEX [CONCURRENTLY] [IF NOT EXISTS] name ON table ..." ka inverse DROP INDEX -
+/// statement CREATE INDEX na ho ya naam na mile to None
+pub fn inverse_create_index(create_sql: &str, dialect: SqlDialect) -> Option<String> {
+    let words: Vec<&str> = create_sql.split_whitespace().collect();
+    if !words.first()?.eq_ignore_ascii_case("CREATE") {
+        return None;
+    }
+
+    let mut position = 1;
+    if words.get(position)?.eq_ignore_ascii_case("UNIQUE") {
+        position += 1;
+    }
+    if !words.get(position)?.eq_ignore_ascii_case("INDEX") {
+        return None;
+    }
+    position += 1;
+    while words
+        .get(position)
+        .is_some_and(|word| ["CONCURRENTLY", "IF", "NOT", "EXISTS"].iter().any(|k| word.eq_ignore_ascii_case(k)))
+    {
+        position += 1;
+    }
+
+    let index_name = words.get(position)?;
+    if !words.get(position + 1)?.eq_ignore_ascii_case("ON") {
+        return None; // CREATE INDEX ON t (c) - naam Postgres generate karta hai
+    }
+    let table = words.get(position + 2)?;
+    Some(drop_index_sql(index_name, table, dialect))
+}
@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::fmt;
+
+const STATEMENT_KEYWORDS: &[&str] = &[
+    "CREATE", "ALTER", "DROP", "SELECT", "INSERT", "UPDATE", "DELETE", "WITH", "SET",
+    "ANALYZE", "VACUUM", "REINDEX", "CLUSTER", "BEGIN", "COMMIT",
+];
+
+// In keywords ke baad identifier (table / column / index name) aana hi chahiye
+const IDENTIFIER_KEYWORDS: &[&str] = &["TABLE", "INDEX", "COLUMN", "ON", "INTO", "FROM", "REFERENCES"];
+
+// In keywords ke baad reserved word bhi naam ho sakta hai - column "type", table "index" etc.
+const NAME_KEYWORDS: &[&str] = &["TABLE", "INDEX", "COLUMN", "ON"];
+
+// Identifier ki jagah ye mile to naam missing hai - "ALTER TABLE  ALTER COLUMN" wala bug
+const RESERVED_WORDS: &[&str] = &[
+    "ALTER", "ADD", "DROP", "MODIFY", "TABLE", "INDEX", "COLUMN", "ON", "SET", "TYPE", "WHERE",
+    "USING", "INCLUDE", "FROM", "INTO", "VALUES", "SELECT", "AND", "OR", "NOT", "NULL",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum SqlSyntaxError {
+    Empty,
+    UnknownStatement { keyword: String },
+    UnbalancedParens { position: usize },
+    EmptyParens { position: usize },
+    UnterminatedQuote { quote: char, position: usize },
+    MissingIdentifier { after: String },
+}
+
+impl fmt::Display for SqlSyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SqlSyntaxError::Empty => write!(f, "empty SQL statement"),
+            SqlSyntaxError::UnknownStatement { keyword } => write!(f, "unrecognized statement keyword '{}'", keyword),
+            SqlSyntaxError::UnbalancedParens { position } => write!(f, "unbalanced parenthesis at byte {}", position),
+            SqlSyntaxError::EmptyParens { position } => write!(f, "empty parenthesized list at byte {}", position),
+            SqlSyntaxError::UnterminatedQuote { quote, position } => {
+                write!(f, "unterminated {} quote starting at byte {}", quote, position)
+            }
+            SqlSyntaxError::MissingIdentifier { after } => write!(f, "missing identifier after {}", after),
+        }
+    }
+}
+
+impl std::error::Error for SqlSyntaxError {}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Word(String),  // uppercase keyword ya bare identifier
+    QuotedIdentifier,
+    Literal,
+    Symbol(char),
+}
+
+/// Lightweight dry-run check - parser nahi hai, sirf generated SQL ki common galtiyan pakadta hai:
+/// balanced parens/quotes, non-empty identifiers aur recognized leading keyword.
+/// Sirf "--" comments wala statement valid hai (schema optimizer hints ke liye)
+pub fn validate(statement: &str) -> Result<(), SqlSyntaxError> {
+    let tokens = tokenize(statement)?;
+    if tokens.is_empty() {
+        return if statement.trim().is_empty() { Err(SqlSyntaxError::Empty) } else { Ok(()) };
+    }
+
+    match &tokens[0] {
+        Token::Word(keyword) if STATEMENT_KEYWORDS.contains(&keyword.as_str()) => {}
+        Token::Word(keyword) => return Err(SqlSyntaxError::UnknownStatement { keyword: keyword.clone() }),
+        _ => return Err(SqlSyntaxError::UnknownStatement { keyword: String::new() }),
+    }
+
+    let is_drop = tokens[0] == Token::Word("DROP".to_string());
+    let is_index_statement = tokens.get(1) == Some(&Token::Word("INDEX".to_string()))
+        || tokens.get(2) == Some(&Token::Word("INDEX".to_string()));
+    for (i, token) in tokens.iter().enumerate() {
+        let Token::Word(keyword) = token else {
+            continue;
+        };
+        if !IDENTIFIER_KEYWORDS.contains(&keyword.as_str()) {
+            continue;
+        }
+        // CREATE INDEX ON t (c) Postgres mein valid hai, DROP INDEX ON nahi
+        if keyword == "INDEX" && !is_drop && tokens.get(i + 1) == Some(&Token::Word("ON".to_string())) {
+            continue;
+        }
+
+        let mut next = i + 1;
+        // IF [NOT] EXISTS / CONCURRENTLY identifier se pehle aa sakte hai
+        while let Some(Token::Word(word)) = tokens.get(next) {
+            if matches!(word.as_str(), "IF" | "NOT" | "EXISTS" | "CONCURRENTLY" | "ONLY") {
+                next += 1;
+            } else {
+                break;
+            }
+        }
+
+        let has_identifier = match tokens.get(next) {
+            Some(Token::Word(word)) if RESERVED_WORDS.contains(&word.as_str()) => {
+                // ADD / MODIFY COLUMN ke baad hamesha naam aata hai, phir type
+                let defines_column = keyword == "COLUMN"
+                    && i > 0
+                    && matches!(&tokens[i - 1], Token::Word(prev) if prev == "ADD" || prev == "MODIFY");
+                NAME_KEYWORDS.contains(&keyword.as_str())
+                    && (defines_column || is_reserved_name(tokens.get(next + 1)))
+            }
+            Some(Token::Word(_)) => true,
+            Some(Token::QuotedIdentifier) => true,
+            // FROM (subquery) / JOIN ... ON (a = b) - lekin CREATE INDEX ... ON ke baad table chahiye
+            Some(Token::Symbol('(')) => keyword == "FROM" || (keyword == "ON" && !is_index_statement),
+            _ => false,
+        };
+        if !has_identifier {
+            return Err(SqlSyntaxError::MissingIdentifier { after: keyword.clone() });
+        }
+    }
+
+    Ok(())
+}
+
+// "ALTER COLUMN type TYPE INT" - reserved word ke baad clause keyword / symbol / end aaye to wo naam hai.
+// "ALTER COLUMN TYPE INT" (naam missing) mein baad mein plain word aata hai, aur
+// "ALTER TABLE ALTER COLUMN" mein identifier keyword
+fn is_reserved_name(following: Option<&Token>) -> bool {
+    match following {
+        None | Some(Token::Symbol(_)) => true,
+        Some(Token::Word(word)) => {
+            RESERVED_WORDS.contains(&word.as_str()) && !IDENTIFIER_KEYWORDS.contains(&word.as_str())
+        }
+        _ => false,
+    }
+}
+
+fn tokenize(statement: &str) -> Result<Vec<Token>, SqlSyntaxError> {
+    let mut tokens = Vec::new();
+    let mut open_parens: Vec<usize> = Vec::new();
+    let mut call_parens: Vec<bool> = Vec::new(); // "now(" - naam se chipka hua paren function call hai
+    let mut chars = statement.char_indices().peekable();
+
+    while let Some((position, c)) = chars.next() {
+        match c {
+            '-' if chars.peek().map(|(_, next)| *next) == Some('-') => {
+                // Line comment - newline tak skip
+                for (_, next) in chars.by_ref() {
+                    if next == '\n' {
+                        break;
+                    }
+                }
+            }
+            '\'' | '"' | '`' => {
+                let mut closed = false;
+                while let Some((_, next)) = chars.next() {
+                    if next == c {
+                        // '' / "" escape - quote abhi band nahi hua
+                        if chars.peek().map(|(_, n)| *n) == Some(c) {
+                            chars.next();
+                            continue;
+                        }
+                        closed = true;
+                        break;
+                    }
+                }
+                if !closed {
+                    return Err(SqlSyntaxError::UnterminatedQuote { quote: c, position });
+                }
+                tokens.push(if c == '\'' { Token::Literal } else { Token::QuotedIdentifier });
+            }
+            '(' => {
+                let is_call = statement[..position]
+                    .chars()
+                    .next_back()
+                    .is_some_and(|prev| prev.is_alphanumeric() || prev == '_');
+                open_parens.push(position);
+                call_parens.push(is_call);
+                tokens.push(Token::Symbol('('));
+            }
+            ')' => {
+                if open_parens.pop().is_none() {
+                    return Err(SqlSyntaxError::UnbalancedParens { position });
+                }
+                let is_call = call_parens.pop().unwrap_or(false);
+                // now() / count() valid hai, "ON t ()" jaisi khaali column list nahi
+                if tokens.last() == Some(&Token::Symbol('(')) && !is_call {
+                    return Err(SqlSyntaxError::EmptyParens { position });
+                }
+                tokens.push(Token::Symbol(')'));
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut word = c.to_string();
+                while let Some((_, next)) = chars.peek() {
+                    if next.is_alphanumeric() || *next == '_' || *next == '.' || *next == '$' {
+                        word.push(*next);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(if word.starts_with(|c: char| c.is_ascii_digit()) {
+                    Token::Literal
+                } else {
+                    Token::Word(word.to_uppercase())
+                });
+            }
+            c if c.is_whitespace() => {}
+            c => tokens.push(Token::Symbol(c)),
+        }
+    }
+
+    if let Some(position) = open_parens.pop() {
+        return Err(SqlSyntaxError::UnbalancedParens { position });
+    }
+
+    Ok(tokens)
+}
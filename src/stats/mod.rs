@@ -0,0 +1,3 @@
+pub mod table_statistics;
+
+pub use table_statistics::{ColumnStatistics, StatsParseError, TableStatistics};
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+use crate::analyzer::log_parser::read_csv_records;
+use crate::analyzer::{ColumnStats, LogParseError};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum StatsParseError {
+    Csv(LogParseError),
+    MissingHeader { column: String },
+    InvalidNumber { line: usize, column: String, value: String },
+}
+
+impl fmt::Display for StatsParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatsParseError::Csv(err) => write!(f, "{}", err),
+            StatsParseError::MissingHeader { column } => write!(f, "pg_stats header has no '{}' column", column),
+            StatsParseError::InvalidNumber { line, column, value } => {
+                write!(f, "line {}: invalid {} '{}'", line, column, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StatsParseError {}
+
+/// One pg_stats row - planner ke real column statistics
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ColumnStatistics {
+    pub column_name: String,
+    pub null_frac: f64,
+    pub n_distinct: f64, // pg convention: > 0 = absolute count, < 0 = -(fraction of rows), e.g. -1 = unique
+    pub most_common_vals: Vec<String>,
+    pub most_common_freqs: Vec<f64>, // most_common_vals ke saath index-aligned
+}
+
+impl ColumnStatistics {
+    /// Absolute distinct count - negative n_distinct ko total_rows se scale karta hai
+    pub fn distinct_values(&self, total_rows: u64) -> u64 {
+        if self.n_distinct < 0.0 {
+            (-self.n_distinct * total_rows as f64).round() as u64
+        } else {
+            self.n_distinct.round() as u64
+        }
+    }
+
+    /// "col = value" ki selectivity - MCV list mein ho to uski frequency, warna baaki rows
+    /// non-MCV distinct values mein barabar bati hui maan lete hai (planner jaisa hi)
+    pub fn equality_selectivity(&self, value: Option<&str>, total_rows: u64) -> f64 {
+        if let Some(position) = value.and_then(|v| self.most_common_vals.iter().position(|mcv| mcv == v))
+            && let Some(freq) = self.most_common_freqs.get(position) {
+                return *freq;
+            }
+
+        let mcv_total: f64 = self.most_common_freqs.iter().sum();
+        let remaining_fraction = (1.0 - self.null_frac - mcv_total).max(0.0);
+        let remaining_distinct = self.distinct_values(total_rows).saturating_sub(self.most_common_vals.len() as u64).max(1);
+        remaining_fraction / remaining_distinct as f64
+    }
+
+    // '' / 0 / N/A placeholders jo MCV list mein dikh rahe hai
+    fn sentinel_fraction(&self) -> f64 {
+        self.most_common_vals
+            .iter()
+            .zip(&self.most_common_freqs)
+            .filter(|(value, _)| matches!(value.as_str(), "" | "0" | "N/A"))
+            .map(|(_, freq)| freq)
+            .sum()
+    }
+
+    // MCV list saari non-null rows cover kare tabhi wo column ki poori value list hai
+    fn complete_values(&self) -> Vec<String> {
+        let mcv_total: f64 = self.most_common_freqs.iter().sum();
+        if self.most_common_vals.is_empty() || self.null_frac + mcv_total < 0.999 {
+            return Vec::new();
+        }
+        self.most_common_vals.clone()
+    }
+}
+
+/// Per-table statistics from a pg_stats dump - PartialIndexRecommender, IndexUsageSimulator aur
+/// SchemaOptimizer ke hardcoded guesses ki jagah
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TableStatistics {
+    pub table_name: String,
+    #[serde(default)]
+    pub schema_name: Option<String>, // pg_stats schemaname - public.orders aur archive.orders alag tables hai
+    pub columns: HashMap<String, ColumnStatistics>,
+}
+
+impl TableStatistics {
+    pub fn new(table_name: &str) -> Self {
+        Self {
+            table_name: table_name.to_string(),
+            schema_name: None,
+            columns: HashMap::new(),
+        }
+    }
+
+    /// "schema.table", schema pata na ho to sirf table
+    pub fn qualified_name(&self) -> String {
+        match &self.schema_name {
+            Some(schema) => format!("{}.{}", schema, self.table_name),
+            None => self.table_name.clone(),
+        }
+    }
+
+    /// `\copy (SELECT * FROM pg_stats) TO 'stats.csv' CSV HEADER` ka output - columns header se
+    /// naam se dhoondhe jate hai. inherited = true rows skip (parent + children ka combined view)
+    pub fn from_pg_stats_csv(csv: &str) -> Result<Vec<TableStatistics>, StatsParseError> {
+        let records = read_csv_records(csv).map_err(StatsParseError::Csv)?;
+        let Some(((_, header), rows)) = records.split_first() else {
+            return Ok(Vec::new());
+        };
+
+        let position = |name: &str| header.iter().position(|h| h.trim() == name);
+        let required = |name: &str| position(name).ok_or_else(|| StatsParseError::MissingHeader { column: name.to_string() });
+        let schema_col = position("schemaname");
+        let table_col = required("tablename")?;
+        let column_col = required("attname")?;
+        let null_frac_col = required("null_frac")?;
+        let n_distinct_col = required("n_distinct")?;
+        let inherited_col = position("inherited");
+        let mcv_col = position("most_common_vals");
+        let mcf_col = position("most_common_freqs");
+
+        let mut tables: HashMap<(String, String), TableStatistics> = HashMap::new();
+        for (line, fields) in rows {
+            let field = |index: usize| fields.get(index).map(|f| f.trim()).unwrap_or("");
+            if inherited_col.is_some_and(|i| matches!(field(i), "t" | "true")) {
+                continue;
+            }
+
+            let number = |index: usize, column: &str| -> Result<f64, StatsParseError> {
+                field(index).parse().map_err(|_| StatsParseError::InvalidNumber {
+                    line: *line,
+                    column: column.to_string(),
+                    value: field(index).to_string(),
+                })
+            };
+            let most_common_freqs = parse_pg_array(mcf_col.map(field).unwrap_or(""))
+                .iter()
+                .map(|freq| {
+                    freq.parse().map_err(|_| StatsParseError::InvalidNumber {
+                        line: *line,
+                        column: "most_common_freqs".to_string(),
+                        value: freq.clone(),
+                    })
+                })
+                .collect::<Result<Vec<f64>, _>>()?;
+
+            let column = ColumnStatistics {
+                column_name: field(column_col).to_string(),
+                null_frac: number(null_frac_col, "null_frac")?,
+                n_distinct: number(n_distinct_col, "n_distinct")?,
+                most_common_vals: parse_pg_array(mcv_col.map(field).unwrap_or("")),
+                most_common_freqs,
+            };
+
+            let table_name = field(table_col);
+            let schema_name = schema_col.map(field).unwrap_or("");
+            tables
+                .entry((schema_name.to_string(), table_name.to_string()))
+                .or_insert_with(|| TableStatistics {
+                    schema_name: (!schema_name.is_empty()).then(|| schema_name.to_string()),
+                    ..TableStatistics::new(table_name)
+                })
+                .columns
+                .insert(column.column_name.clone(), column);
+        }
+
+        let mut tables: Vec<TableStatistics> = tables.into_values().collect();
+        tables.sort_by(|a, b| a.table_name.cmp(&b.table_name).then_with(|| a.schema_name.cmp(&b.schema_name)));
+        Ok(tables)
+    }
+
+    pub fn column(&self, column_name: &str) -> Option<&ColumnStatistics> {
+        self.columns.get(column_name)
+    }
+
+    /// Existing analyzers ka ColumnStats format, "table.column" keys ke saath - pg_stats mein
+    /// row count nahi hota, isliye total_rows (pg_class.reltuples) caller deta hai
+    pub fn to_column_stats(&self, total_rows: u64) -> HashMap<String, ColumnStats> {
+        self.columns
+            .values()
+            .map(|column| {
+                let stats = ColumnStats {
+                    distinct_values: column.distinct_values(total_rows),
+                    total_rows,
+                    null_fraction: column.null_frac,
+                    sentinel_fraction: column.sentinel_fraction(),
+                    observed_values: column.complete_values(),
+                };
+                (format!("{}.{}", self.table_name, column.column_name), stats)
+            })
+            .collect()
+    }
+}
+
+// Postgres array literal: {a,b,"c d","e\"f",NULL} -> ["a", "b", "c d", "e\"f", "NULL"]
+fn parse_pg_array(literal: &str) -> Vec<String> {
+    let Some(inner) = literal.trim().strip_prefix('{').and_then(|rest| rest.strip_suffix('}')) else {
+        return Vec::new();
+    };
+
+    let mut values = Vec::new();
+    let mut value = String::new();
+    let mut in_quotes = false;
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if in_quotes => {
+                if let Some(escaped) = chars.next() {
+                    value.push(escaped);
+                }
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => values.push(std::mem::take(&mut value)),
+            _ => value.push(c),
+        }
+    }
+    if !inner.is_empty() {
+        values.push(value);
+    }
+    values
+}
@@ -0,0 +1,3 @@
+pub mod synthetic_logs;
+
+pub use synthetic_logs::{generate_synthetic_logs, LatencyDistribution, WorkloadConfig};
@@ -0,0 +1,142 @@
+use crate::analyzer::QueryLog;
+
+/// Fast queries ki latency kaise distribute hoti hai - slow queries isi ko slow_multiplier se scale karti hai
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LatencyDistribution {
+    Uniform { min_ms: u64, max_ms: u64 },
+    Exponential { mean_ms: f64 },
+    LogNormal { median_ms: f64, sigma: f64 }, // real workloads ki long tail ke sabse kareeb
+}
+
+/// Synthetic workload ka shape - benchmarks aur property tests ke liye
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkloadConfig {
+    pub table_count: usize,
+    pub slow_query_fraction: f64, // 0.0 to 1.0
+    pub write_fraction: f64,      // INSERT / UPDATE / DELETE ka hissa, baaki SELECT
+    pub latency: LatencyDistribution,
+    pub slow_multiplier: f64, // slow query = normal latency * ye
+    pub start_timestamp: u64,
+    pub interval_secs: u64, // consecutive logs ke beech
+}
+
+impl Default for WorkloadConfig {
+    fn default() -> Self {
+        Self {
+            table_count: 8,
+            slow_query_fraction: 0.05,
+            write_fraction: 0.2,
+            latency: LatencyDistribution::LogNormal { median_ms: 5.0, sigma: 1.0 },
+            slow_multiplier: 100.0,
+            start_timestamp: 1_700_000_000,
+            interval_secs: 1,
+        }
+    }
+}
+
+const STATUSES: [&str; 4] = ["pending", "paid", "shipped", "cancelled"];
+
+// SplitMix64 - chhota, fast aur har platform pe same sequence; rand dependency ki zarurat nahi
+struct SyntheticRng {
+    state: u64,
+}
+
+impl SyntheticRng {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound.max(1)
+    }
+
+    // Box-Muller standard normal
+    fn next_normal(&mut self) -> f64 {
+        let u1 = self.next_f64().max(f64::MIN_POSITIVE);
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
+
+/// Same seed + config = same logs, har run aur platform pe. Table popularity skewed hai (table_0
+/// sabse hot), taaki PatternAnalyzer ko realistic hot/cold templates mile
+pub fn generate_synthetic_logs(seed: u64, count: usize, config: &WorkloadConfig) -> Vec<QueryLog> {
+    let mut rng = SyntheticRng::new(seed);
+    let table_count = config.table_count.max(1) as u64;
+
+    (0..count)
+        .map(|i| {
+            // u^2 skew - low index tables zyada baar aate hai
+            let skewed = rng.next_f64() * rng.next_f64();
+            let table = format!("table_{}", (skewed * table_count as f64) as u64);
+            let other = format!("table_{}", rng.below(table_count));
+            let id = rng.below(1_000_000);
+            let status = STATUSES[rng.below(STATUSES.len() as u64) as usize];
+            let is_slow = rng.next_f64() < config.slow_query_fraction;
+            let is_write = rng.next_f64() < config.write_fraction;
+
+            let (query, mut tables) = if is_write {
+                let query = match rng.below(3) {
+                    0 => format!("INSERT INTO {} (id, status) VALUES ({}, '{}')", table, id, status),
+                    1 => format!("UPDATE {} SET status = '{}' WHERE id = {}", table, status, id),
+                    _ => format!("DELETE FROM {} WHERE id = {}", table, id),
+                };
+                (query, vec![table])
+            } else {
+                match rng.below(4) {
+                    0 => (format!("SELECT * FROM {} WHERE id = {}", table, id), vec![table]),
+                    1 => (
+                        format!("SELECT id, status FROM {} WHERE status = '{}' AND created_at > {}", table, status, id),
+                        vec![table],
+                    ),
+                    2 => (
+                        format!(
+                            "SELECT a.id, b.status FROM {} a JOIN {} b ON a.{}_id = b.id WHERE a.id = {}",
+                            table, other, other, id
+                        ),
+                        vec![table, other],
+                    ),
+                    _ => (format!("SELECT status, COUNT(*) FROM {} GROUP BY status", table), vec![table]),
+                }
+            };
+            tables.dedup();
+
+            let base_latency = sample_latency(&mut rng, config.latency);
+            let execution_time_ms = if is_slow {
+                (base_latency * config.slow_multiplier.max(1.0)).round() as u64
+            } else {
+                base_latency.round() as u64
+            };
+            let rows_scanned = if is_slow { 100_000 + rng.below(900_000) } else { 1 + rng.below(100) };
+            let rows_returned = 1 + rng.below(50);
+            let timestamp = config.start_timestamp + i as u64 * config.interval_secs;
+
+            QueryLog::new(query, execution_time_ms.max(1), timestamp, tables, rows_scanned)
+                .with_rows_returned(rows_returned)
+        })
+        .collect()
+}
+
+fn sample_latency(rng: &mut SyntheticRng, distribution: LatencyDistribution) -> f64 {
+    match distribution {
+        LatencyDistribution::Uniform { min_ms, max_ms } => {
+            let (low, high) = (min_ms.min(max_ms), min_ms.max(max_ms));
+            (low + rng.below((high - low).saturating_add(1))) as f64
+        }
+        LatencyDistribution::Exponential { mean_ms } => -mean_ms.max(0.0) * (1.0 - rng.next_f64()).ln(),
+        LatencyDistribution::LogNormal { median_ms, sigma } => median_ms.max(0.0) * (sigma * rng.next_normal()).exp(),
+    }
+}